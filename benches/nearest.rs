@@ -0,0 +1,136 @@
+//! Times the greedy nearest color chain of `order::sort_languages` against a
+//! copy that memoizes the pairwise distances. The chain never asks for the
+//! same pair twice, so the memoized copy gets no hits and only pays for the
+//! hashing, which is why `order::nearest_colors` computes every distance
+//! directly.
+//!
+//! Run with `cargo bench --bench nearest`.
+
+use std::collections::{
+    BTreeMap,
+    HashMap,
+};
+use std::hint::black_box;
+use std::time::{
+    Duration,
+    Instant,
+};
+
+use language_colors::color::Color;
+use language_colors::order::{
+    sort_languages,
+    DistanceMetric,
+    Order,
+};
+
+const SIZES: &[usize] = &[100, 500, 2000];
+const RUNS: usize = 5;
+
+/// `count` colors spread over the rgb cube, the same on every run.
+fn colors(count: usize) -> Vec<Color> {
+    (0..count)
+        .map(|index| {
+            let rgb = (index as u64 * 2_654_435_761) & 0xFF_FFFF;
+            Color::from_webcolor(&format!("#{:06X}", rgb)).unwrap()
+        })
+        .collect()
+}
+
+/// The chain of `order::Order::Nearest` over indices with every distance
+/// memoized for both `(a, b)` and `(b, a)`, returns the chain and the number
+/// of cache hits.
+fn memoized_chain(colors: &[Color], metric: DistanceMetric) -> (Vec<usize>, usize) {
+    let mut distances: HashMap<(usize, usize), f64> = HashMap::new();
+    let mut hits = 0;
+    let mut distance = |a: usize, b: usize| {
+        let key = (a.min(b), a.max(b));
+        if let Some(distance) = distances.get(&key) {
+            hits += 1;
+            return *distance;
+        }
+
+        *distances
+            .entry(key)
+            .or_insert_with(|| metric.distance(&colors[a], &colors[b]))
+    };
+
+    let mut used = vec![false; colors.len()];
+    let mut current = 0;
+    used[current] = true;
+    let mut chain = vec![current];
+
+    while chain.len() < colors.len() {
+        let mut shortest: Option<(usize, f64)> = None;
+        for candidate in (0..colors.len()).filter(|&candidate| !used[candidate]) {
+            let distance = distance(current, candidate);
+
+            match shortest {
+                Some((_, nearest_distance)) if nearest_distance <= distance => {}
+                _ => shortest = Some((candidate, distance)),
+            }
+        }
+
+        let (nearest, _) = shortest.expect("unused colors left");
+        used[nearest] = true;
+        chain.push(nearest);
+        current = nearest;
+    }
+
+    (chain, hits)
+}
+
+/// Fastest of `RUNS` runs of `f` and its last result.
+fn time<T>(mut f: impl FnMut() -> T) -> (Duration, T) {
+    let mut fastest = Duration::MAX;
+    let mut result = None;
+
+    for _ in 0..RUNS {
+        let started = Instant::now();
+        let value = black_box(f());
+        fastest = fastest.min(started.elapsed());
+        result = Some(value);
+    }
+
+    (fastest, result.expect("no runs"))
+}
+
+fn main() {
+    let metric = DistanceMetric::Euclidean;
+
+    println!(
+        "{:>8} {:>12} {:>12} {:>12}",
+        "colors", "direct", "memoized", "cache hits"
+    );
+
+    for &count in SIZES {
+        let colors = colors(count);
+        // Names in index order, so the ties resolve the same way in both.
+        let names = (0..count)
+            .map(|index| format!("L{:05}", index))
+            .collect::<Vec<_>>();
+        let languages = names
+            .iter()
+            .cloned()
+            .zip(colors.iter().cloned())
+            .collect::<BTreeMap<_, _>>();
+
+        let (direct_time, direct) =
+            time(|| sort_languages(&languages, Order::Nearest, metric, None));
+        let (memoized_time, (memoized, hits)) = time(|| memoized_chain(&colors, metric));
+
+        let direct = direct.into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+        let memoized = memoized
+            .into_iter()
+            .map(|index| names[index].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(direct, memoized, "the chains differ for {} colors", count);
+
+        println!(
+            "{:>8} {:>10.2}ms {:>10.2}ms {:>12}",
+            count,
+            direct_time.as_secs_f64() * 1000.0,
+            memoized_time.as_secs_f64() * 1000.0,
+            hits,
+        );
+    }
+}
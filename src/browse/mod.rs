@@ -0,0 +1,280 @@
+#[cfg(feature = "tui")]
+mod terminal;
+
+#[cfg(feature = "tui")]
+pub use self::terminal::run;
+
+use std::collections::BTreeMap;
+
+use crate::color::Color;
+use crate::language::LanguageInfo;
+use crate::order::{
+    self,
+    DistanceMetric,
+    Order,
+};
+
+const NEAREST_COUNT: usize = 5;
+
+/// Key presses the browser reacts to, independent of the terminal library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Input {
+    Up,
+    Down,
+    Char(char),
+    Backspace,
+    Enter,
+    Escape,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Continue,
+    Quit,
+    Copy(String),
+}
+
+/// State of the palette browser: the languages in name order, the current
+/// search query and the selected entry of the filtered list.
+pub struct Browser<'a> {
+    languages: &'a BTreeMap<String, LanguageInfo>,
+    languages_colors: &'a BTreeMap<String, Color>,
+    by_name: Vec<(String, Color)>,
+    pub query: String,
+    pub searching: bool,
+    pub selected: usize,
+}
+
+impl<'a> Browser<'a> {
+    pub fn new(
+        languages: &'a BTreeMap<String, LanguageInfo>,
+        languages_colors: &'a BTreeMap<String, Color>,
+    ) -> Self {
+        Self {
+            languages,
+            languages_colors,
+            by_name: order::sort_languages(
+                languages_colors,
+                Order::Name,
+                DistanceMetric::Euclidean,
+                None,
+            ),
+            query: String::new(),
+            searching: false,
+            selected: 0,
+        }
+    }
+
+    /// Languages matching the current query.
+    pub fn visible(&self) -> Vec<&(String, Color)> {
+        filter(&self.by_name, &self.query)
+    }
+
+    pub fn selected_language(&self) -> Option<&(String, Color)> {
+        self.visible().get(self.selected).cloned()
+    }
+
+    pub fn handle_input(&mut self, input: Input) -> Action {
+        if self.searching {
+            match input {
+                Input::Char(c) => self.query.push(c),
+                Input::Backspace => {
+                    self.query.pop();
+                }
+                Input::Enter | Input::Escape => self.searching = false,
+                Input::Up => self.move_selection(-1),
+                Input::Down => self.move_selection(1),
+            }
+
+            self.selected = self.selected.min(self.visible().len().saturating_sub(1));
+            return Action::Continue;
+        }
+
+        match input {
+            Input::Char('q') | Input::Escape => return Action::Quit,
+            Input::Char('/') => self.searching = true,
+            Input::Char('k') | Input::Up => self.move_selection(-1),
+            Input::Char('j') | Input::Down => self.move_selection(1),
+            Input::Char('y') => {
+                if let Some((_, color)) = self.selected_language() {
+                    return Action::Copy(color.as_webcolor());
+                }
+            }
+            _ => {}
+        }
+
+        Action::Continue
+    }
+
+    /// Lines for the detail pane of the selected language.
+    pub fn details(&self) -> Vec<String> {
+        let (name, color) = match self.selected_language() {
+            Some(language) => language,
+            None => return Vec::new(),
+        };
+
+        let nearest = order::nearest_languages(
+            self.languages_colors,
+            color,
+            DistanceMetric::Euclidean,
+            name,
+            NEAREST_COUNT,
+        );
+
+        details(name, &self.languages[name], color, &nearest)
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        self.selected = move_selection(self.selected, self.visible().len(), delta);
+    }
+}
+
+/// Languages whose name contains `query`, ignoring case.
+pub fn filter<'a>(languages: &'a [(String, Color)], query: &str) -> Vec<&'a (String, Color)> {
+    let query = query.to_lowercase();
+
+    languages
+        .iter()
+        .filter(|(name, _)| name.to_lowercase().contains(&query))
+        .collect()
+}
+
+/// Moves `selected` by `delta`, staying within a list of length `len`.
+pub fn move_selection(selected: usize, len: usize, delta: isize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+
+    let moved = selected as isize + delta;
+    moved.max(0).min(len as isize - 1) as usize
+}
+
+pub fn details(
+    name: &str,
+    info: &LanguageInfo,
+    color: &Color,
+    nearest: &[(&str, f64)],
+) -> Vec<String> {
+    let (hue, saturation, lightness) = color.to_hsl();
+
+    let extensions = info
+        .extensions
+        .as_ref()
+        .map(|extensions| extensions.join(", "))
+        .unwrap_or_else(|| "none".to_string());
+
+    let mut lines = vec![
+        name.to_string(),
+        String::new(),
+        format!("hex:        {}", color.as_webcolor()),
+        format!("rgb:        {}, {}, {}", color.red, color.green, color.blue),
+        format!(
+            "hsl:        {:.0}°, {:.0}%, {:.0}%",
+            hue,
+            saturation * 100.0,
+            lightness * 100.0
+        ),
+        format!("type:       {}", info.language_type),
+        format!("extensions: {}", extensions),
+        String::new(),
+        "nearest:".to_string(),
+    ];
+
+    lines.extend(
+        nearest
+            .iter()
+            .map(|(name, distance)| format!("  {} ({:.2})", name, distance)),
+    );
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colors() -> BTreeMap<String, Color> {
+        ["Go", "JavaScript", "Rust", "TypeScript"]
+            .iter()
+            .map(|name| (name.to_string(), Color::BLACK))
+            .collect()
+    }
+
+    fn names(languages: &[&(String, Color)]) -> Vec<String> {
+        languages.iter().map(|(name, _)| name.clone()).collect()
+    }
+
+    #[test]
+    fn filter_ignores_case() {
+        let languages = colors().into_iter().collect::<Vec<_>>();
+
+        assert_eq!(
+            names(&filter(&languages, "SCRIPT")),
+            ["JavaScript", "TypeScript"]
+        );
+        assert_eq!(names(&filter(&languages, "rUsT")), ["Rust"]);
+        assert_eq!(filter(&languages, "").len(), 4);
+        assert!(filter(&languages, "python").is_empty());
+    }
+
+    #[test]
+    fn move_selection_stays_within_the_list() {
+        assert_eq!(move_selection(0, 4, -1), 0);
+        assert_eq!(move_selection(3, 4, 1), 3);
+        assert_eq!(move_selection(1, 4, 1), 2);
+        assert_eq!(move_selection(0, 4, 10), 3);
+        assert_eq!(move_selection(2, 0, 1), 0);
+    }
+
+    #[test]
+    fn slash_starts_a_search_and_q_quits() {
+        let languages = BTreeMap::new();
+        let colors = colors();
+        let mut browser = Browser::new(&languages, &colors);
+
+        assert_eq!(browser.handle_input(Input::Char('/')), Action::Continue);
+        assert!(browser.searching);
+
+        // While searching `q` is part of the query.
+        for c in "qts".chars() {
+            assert_eq!(browser.handle_input(Input::Char(c)), Action::Continue);
+        }
+        assert_eq!(browser.query, "qts");
+        assert!(browser.visible().is_empty());
+
+        browser.handle_input(Input::Backspace);
+        browser.handle_input(Input::Backspace);
+        browser.handle_input(Input::Backspace);
+        browser.handle_input(Input::Char('s'));
+        assert_eq!(
+            names(&browser.visible()),
+            ["JavaScript", "Rust", "TypeScript"]
+        );
+
+        browser.handle_input(Input::Enter);
+        assert!(!browser.searching);
+        assert_eq!(browser.query, "s");
+
+        assert_eq!(browser.handle_input(Input::Char('q')), Action::Quit);
+    }
+
+    #[test]
+    fn selection_is_clamped_to_the_filtered_languages() {
+        let languages = BTreeMap::new();
+        let colors = colors();
+        let mut browser = Browser::new(&languages, &colors);
+
+        browser.handle_input(Input::Up);
+        assert_eq!(browser.selected, 0);
+        for _ in 0..10 {
+            browser.handle_input(Input::Down);
+        }
+        assert_eq!(browser.selected, 3);
+        assert_eq!(browser.selected_language().unwrap().0, "TypeScript");
+
+        browser.handle_input(Input::Char('/'));
+        browser.handle_input(Input::Char('g'));
+        assert_eq!(browser.selected, 0);
+        assert_eq!(browser.selected_language().unwrap().0, "Go");
+    }
+}
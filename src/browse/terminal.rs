@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::io::{
+    self,
+    stdout,
+};
+
+use crossterm::event::{
+    self,
+    Event,
+    KeyCode,
+    KeyEventKind,
+};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode,
+    enable_raw_mode,
+    EnterAlternateScreen,
+    LeaveAlternateScreen,
+};
+use ratatui::backend::{
+    Backend,
+    CrosstermBackend,
+};
+use ratatui::layout::{
+    Constraint,
+    Direction,
+    Layout,
+};
+use ratatui::style::{
+    Color as TerminalColor,
+    Modifier,
+    Style,
+};
+use ratatui::text::{
+    Line,
+    Span,
+};
+use ratatui::widgets::{
+    Block,
+    Borders,
+    List,
+    ListItem,
+    ListState,
+    Paragraph,
+};
+use ratatui::{
+    Frame,
+    Terminal,
+};
+
+use crate::browse::{
+    Action,
+    Browser,
+    Input,
+};
+use crate::color::Color;
+use crate::language::LanguageInfo;
+
+/// Runs the interactive browser until the user quits. Hex values copied
+/// with `y` are put on the clipboard when the `clipboard` feature is enabled
+/// and printed to stdout after the browser exits otherwise.
+pub fn run(
+    languages: &BTreeMap<String, LanguageInfo>,
+    languages_colors: &BTreeMap<String, Color>,
+) -> io::Result<()> {
+    let mut browser = Browser::new(languages, languages_colors);
+    let true_color = supports_true_color();
+
+    enable_raw_mode()?;
+    execute!(stdout(), EnterAlternateScreen)?;
+
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+    let result = event_loop(&mut terminal, &mut browser, true_color);
+
+    disable_raw_mode()?;
+    execute!(stdout(), LeaveAlternateScreen)?;
+
+    for hex in result? {
+        println!("{}", hex);
+    }
+
+    Ok(())
+}
+
+fn event_loop<B: Backend>(
+    terminal: &mut Terminal<B>,
+    browser: &mut Browser,
+    true_color: bool,
+) -> io::Result<Vec<String>> {
+    let mut copied = Vec::new();
+
+    loop {
+        terminal.draw(|frame| draw(frame, browser, true_color))?;
+
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+
+        let input = match key.code {
+            KeyCode::Up => Input::Up,
+            KeyCode::Down => Input::Down,
+            KeyCode::Char(c) => Input::Char(c),
+            KeyCode::Backspace => Input::Backspace,
+            KeyCode::Enter => Input::Enter,
+            KeyCode::Esc => Input::Escape,
+            _ => continue,
+        };
+
+        match browser.handle_input(input) {
+            Action::Continue => {}
+            Action::Quit => return Ok(copied),
+            Action::Copy(hex) => {
+                if !copy_to_clipboard(&hex) {
+                    copied.push(hex);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "clipboard")]
+fn copy_to_clipboard(hex: &str) -> bool {
+    arboard::Clipboard::new()
+        .and_then(|mut clipboard| clipboard.set_text(hex.to_string()))
+        .is_ok()
+}
+
+#[cfg(not(feature = "clipboard"))]
+fn copy_to_clipboard(_hex: &str) -> bool {
+    false
+}
+
+fn draw(frame: &mut Frame, browser: &Browser, true_color: bool) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(frame.size());
+
+    let list_area = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(columns[0]);
+
+    let search_title = if browser.searching {
+        "Search"
+    } else {
+        "Search (/)"
+    };
+    let search = Paragraph::new(browser.query.as_str())
+        .block(Block::default().borders(Borders::ALL).title(search_title));
+    frame.render_widget(search, list_area[0]);
+
+    let visible = browser.visible();
+    let items = visible
+        .iter()
+        .map(|(name, color)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    "    ",
+                    Style::default().bg(terminal_color(color, true_color)),
+                ),
+                Span::raw(" "),
+                Span::raw(name.as_str()),
+            ]))
+        })
+        .collect::<Vec<_>>();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("Languages"))
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    let mut state = ListState::default();
+    if !visible.is_empty() {
+        state.select(Some(browser.selected));
+    }
+    frame.render_stateful_widget(list, list_area[1], &mut state);
+
+    let details = Paragraph::new(browser.details().join("\n"))
+        .block(Block::default().borders(Borders::ALL).title("Details"));
+    frame.render_widget(details, columns[1]);
+}
+
+/// Terminals announce true color support with `COLORTERM`.
+fn supports_true_color() -> bool {
+    matches!(
+        std::env::var("COLORTERM").as_deref(),
+        Ok("truecolor") | Ok("24bit")
+    )
+}
+
+/// Falls back to the closest color of the 256 color palette on terminals
+/// without true color support.
+fn terminal_color(color: &Color, true_color: bool) -> TerminalColor {
+    if true_color {
+        TerminalColor::Rgb(color.red as u8, color.green as u8, color.blue as u8)
+    } else {
+        TerminalColor::Indexed(color.to_terminal_256())
+    }
+}
@@ -0,0 +1,297 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::time::{
+    Duration,
+    SystemTime,
+    UNIX_EPOCH,
+};
+
+const LANGUAGES_FILE: &str = "languages.yml";
+/// Sidecar with the `CacheMetadata` of the cached languages.
+const METADATA_FILE: &str = "languages.json";
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// Where and when the cached languages were downloaded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheMetadata {
+    pub url: String,
+    /// Seconds since the unix epoch.
+    pub fetched_at: u64,
+    /// ETag header of the response, if the server sent one.
+    pub etag: Option<String>,
+    /// SHA-256 of the cached languages as lowercase hex.
+    pub sha256: String,
+}
+
+impl CacheMetadata {
+    /// Age of the cache at `now`, zero if it was fetched in the future.
+    pub fn age(&self, now: SystemTime) -> Duration {
+        let fetched_at = UNIX_EPOCH + Duration::from_secs(self.fetched_at);
+
+        now.duration_since(fetched_at).unwrap_or_default()
+    }
+
+    pub fn is_stale(&self, now: SystemTime, stale_after_days: u64) -> bool {
+        self.age(now) > Duration::from_secs(stale_after_days * DAY)
+    }
+}
+
+/// The languages file downloaded by the last online run, read back by
+/// `--offline`.
+///
+/// ```
+/// use std::time::SystemTime;
+///
+/// use language_colors::cache::{
+///     Cache,
+///     CacheMetadata,
+/// };
+///
+/// let cache = Cache::new(std::env::temp_dir().join("language_colors_cache_doc"));
+/// let metadata = CacheMetadata {
+///     url: "https://example.com/languages.yml".to_string(),
+///     fetched_at: 0,
+///     etag: None,
+///     sha256: "0".repeat(64),
+/// };
+/// cache.write(b"Rust: {}\n", &metadata).unwrap();
+///
+/// let (body, read) = cache.read().unwrap();
+/// assert_eq!(body, b"Rust: {}\n");
+/// assert!(read.is_stale(SystemTime::now(), 30));
+///
+/// cache.clear().unwrap();
+/// assert!(cache.info().unwrap().is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+/// What `cache info` prints about the cache.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheInfo {
+    pub path: PathBuf,
+    /// Size of the cached languages in bytes.
+    pub size: u64,
+    pub metadata: CacheMetadata,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// `$XDG_CACHE_HOME/language_colors`, or `~/.cache/language_colors` if
+    /// that is not set.
+    pub fn default_dir() -> Option<PathBuf> {
+        let base = match env::var_os("XDG_CACHE_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(env::var_os("HOME")?).join(".cache"),
+        };
+
+        Some(base.join("language_colors"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn languages_path(&self) -> PathBuf {
+        self.dir.join(LANGUAGES_FILE)
+    }
+
+    fn metadata_path(&self) -> PathBuf {
+        self.dir.join(METADATA_FILE)
+    }
+
+    /// Replaces the cached languages, the sidecar is written last so a
+    /// cache without one is incomplete.
+    pub fn write(&self, body: &[u8], metadata: &CacheMetadata) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let _ = fs::remove_file(self.metadata_path());
+
+        fs::write(self.languages_path(), body)?;
+        fs::write(
+            self.metadata_path(),
+            serde_json::to_vec_pretty(metadata).expect("can not serialize cache metadata"),
+        )
+    }
+
+    pub fn read(&self) -> io::Result<(Vec<u8>, CacheMetadata)> {
+        let metadata = self.read_metadata()?;
+        let body = fs::read(self.languages_path())?;
+
+        Ok((body, metadata))
+    }
+
+    fn read_metadata(&self) -> io::Result<CacheMetadata> {
+        let sidecar = fs::read(self.metadata_path())?;
+
+        serde_json::from_slice(&sidecar)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// `None` if nothing is cached.
+    pub fn info(&self) -> io::Result<Option<CacheInfo>> {
+        let metadata = match self.read_metadata() {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let path = self.languages_path();
+        let size = fs::metadata(&path)?.len();
+
+        Ok(Some(CacheInfo {
+            path,
+            size,
+            metadata,
+        }))
+    }
+
+    /// Removes the cached languages and their sidecar, returns the files
+    /// that existed.
+    pub fn clear(&self) -> io::Result<Vec<PathBuf>> {
+        let mut removed = Vec::new();
+
+        for path in [self.metadata_path(), self.languages_path()] {
+            match fs::remove_file(&path) {
+                Ok(()) => removed.push(path),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+impl CacheInfo {
+    /// One `name: value` line per field for `cache info`.
+    pub fn lines(&self, now: SystemTime) -> Vec<String> {
+        vec![
+            format!("path: {}", self.path.display()),
+            format!("size: {} bytes", self.size),
+            format!("age: {}", format_age(self.metadata.age(now))),
+            format!("url: {}", self.metadata.url),
+            format!(
+                "etag: {}",
+                self.metadata.etag.as_deref().unwrap_or("not recorded")
+            ),
+            format!("sha256: {}", self.metadata.sha256),
+        ]
+    }
+}
+
+/// Whole days, or hours and minutes below a day, e.g. `40 days`.
+pub fn format_age(age: Duration) -> String {
+    let seconds = age.as_secs();
+    let (count, unit) = if seconds >= DAY {
+        (seconds / DAY, "day")
+    } else if seconds >= 60 * 60 {
+        (seconds / (60 * 60), "hour")
+    } else {
+        (seconds / 60, "minute")
+    };
+
+    format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(test: &str) -> Cache {
+        let dir = env::temp_dir().join(format!(
+            "language_colors_cache_{}_{}",
+            test,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+
+        Cache::new(dir)
+    }
+
+    fn metadata(fetched_at: u64) -> CacheMetadata {
+        CacheMetadata {
+            url: "https://example.com/languages.yml".to_string(),
+            fetched_at,
+            etag: Some("\"abc\"".to_string()),
+            sha256: "0".repeat(64),
+        }
+    }
+
+    fn days(days: u64) -> Duration {
+        Duration::from_secs(days * DAY)
+    }
+
+    #[test]
+    fn info_reads_a_synthetic_cache() {
+        let cache = cache("info");
+        assert_eq!(cache.info().unwrap(), None);
+
+        fs::create_dir_all(cache.dir()).unwrap();
+        fs::write(cache.languages_path(), "Rust: {}\n").unwrap();
+        fs::write(
+            cache.dir().join(METADATA_FILE),
+            serde_json::to_vec(&metadata(1_000_000)).unwrap(),
+        )
+        .unwrap();
+
+        let info = cache.info().unwrap().unwrap();
+        assert_eq!(info.size, 9);
+        assert_eq!(info.metadata, metadata(1_000_000));
+
+        let now = UNIX_EPOCH + Duration::from_secs(1_000_000) + days(40);
+        assert_eq!(
+            info.lines(now),
+            [
+                format!("path: {}", cache.languages_path().display()),
+                "size: 9 bytes".to_string(),
+                "age: 40 days".to_string(),
+                "url: https://example.com/languages.yml".to_string(),
+                "etag: \"abc\"".to_string(),
+                format!("sha256: {}", "0".repeat(64)),
+            ]
+        );
+    }
+
+    #[test]
+    fn clear_removes_the_cached_files() {
+        let cache = cache("clear");
+        cache.write(b"Rust: {}\n", &metadata(0)).unwrap();
+
+        assert_eq!(
+            cache.clear().unwrap(),
+            [cache.dir().join(METADATA_FILE), cache.languages_path()]
+        );
+        assert!(cache.clear().unwrap().is_empty());
+        assert!(cache.read().is_err());
+    }
+
+    #[test]
+    fn caches_older_than_the_threshold_are_stale() {
+        let now = UNIX_EPOCH + days(100);
+
+        assert!(!metadata(days(70).as_secs()).is_stale(now, 30));
+        assert!(!metadata(days(80).as_secs()).is_stale(now, 30));
+        assert!(metadata(days(70).as_secs() - 1).is_stale(now, 30));
+        assert!(!metadata(days(200).as_secs()).is_stale(now, 30));
+        assert_eq!(metadata(days(200).as_secs()).age(now), Duration::ZERO);
+    }
+
+    #[test]
+    fn ages_are_formatted_in_the_largest_unit() {
+        assert_eq!(format_age(days(40)), "40 days");
+        assert_eq!(format_age(days(1)), "1 day");
+        assert_eq!(format_age(Duration::from_secs(5 * 60 * 60 + 59)), "5 hours");
+        assert_eq!(format_age(Duration::from_secs(90)), "1 minute");
+        assert_eq!(format_age(Duration::ZERO), "0 minutes");
+    }
+}
@@ -0,0 +1,105 @@
+use crate::color::Color;
+
+/// One segment of a palette card, `x` and `width` are in the units of the
+/// total width passed to `layout`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub language: String,
+    pub color: Color,
+    pub x: f64,
+    pub width: f64,
+}
+
+/// Parses the weight of a segment, it has to be a positive, finite number.
+pub fn parse_weight(s: &str) -> Result<f64, String> {
+    match s.trim().parse::<f64>() {
+        Ok(weight) if weight.is_finite() && weight > 0.0 => Ok(weight),
+        Ok(_) => Err(format!("weight has to be positive: {}", s)),
+        Err(_) => Err(format!("invalid weight: {}", s)),
+    }
+}
+
+/// Splits `width` into one segment per language, left to right in the given
+/// order. Without `weights` every language gets an equal share, otherwise
+/// the share of a language is proportional to its weight. The segments
+/// always cover the full width without gaps.
+///
+/// ```
+/// use language_colors::card::layout;
+/// use language_colors::color::Color;
+///
+/// let languages = vec![
+///     ("Rust".to_string(), Color::from_webcolor("#DEA584").unwrap()),
+///     ("Go".to_string(), Color::from_webcolor("#00ADD8").unwrap()),
+///     ("C".to_string(), Color::from_webcolor("#555555").unwrap()),
+///     ("Java".to_string(), Color::from_webcolor("#B07219").unwrap()),
+/// ];
+///
+/// let geometry = |segments: Vec<language_colors::card::Segment>| {
+///     segments
+///         .iter()
+///         .map(|segment| (segment.x, segment.width))
+///         .collect::<Vec<_>>()
+/// };
+///
+/// let equal = layout(&languages, None, 800.0).unwrap();
+/// assert_eq!(
+///     geometry(equal),
+///     [(0.0, 200.0), (200.0, 200.0), (400.0, 200.0), (600.0, 200.0)]
+/// );
+///
+/// let weighted = layout(&languages, Some(&[40.0, 30.0, 20.0, 10.0]), 800.0).unwrap();
+/// assert_eq!(
+///     geometry(weighted),
+///     [(0.0, 320.0), (320.0, 240.0), (560.0, 160.0), (720.0, 80.0)]
+/// );
+///
+/// let thirds = layout(&languages[..3], Some(&[1.0, 1.0, 1.0]), 100.0).unwrap();
+/// let last = thirds.last().unwrap();
+/// assert_eq!(last.x + last.width, 100.0);
+///
+/// assert!(layout(&languages, Some(&[1.0, 2.0]), 800.0).is_err());
+/// assert!(layout(&[], None, 800.0).is_err());
+/// ```
+pub fn layout(
+    languages: &[(String, Color)],
+    weights: Option<&[f64]>,
+    width: f64,
+) -> Result<Vec<Segment>, String> {
+    if languages.is_empty() {
+        return Err("a card needs at least one language".to_string());
+    }
+
+    let weights = match weights {
+        Some(weights) if weights.len() != languages.len() => {
+            return Err(format!(
+                "got {} weights for {} languages",
+                weights.len(),
+                languages.len()
+            ));
+        }
+        Some(weights) => weights.to_vec(),
+        None => vec![1.0; languages.len()],
+    };
+    let total = weights.iter().sum::<f64>();
+
+    let mut covered = 0.0;
+    let segments = languages
+        .iter()
+        .zip(&weights)
+        .map(|((language, color), weight)| {
+            let x = width * covered / total;
+            covered += weight;
+            let end = width * covered / total;
+
+            Segment {
+                language: language.clone(),
+                color: color.clone(),
+                x,
+                width: end - x,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Ok(segments)
+}
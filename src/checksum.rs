@@ -0,0 +1,57 @@
+use sha2::{
+    Digest,
+    Sha256,
+};
+
+/// Lowercase hex encoded SHA-256 of `bytes`.
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Compares the SHA-256 of `bytes` to the hex encoded `expected` hash,
+/// ignoring case. Returns the actual hash if they differ.
+pub fn verify_sha256(bytes: &[u8], expected: &str) -> Result<(), String> {
+    let actual = sha256_hex(bytes);
+
+    if actual == expected.trim().to_lowercase() {
+        Ok(())
+    } else {
+        Err(actual)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+    #[test]
+    fn hashes_raw_bytes() {
+        assert_eq!(sha256_hex(b"abc"), ABC_SHA256);
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn verifies_ignoring_case_and_whitespace() {
+        assert_eq!(verify_sha256(b"abc", ABC_SHA256), Ok(()));
+        assert_eq!(
+            verify_sha256(b"abc", &format!(" {}\n", ABC_SHA256.to_uppercase())),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn returns_actual_hash_on_mismatch() {
+        assert_eq!(
+            verify_sha256(b"abc", &"0".repeat(64)),
+            Err(ABC_SHA256.to_string())
+        );
+    }
+}
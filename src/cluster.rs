@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use rand::seq::index;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::color::{
+    AverageSpace,
+    Color,
+};
+
+const MAX_ITERATIONS: usize = 50;
+
+/// Groups the languages into at most `k` clusters of similar colors using
+/// k-means. The initial centroids are picked evenly spaced from the
+/// languages in name order, so the result only depends on the input.
+/// Clusters are returned in the order of their initial centroid and contain
+/// the language names in name order. The centroids are averaged in `space`.
+pub fn cluster_by_color(
+    languages_colors: &BTreeMap<String, Color>,
+    k: usize,
+    space: AverageSpace,
+) -> Vec<Vec<String>> {
+    let colors = languages_colors.values().collect::<Vec<_>>();
+    if colors.is_empty() || k == 0 {
+        return Vec::new();
+    }
+
+    let k = k.min(colors.len());
+    let centroids = (0..k)
+        .map(|i| colors[i * colors.len() / k].clone())
+        .collect();
+
+    kmeans(languages_colors, centroids, space)
+}
+
+/// Like [`cluster_by_color`] but the initial centroids are picked at random
+/// from the languages using `seed`. The same seed and input always result
+/// in the same clusters. ChaCha is used instead of `StdRng` because its
+/// output is guaranteed to stay the same across rand versions.
+pub fn cluster_by_color_seeded(
+    languages_colors: &BTreeMap<String, Color>,
+    k: usize,
+    seed: u64,
+    space: AverageSpace,
+) -> Vec<Vec<String>> {
+    let colors = languages_colors.values().collect::<Vec<_>>();
+    if colors.is_empty() || k == 0 {
+        return Vec::new();
+    }
+
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let centroids = index::sample(&mut rng, colors.len(), k.min(colors.len()))
+        .into_iter()
+        .map(|i| colors[i].clone())
+        .collect();
+
+    kmeans(languages_colors, centroids, space)
+}
+
+fn kmeans(
+    languages_colors: &BTreeMap<String, Color>,
+    mut centroids: Vec<Color>,
+    space: AverageSpace,
+) -> Vec<Vec<String>> {
+    let colors = languages_colors.values().collect::<Vec<_>>();
+    let k = centroids.len();
+
+    let mut assignments = vec![0; colors.len()];
+    for _ in 0..MAX_ITERATIONS {
+        let new_assignments = colors
+            .iter()
+            .map(|color| nearest_centroid(color, &centroids))
+            .collect::<Vec<_>>();
+
+        let changed = new_assignments != assignments;
+        assignments = new_assignments;
+
+        for (index, centroid) in centroids.iter_mut().enumerate() {
+            let members = colors
+                .iter()
+                .zip(&assignments)
+                .filter(|(_, assignment)| **assignment == index)
+                .map(|(color, _)| *color);
+
+            if let Some(average) = Color::average(members, space) {
+                *centroid = average;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    let mut clusters = vec![Vec::new(); k];
+    for (name, assignment) in languages_colors.keys().zip(&assignments) {
+        clusters[*assignment].push(name.clone());
+    }
+
+    clusters.retain(|cluster| !cluster.is_empty());
+    clusters
+}
+
+/// Index of the centroid closest to `color`, the lowest index on equal
+/// distances.
+fn nearest_centroid(color: &Color, centroids: &[Color]) -> usize {
+    let mut nearest = 0;
+    let mut nearest_distance = f64::MAX;
+
+    for (index, centroid) in centroids.iter().enumerate() {
+        let distance = color.euclidean_distance(centroid);
+        if distance < nearest_distance {
+            nearest = index;
+            nearest_distance = distance;
+        }
+    }
+
+    nearest
+}
@@ -0,0 +1,1694 @@
+use std::cmp::{
+    Ordering,
+    Reverse,
+};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use ordered_float::OrderedFloat;
+use rand::Rng;
+
+/// Number of bands the lightness is split into by `perceptual_sort_key`.
+const LIGHTNESS_BANDS: f64 = 3.0;
+
+/// The 16 system colors of xterm, index 0 to 15 of the 256 color palette.
+const TERMINAL_SYSTEM_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Names returned by `Color::perceptual_group`, in the order of the hue
+/// circle followed by the colors without a hue.
+pub const COLOR_FAMILIES: [&str; 13] = [
+    "red", "orange", "yellow", "green", "cyan", "blue", "purple", "magenta", "pink", "brown",
+    "gray", "black", "white",
+];
+
+/// Channel values of the 6x6x6 color cube, index 16 to 231.
+const TERMINAL_CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Space in which colors are averaged by `Color::average`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AverageSpace {
+    /// Directly on the gamma encoded channels, gives muddy and too dark
+    /// averages.
+    Srgb,
+    /// On the channels with the sRGB gamma removed.
+    #[default]
+    Linear,
+    /// On CIELAB lightness, a and b.
+    Lab,
+}
+
+impl AverageSpace {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AverageSpace::Srgb => "srgb",
+            AverageSpace::Linear => "linear",
+            AverageSpace::Lab => "lab",
+        }
+    }
+}
+
+impl FromStr for AverageSpace {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "srgb" => Ok(AverageSpace::Srgb),
+            "linear" => Ok(AverageSpace::Linear),
+            "lab" => Ok(AverageSpace::Lab),
+            _ => Err(format!("unknown average space: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColorParseError {
+    InvalidBase64(String),
+    InvalidLength(usize),
+    InvalidWebcolor(String),
+    /// Input of `Color::from_css_string` that is neither hex, `rgb()` nor
+    /// `hsl()`.
+    InvalidCssColor(String),
+    /// Input of `parse_any_color` that matches none of the formats.
+    Unrecognized(String),
+}
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColorParseError::InvalidBase64(err) => write!(f, "invalid base64 color: {}", err),
+            ColorParseError::InvalidLength(length) => {
+                write!(f, "expected 3 color channels but got {} bytes", length)
+            }
+            ColorParseError::InvalidWebcolor(color) => {
+                write!(f, "expected #RGB, #RRGGBB or #RRGGBBAA but got {}", color)
+            }
+            ColorParseError::InvalidCssColor(color) => write!(
+                f,
+                "expected #RGB, #RRGGBB, #RRGGBBAA, rgb(red, green, blue) or hsl(hue, \
+                 saturation%, lightness%) but got {}",
+                color
+            ),
+            ColorParseError::Unrecognized(color) => write!(
+                f,
+                "expected a hex color (RGB, RRGGBB or RRGGBBAA with an optional #), rgb(red, \
+                 green, blue), hsl(hue, saturation%, lightness%) or a css color name but got {}",
+                color
+            ),
+        }
+    }
+}
+
+impl Error for ColorParseError {}
+
+#[derive(Debug, Ord, PartialOrd, PartialEq, Eq, Clone)]
+pub struct Color {
+    pub(crate) red: i64,
+    pub(crate) green: i64,
+    pub(crate) blue: i64,
+    /// Opacity from 0 (transparent) to 255 (opaque).
+    pub(crate) alpha: i64,
+}
+
+impl Color {
+    pub const BLACK: Color = Color {
+        red: 0,
+        green: 0,
+        blue: 0,
+        alpha: 255,
+    };
+    pub const WHITE: Color = Color {
+        red: 255,
+        green: 255,
+        blue: 255,
+        alpha: 255,
+    };
+
+    /// Opaque color from its red, green and blue channels.
+    ///
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// let rust = Color::from_rgb(0xDE, 0xA5, 0x84);
+    /// assert_eq!(rust.as_webcolor(), "#DEA584");
+    /// assert_eq!(rust, Color::from_webcolor("#dea584").unwrap());
+    /// ```
+    pub fn from_rgb(red: u8, green: u8, blue: u8) -> Color {
+        Color::from_rgba(red, green, blue, 255)
+    }
+
+    /// Support for `color!`, parses `RRGGBB` or `RRGGBBAA` in a const
+    /// context and panics on anything else. Spaces are skipped because
+    /// `stringify!` can put them between the tokens of a literal.
+    #[doc(hidden)]
+    pub const fn from_hex_literal(hex: &str) -> Color {
+        let bytes = hex.as_bytes();
+        let mut channels = [0i64, 0, 0, 255];
+        let mut digits = 0;
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let digit = match bytes[i] {
+                b' ' => {
+                    i += 1;
+                    continue;
+                }
+                b @ b'0'..=b'9' => b - b'0',
+                b @ b'a'..=b'f' => b - b'a' + 10,
+                b @ b'A'..=b'F' => b - b'A' + 10,
+                _ => panic!("color! expects hex digits"),
+            };
+
+            if digits == 8 {
+                panic!("color! expects RRGGBB or RRGGBBAA");
+            }
+
+            let channel = digits / 2;
+            if digits % 2 == 0 {
+                channels[channel] = 0;
+            }
+            channels[channel] = channels[channel] * 16 + digit as i64;
+            digits += 1;
+            i += 1;
+        }
+
+        if digits != 6 && digits != 8 {
+            panic!("color! expects RRGGBB or RRGGBBAA");
+        }
+
+        Color {
+            red: channels[0],
+            green: channels[1],
+            blue: channels[2],
+            alpha: channels[3],
+        }
+    }
+
+    pub fn from_rgba(red: u8, green: u8, blue: u8, alpha: u8) -> Color {
+        Color {
+            red: i64::from(red),
+            green: i64::from(green),
+            blue: i64::from(blue),
+            alpha: i64::from(alpha),
+        }
+    }
+
+    /// Random color drawn from `rng`, pass a seeded rng for reproducible
+    /// colors.
+    pub fn random_seeded(rng: &mut impl Rng) -> Color {
+        Color::from_rgb(rng.gen(), rng.gen(), rng.gen())
+    }
+
+    /// Parses `#RGB`, `#RRGGBB` or `#RRGGBBAA`, the `#` is optional.
+    ///
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// assert_eq!(Color::from_webcolor("#f00"), Ok(Color::from_rgb(255, 0, 0)));
+    /// assert_eq!(Color::from_webcolor("DEA58480").unwrap().as_webcolor(), "#DEA58480");
+    /// assert!(Color::from_webcolor("#dea58").is_err());
+    /// assert!(Color::from_webcolor("#+ea584").is_err());
+    /// ```
+    pub fn from_webcolor(color: &str) -> Result<Self, ColorParseError> {
+        let invalid = || ColorParseError::InvalidWebcolor(color.to_string());
+
+        let hex = color.trim_start_matches('#');
+        let valid = (hex.len() == 3 || hex.len() == 6 || hex.len() == 8)
+            && hex.chars().all(|c| c.is_ascii_hexdigit());
+        if !valid {
+            return Err(invalid());
+        }
+
+        let expanded;
+        let hex = if hex.len() == 3 {
+            expanded = hex.chars().flat_map(|c| [c, c]).collect::<String>();
+            &expanded
+        } else {
+            hex
+        };
+
+        // Only ascii hex digits are left, so every channel is two bytes.
+        let channel = |index: usize| {
+            i64::from_str_radix(&hex[index * 2..index * 2 + 2], 16).map_err(|_| invalid())
+        };
+
+        Ok(Self {
+            red: channel(0)?,
+            green: channel(1)?,
+            blue: channel(2)?,
+            alpha: if hex.len() == 8 { channel(3)? } else { 255 },
+        })
+    }
+
+    /// Parses the css notations `#RGB`, `#RRGGBB`, `#RRGGBBAA`, `rgb(R, G, B)`
+    /// and `hsl(H, S%, L%)`. The channels of `rgb()` are either all integers
+    /// from 0 to 255 or all percentages from 0% to 100%. The hue of `hsl()`
+    /// is in degrees, an integer or a decimal with an optional `deg`, the
+    /// saturation and lightness are percentages. `hsl()` is converted to rgb
+    /// right away, so it doesn't survive a round trip exactly. Case and
+    /// whitespace around the arguments are ignored.
+    ///
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// let cases = [
+    ///     ("#FF8000", Some("#FF8000")),
+    ///     ("#ff800080", Some("#FF800080")),
+    ///     ("rgb(255, 128, 0)", Some("#FF8000")),
+    ///     ("rgb(255,128,0)", Some("#FF8000")),
+    ///     ("  RGB( 255 , 128 , 0 )  ", Some("#FF8000")),
+    ///     ("rgb(100%, 50%, 0%)", Some("#FF8000")),
+    ///     ("rgb(12.5%, 0%, 100%)", Some("#2000FF")),
+    ///     ("rgb(256, 0, 0)", None),
+    ///     ("rgb(-1, 0, 0)", None),
+    ///     ("rgb(101%, 0%, 0%)", None),
+    ///     ("rgb(100%, 128, 0)", None),
+    ///     ("rgb(255, 128)", None),
+    ///     ("rgb 255, 128, 0", None),
+    ///     ("hsl(240, 100%, 50%)", Some("#0000FF")),
+    ///     ("hsl(30.5, 100%, 50%)", Some("#FF8200")),
+    ///     ("hsl(120deg, 100%, 25%)", Some("#008000")),
+    ///     ("HSL( 0 , 0% , 100% )", Some("#FFFFFF")),
+    ///     ("hsl(24, 57.5%, 69.2%)", Some("#DEA783")),
+    ///     ("hsl(240, 100, 50)", None),
+    ///     ("hsl(240, 101%, 50%)", None),
+    ///     ("hsl(240, 100%)", None),
+    /// ];
+    ///
+    /// for (input, expected) in &cases {
+    ///     let parsed = Color::from_css_string(input).ok().map(|color| color.as_webcolor());
+    ///     assert_eq!(parsed.as_deref(), *expected, "{}", input);
+    /// }
+    /// ```
+    pub fn from_css_string(s: &str) -> Result<Color, ColorParseError> {
+        let input = s.trim().to_lowercase();
+
+        if let Some(arguments) = function_arguments(&input, "rgb") {
+            rgb_function(&arguments)
+        } else if let Some(arguments) = function_arguments(&input, "hsl") {
+            hsl_function(&arguments)
+        } else {
+            input.parse().ok()
+        }
+        .ok_or_else(|| ColorParseError::InvalidCssColor(s.to_string()))
+    }
+
+    pub fn as_tuple(&self) -> (u8, u8, u8) {
+        (self.red as u8, self.green as u8, self.blue as u8)
+    }
+
+    pub fn as_array(&self) -> [u8; 3] {
+        [self.red as u8, self.green as u8, self.blue as u8]
+    }
+
+    pub fn alpha(&self) -> u8 {
+        self.alpha as u8
+    }
+
+    /// `#RRGGBB` for opaque colors and `#RRGGBBAA` otherwise.
+    pub fn as_webcolor(&self) -> String {
+        if self.alpha == 255 {
+            format!("#{:02X}{:02X}{:02X}", self.red, self.green, self.blue)
+        } else {
+            format!(
+                "#{:02X}{:02X}{:02X}{:02X}",
+                self.red, self.green, self.blue, self.alpha
+            )
+        }
+    }
+
+    /// Whether the color can be written as `#RGB`: it is opaque and both hex
+    /// digits of every channel are the same.
+    pub fn is_shortable(&self) -> bool {
+        self.alpha == 255
+            && [self.red, self.green, self.blue]
+                .iter()
+                .all(|channel| channel % 17 == 0)
+    }
+
+    /// `#RGB` if the color `is_shortable`, otherwise the same as
+    /// `as_webcolor`.
+    ///
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// let orange = Color::from_webcolor("#FFAA00").unwrap();
+    /// assert!(orange.is_shortable());
+    /// assert_eq!(orange.to_webcolor_short(), "#FA0");
+    ///
+    /// let rust = Color::from_webcolor("#DEA584").unwrap();
+    /// assert!(!rust.is_shortable());
+    /// assert_eq!(rust.to_webcolor_short(), "#DEA584");
+    ///
+    /// let transparent = Color::from_webcolor("#FFAA0080").unwrap();
+    /// assert!(!transparent.is_shortable());
+    /// assert_eq!(transparent.to_webcolor_short(), "#FFAA0080");
+    ///
+    /// for color in &[orange, rust, transparent] {
+    ///     assert_eq!(&Color::from_webcolor(&color.to_webcolor_short()).unwrap(), color);
+    ///     assert_eq!(&color.to_webcolor_short().parse::<Color>().unwrap(), color);
+    /// }
+    /// ```
+    pub fn to_webcolor_short(&self) -> String {
+        if self.is_shortable() {
+            format!(
+                "#{:X}{:X}{:X}",
+                self.red / 17,
+                self.green / 17,
+                self.blue / 17
+            )
+        } else {
+            self.as_webcolor()
+        }
+    }
+
+    /// Same as `as_webcolor` but with lowercase hex digits, e.g. `#dea584`.
+    ///
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// let rust = Color::from_webcolor("#DEA584").unwrap();
+    /// assert_eq!(rust.to_webcolor_lowercase(), "#dea584");
+    /// assert_eq!(
+    ///     Color::from_webcolor(&rust.to_webcolor_lowercase()),
+    ///     Color::from_webcolor(&rust.as_webcolor())
+    /// );
+    /// ```
+    pub fn to_webcolor_lowercase(&self) -> String {
+        self.as_webcolor().to_lowercase()
+    }
+
+    /// Name of the CSS named color that is exactly this color, the first in
+    /// alphabetical order for colors with several names. Colors that are not
+    /// opaque have no name.
+    ///
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// assert_eq!(Color::from_webcolor("#FF0000").unwrap().to_named_css(), Some("red"));
+    /// assert_eq!(Color::from_webcolor("#00FFFF").unwrap().to_named_css(), Some("aqua"));
+    /// assert_eq!(Color::from_webcolor("#1A2B3C").unwrap().to_named_css(), None);
+    /// ```
+    pub fn to_named_css(&self) -> Option<&'static str> {
+        if self.alpha != 255 {
+            return None;
+        }
+
+        CSS_NAMED_COLORS
+            .iter()
+            .find(|(_, red, green, blue)| self.as_tuple() == (*red, *green, *blue))
+            .map(|(name, ..)| *name)
+    }
+
+    /// CSS named color that is closest to this color, see `nearest_in_set`,
+    /// and its CIEDE2000 difference to this color.
+    ///
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// assert_eq!(Color::from_webcolor("#FF0000").unwrap().nearest_named_css(), ("red", 0.0));
+    ///
+    /// let (name, delta_e) = Color::from_webcolor("#DEA584").unwrap().nearest_named_css();
+    /// assert_eq!(name, "burlywood");
+    /// assert!(delta_e > 0.0);
+    /// ```
+    pub fn nearest_named_css(&self) -> (&'static str, f64) {
+        let named = CSS_NAMED_COLORS
+            .iter()
+            .map(|(name, red, green, blue)| (*name, Color::from_rgb(*red, *green, *blue)))
+            .collect::<Vec<_>>();
+
+        let (nearest, _) = self
+            .nearest_in_set(named.iter().map(|(name, color)| (*name, color)))
+            .expect("css named colors are not empty");
+        let (name, color) = named
+            .iter()
+            .find(|(name, _)| *name == nearest)
+            .expect("nearest css named color is in the list");
+
+        (name, self.delta_e_2000(color))
+    }
+
+    /// The `#` prefix and the uppercase hex digits of each channel, e.g.
+    /// `("#", "DE", "A5", "84")` for `#DEA584`. Useful in templates that
+    /// reference the channels on their own.
+    pub fn to_rgb_hex_parts(&self) -> (&'static str, String, String, String) {
+        (
+            "#",
+            format!("{:02X}", self.red),
+            format!("{:02X}", self.green),
+            format!("{:02X}", self.blue),
+        )
+    }
+
+    /// The three color channels encoded as standard base64, e.g. `3qWE` for
+    /// `#DEA584`.
+    ///
+    /// ```
+    /// use language_colors::color::{
+    ///     Color,
+    ///     ColorParseError,
+    /// };
+    ///
+    /// let rust = Color::from_webcolor("#DEA584").unwrap();
+    /// assert_eq!(rust.to_base64(), "3qWE");
+    /// assert_eq!(rust.to_base64().len(), 4);
+    /// assert_eq!(Color::from_base64(&rust.to_base64()), Ok(rust));
+    ///
+    /// assert!(matches!(Color::from_base64("3q!E"), Err(ColorParseError::InvalidBase64(_))));
+    /// assert_eq!(Color::from_base64("3qWEAA=="), Err(ColorParseError::InvalidLength(4)));
+    /// ```
+    pub fn to_base64(&self) -> String {
+        base64::encode(&self.as_array())
+    }
+
+    pub fn from_base64(s: &str) -> Result<Color, ColorParseError> {
+        let bytes =
+            base64::decode(s).map_err(|err| ColorParseError::InvalidBase64(err.to_string()))?;
+
+        match bytes.as_slice() {
+            [red, green, blue] => Ok(Color::from_rgb(*red, *green, *blue)),
+            _ => Err(ColorParseError::InvalidLength(bytes.len())),
+        }
+    }
+
+    pub fn euclidean_distance(&self, other: &Color) -> f64 {
+        let p_red = (other.red - self.red).pow(2);
+        let p_green = (other.green - self.green).pow(2);
+        let p_blue = (other.blue - self.blue).pow(2);
+
+        ((p_red + p_green + p_blue) as f64).sqrt()
+    }
+
+    /// Average of `colors` in `space`, `None` if there are no colors. The
+    /// alpha channel is always averaged directly.
+    ///
+    /// ```
+    /// use language_colors::color::{
+    ///     AverageSpace,
+    ///     Color,
+    /// };
+    ///
+    /// let colors = [Color::from_webcolor("#FF0000").unwrap(), Color::from_webcolor("#00FF00").unwrap()];
+    /// let average = |space| Color::average(&colors, space).unwrap();
+    ///
+    /// assert_eq!(average(AverageSpace::Srgb).as_webcolor(), "#808000");
+    /// assert_eq!(average(AverageSpace::Linear).as_webcolor(), "#BCBC00");
+    /// assert!(
+    ///     average(AverageSpace::Linear).relative_luminance()
+    ///         > average(AverageSpace::Srgb).relative_luminance() * 1.5
+    /// );
+    /// assert_eq!(average(AverageSpace::Lab).as_webcolor(), "#C9AB00");
+    ///
+    /// assert_eq!(Color::average(&[], AverageSpace::Linear), None);
+    /// ```
+    pub fn average<'a>(
+        colors: impl IntoIterator<Item = &'a Color>,
+        space: AverageSpace,
+    ) -> Option<Color> {
+        Color::weighted_average(colors.into_iter().map(|color| (color, 1.0)), space)
+    }
+
+    fn weighted_average<'a>(
+        colors: impl IntoIterator<Item = (&'a Color, f64)>,
+        space: AverageSpace,
+    ) -> Option<Color> {
+        let mut total = 0.0;
+        let mut sums = [0.0; 4];
+
+        for (color, weight) in colors {
+            let [x, y, z] = match space {
+                AverageSpace::Srgb => [color.red as f64, color.green as f64, color.blue as f64],
+                AverageSpace::Linear => color.linear_rgb(),
+                AverageSpace::Lab => {
+                    let (lightness, a, b) = color.to_lab();
+                    [lightness, a, b]
+                }
+            };
+
+            for (sum, value) in sums.iter_mut().zip([x, y, z, color.alpha as f64]) {
+                *sum += weight * value;
+            }
+            total += weight;
+        }
+
+        if total == 0.0 {
+            return None;
+        }
+
+        let [x, y, z, alpha] = sums.map(|sum| sum / total);
+        let mut average = match space {
+            AverageSpace::Srgb => {
+                Color::from_rgb(x.round() as u8, y.round() as u8, z.round() as u8)
+            }
+            AverageSpace::Linear => Color::from_linear_rgb([x, y, z]),
+            AverageSpace::Lab => Color::from_lab(x, y, z),
+        };
+        average.alpha = alpha.round() as i64;
+
+        Some(average)
+    }
+
+    /// Linear interpolation in linear sRGB from this color (`t` of `0.0`) to
+    /// `other` (`t` of `1.0`), including the alpha channel. `t` is clamped to
+    /// `0.0..=1.0`.
+    pub fn mix(&self, other: &Color, t: f64) -> Color {
+        let t = t.clamp(0.0, 1.0);
+
+        Color::weighted_average([(self, 1.0 - t), (other, t)], AverageSpace::Linear)
+            .expect("can not mix colors without weight")
+    }
+
+    /// `steps` evenly spaced colors from this color to `other`, both
+    /// included.
+    ///
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// let gradient = Color::from_webcolor("#000000").unwrap()
+    ///     .transition_to(&Color::from_webcolor("#FFFFFF").unwrap(), 3)
+    ///     .map(|color| color.as_webcolor())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(gradient, ["#000000", "#BCBCBC", "#FFFFFF"]);
+    ///
+    /// let red = Color::from_webcolor("#FF0000").unwrap();
+    /// assert_eq!(red.transition_to(&Color::BLACK, 1).collect::<Vec<_>>(), [red.clone()]);
+    /// assert_eq!(red.transition_to(&Color::BLACK, 0).count(), 0);
+    /// ```
+    pub fn transition_to(&self, other: &Color, steps: usize) -> ColorTransition {
+        ColorTransition {
+            start: self.clone(),
+            end: other.clone(),
+            steps,
+            step: 0,
+        }
+    }
+
+    /// Composites this color over `background` ("source over"). The
+    /// background is treated as opaque, so the result is always opaque.
+    ///
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// let transparent = Color::from_webcolor("#DEA58400").unwrap();
+    /// assert_eq!(transparent.blend(&Color::WHITE), Color::WHITE);
+    ///
+    /// let opaque = Color::from_webcolor("#DEA584").unwrap();
+    /// assert_eq!(opaque.blend(&Color::WHITE), opaque);
+    /// ```
+    pub fn blend(&self, background: &Color) -> Color {
+        let channel = |source: i64, destination: i64| {
+            // Rounded integer version of
+            // source * alpha + destination * (1 - alpha).
+            (source * self.alpha + destination * (255 - self.alpha) + 127) / 255
+        };
+
+        Color {
+            red: channel(self.red, background.red),
+            green: channel(self.green, background.green),
+            blue: channel(self.blue, background.blue),
+            alpha: 255,
+        }
+    }
+
+    /// Like `euclidean_distance` but with the alpha channel as a fourth
+    /// dimension.
+    pub fn euclidean_distance_with_alpha(&self, other: &Color) -> f64 {
+        let p_alpha = (other.alpha - self.alpha).pow(2);
+
+        (self.euclidean_distance(other).powi(2) + p_alpha as f64).sqrt()
+    }
+
+    /// Whether the color is perceived as dark, based on its YIQ brightness.
+    pub fn is_dark(&self) -> bool {
+        let brightness = (self.red * 299 + self.green * 587 + self.blue * 114) / 1000;
+
+        brightness < 128
+    }
+
+    /// Text color that is readable on top of this color: white on dark colors
+    /// and black on light ones.
+    pub fn suggested_text_color(&self) -> Color {
+        if self.is_dark() {
+            Color::WHITE
+        } else {
+            Color::BLACK
+        }
+    }
+
+    /// WCAG 2 relative luminance from `0.0` (black) to `1.0` (white).
+    pub fn relative_luminance(&self) -> f64 {
+        let [red, green, blue] = self.linear_rgb();
+
+        0.2126 * red + 0.7152 * green + 0.0722 * blue
+    }
+
+    /// WCAG 2 contrast ratio between this color and `other`, from `1.0` to
+    /// `21.0`.
+    pub fn contrast_ratio(&self, other: &Color) -> f64 {
+        let a = self.relative_luminance();
+        let b = other.relative_luminance();
+
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// CIELAB lightness, a and b with a D65 white point.
+    pub fn to_lab(&self) -> (f64, f64, f64) {
+        let [red, green, blue] = self.linear_rgb();
+
+        let x = (0.4124 * red + 0.3576 * green + 0.1805 * blue) / 0.95047;
+        let y = 0.2126 * red + 0.7152 * green + 0.0722 * blue;
+        let z = (0.0193 * red + 0.1192 * green + 0.9505 * blue) / 1.08883;
+
+        let f = |t: f64| {
+            if t > 216.0 / 24389.0 {
+                t.cbrt()
+            } else {
+                (24389.0 / 27.0 * t + 16.0) / 116.0
+            }
+        };
+
+        (
+            116.0 * f(y) - 16.0,
+            500.0 * (f(x) - f(y)),
+            200.0 * (f(y) - f(z)),
+        )
+    }
+
+    /// Opaque color from CIELAB lightness, a and b, the inverse of `to_lab`.
+    /// Colors outside of sRGB are clamped.
+    pub fn from_lab(lightness: f64, a: f64, b: f64) -> Color {
+        let f_y = (lightness + 16.0) / 116.0;
+        let f_x = f_y + a / 500.0;
+        let f_z = f_y - b / 200.0;
+
+        let f_inverse = |t: f64| {
+            if t.powi(3) > 216.0 / 24389.0 {
+                t.powi(3)
+            } else {
+                (116.0 * t - 16.0) / (24389.0 / 27.0)
+            }
+        };
+
+        let x = f_inverse(f_x) * 0.95047;
+        let y = f_inverse(f_y);
+        let z = f_inverse(f_z) * 1.08883;
+
+        Color::from_linear_rgb([
+            3.2406 * x - 1.5372 * y - 0.4986 * z,
+            -0.9689 * x + 1.8758 * y + 0.0415 * z,
+            0.0557 * x - 0.2040 * y + 1.0570 * z,
+        ])
+    }
+
+    /// CIE76 color difference, the euclidean distance in CIELAB.
+    pub fn delta_e_76(&self, other: &Color) -> f64 {
+        let (l1, a1, b1) = self.to_lab();
+        let (l2, a2, b2) = other.to_lab();
+
+        ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+    }
+
+    /// CIEDE2000 color difference, about `1.0` is the smallest difference
+    /// that is noticeable. Sharma, Wu and Dalal, "The CIEDE2000
+    /// color-difference formula" (2005).
+    pub fn delta_e_2000(&self, other: &Color) -> f64 {
+        let (l1, a1, b1) = self.to_lab();
+        let (l2, a2, b2) = other.to_lab();
+
+        let c_mean = (a1.hypot(b1) + a2.hypot(b2)) / 2.0;
+        let g = 0.5 * (1.0 - (c_mean.powi(7) / (c_mean.powi(7) + 25f64.powi(7))).sqrt());
+
+        let a1 = a1 * (1.0 + g);
+        let a2 = a2 * (1.0 + g);
+        let c1 = a1.hypot(b1);
+        let c2 = a2.hypot(b2);
+        let hue = |a: f64, b: f64| {
+            if a == 0.0 && b == 0.0 {
+                0.0
+            } else {
+                b.atan2(a).to_degrees().rem_euclid(360.0)
+            }
+        };
+        let h1 = hue(a1, b1);
+        let h2 = hue(a2, b2);
+
+        let delta_l = l2 - l1;
+        let delta_c = c2 - c1;
+        let delta_h = if c1 * c2 == 0.0 {
+            0.0
+        } else if (h2 - h1).abs() <= 180.0 {
+            h2 - h1
+        } else if h2 <= h1 {
+            h2 - h1 + 360.0
+        } else {
+            h2 - h1 - 360.0
+        };
+        let delta_h = 2.0 * (c1 * c2).sqrt() * (delta_h / 2.0).to_radians().sin();
+
+        let l_mean = (l1 + l2) / 2.0;
+        let c_mean = (c1 + c2) / 2.0;
+        let h_mean = if c1 * c2 == 0.0 {
+            h1 + h2
+        } else if (h1 - h2).abs() <= 180.0 {
+            (h1 + h2) / 2.0
+        } else if h1 + h2 < 360.0 {
+            (h1 + h2 + 360.0) / 2.0
+        } else {
+            (h1 + h2 - 360.0) / 2.0
+        };
+
+        let t = 1.0 - 0.17 * (h_mean - 30.0).to_radians().cos()
+            + 0.24 * (2.0 * h_mean).to_radians().cos()
+            + 0.32 * (3.0 * h_mean + 6.0).to_radians().cos()
+            - 0.20 * (4.0 * h_mean - 63.0).to_radians().cos();
+
+        let s_l = 1.0 + 0.015 * (l_mean - 50.0).powi(2) / (20.0 + (l_mean - 50.0).powi(2)).sqrt();
+        let s_c = 1.0 + 0.045 * c_mean;
+        let s_h = 1.0 + 0.015 * c_mean * t;
+
+        let delta_theta = 30.0 * (-((h_mean - 275.0) / 25.0).powi(2)).exp();
+        let r_c = 2.0 * (c_mean.powi(7) / (c_mean.powi(7) + 25f64.powi(7))).sqrt();
+        let r_t = -r_c * (2.0 * delta_theta).to_radians().sin();
+
+        ((delta_l / s_l).powi(2)
+            + (delta_c / s_c).powi(2)
+            + (delta_h / s_h).powi(2)
+            + r_t * (delta_c / s_c) * (delta_h / s_h))
+            .sqrt()
+    }
+
+    /// OKLab lightness, a and b. Björn Ottosson, "A perceptual color space
+    /// for image processing" (2020).
+    ///
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// let close = |color: &str, expected: (f64, f64, f64)| {
+    ///     let (l, a, b) = Color::from_webcolor(color).unwrap().to_oklab();
+    ///     (l - expected.0).abs() < 1e-4
+    ///         && (a - expected.1).abs() < 1e-4
+    ///         && (b - expected.2).abs() < 1e-4
+    /// };
+    ///
+    /// assert!(close("#FFFFFF", (1.0, 0.0, 0.0)));
+    /// assert!(close("#000000", (0.0, 0.0, 0.0)));
+    /// assert!(close("#FF0000", (0.627955, 0.224863, 0.125846)));
+    /// assert!(close("#00FF00", (0.866440, -0.233888, 0.179498)));
+    /// assert!(close("#0000FF", (0.452014, -0.032457, -0.311528)));
+    ///
+    /// let rust = Color::from_webcolor("#DEA584").unwrap();
+    /// let (l, a, b) = rust.to_oklab();
+    /// assert_eq!(Color::from_oklab(l, a, b), rust);
+    /// ```
+    pub fn to_oklab(&self) -> (f64, f64, f64) {
+        let [red, green, blue] = self.linear_rgb();
+
+        let l = (0.4122214708 * red + 0.5363325363 * green + 0.0514459929 * blue).cbrt();
+        let m = (0.2119034982 * red + 0.6806995451 * green + 0.1073969566 * blue).cbrt();
+        let s = (0.0883024619 * red + 0.2817188376 * green + 0.6299787005 * blue).cbrt();
+
+        (
+            0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+        )
+    }
+
+    /// Opaque color from OKLab lightness, a and b, the inverse of
+    /// `to_oklab`. Colors outside of sRGB are clamped.
+    pub fn from_oklab(lightness: f64, a: f64, b: f64) -> Color {
+        let l = (lightness + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+        let m = (lightness - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+        let s = (lightness - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+
+        Color::from_linear_rgb([
+            4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+            -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+            -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+        ])
+    }
+
+    /// Euclidean distance in OKLab, a cheaper alternative to `delta_e_2000`.
+    /// About `0.02` is the smallest difference that is noticeable.
+    pub fn oklab_distance(&self, other: &Color) -> f64 {
+        let (l1, a1, b1) = self.to_oklab();
+        let (l2, a2, b2) = other.to_oklab();
+
+        ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+    }
+
+    /// Channels with the sRGB gamma removed, from `0.0` to `1.0`.
+    fn linear_rgb(&self) -> [f64; 3] {
+        let linear = |channel: i64| {
+            let channel = channel as f64 / 255.0;
+            if channel <= 0.04045 {
+                channel / 12.92
+            } else {
+                ((channel + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        [linear(self.red), linear(self.green), linear(self.blue)]
+    }
+
+    /// Opaque color from channels without the sRGB gamma, the inverse of
+    /// `linear_rgb`. Channels outside of `0.0..=1.0` are clamped.
+    fn from_linear_rgb([red, green, blue]: [f64; 3]) -> Color {
+        let channel = |linear: f64| {
+            let encoded = if linear <= 0.0031308 {
+                12.92 * linear
+            } else {
+                1.055 * linear.powf(1.0 / 2.4) - 0.055
+            };
+
+            (encoded * 255.0).round().clamp(0.0, 255.0) as u8
+        };
+
+        Color::from_rgb(channel(red), channel(green), channel(blue))
+    }
+
+    /// Hue in degrees (`0.0..360.0`), saturation and lightness (both
+    /// `0.0..=1.0`).
+    pub fn to_hsl(&self) -> (f64, f64, f64) {
+        let red = self.red as f64 / 255.0;
+        let green = self.green as f64 / 255.0;
+        let blue = self.blue as f64 / 255.0;
+
+        let max = red.max(green).max(blue);
+        let min = red.min(green).min(blue);
+        let delta = max - min;
+
+        let lightness = (max + min) / 2.0;
+
+        if delta == 0.0 {
+            return (0.0, 0.0, lightness);
+        }
+
+        let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+
+        let hue = if max == red {
+            60.0 * (((green - blue) / delta) % 6.0)
+        } else if max == green {
+            60.0 * ((blue - red) / delta + 2.0)
+        } else {
+            60.0 * ((red - green) / delta + 4.0)
+        };
+
+        let hue = if hue < 0.0 { hue + 360.0 } else { hue };
+
+        (hue, saturation, lightness)
+    }
+
+    /// HSL saturation from `0.0` to `1.0`, see `to_hsl`.
+    ///
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// assert_eq!(Color::from_webcolor("#FF0000").unwrap().saturation(), 1.0);
+    /// assert_eq!(Color::from_webcolor("#808080").unwrap().saturation(), 0.0);
+    /// ```
+    pub fn saturation(&self) -> f64 {
+        self.to_hsl().1
+    }
+
+    /// HSL lightness from `0.0` to `1.0`, see `to_hsl`.
+    ///
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// assert_eq!(Color::from_webcolor("#FF0000").unwrap().lightness(), 0.5);
+    /// assert_eq!(Color::from_webcolor("#FFFFFF").unwrap().lightness(), 1.0);
+    /// ```
+    pub fn lightness(&self) -> f64 {
+        self.to_hsl().2
+    }
+
+    /// Opaque color from a hue in degrees and a saturation and lightness from
+    /// `0.0` to `1.0`, the inverse of `to_hsl`.
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Color {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+        let (red, green, blue) = match sector as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+
+        let offset = lightness - chroma / 2.0;
+        let channel = |value: f64| ((value + offset) * 255.0).round().clamp(0.0, 255.0) as u8;
+
+        Color::from_rgb(channel(red), channel(green), channel(blue))
+    }
+
+    /// Signed HSL offset of this color from `base`: the hue difference in
+    /// degrees the short way around the wheel (`-180.0..=180.0`), the
+    /// saturation and lightness differences from `-1.0` to `1.0`. The hue of
+    /// a gray is `0.0`, like in `to_hsl`.
+    ///
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// let orange = Color::from_webcolor("#FF8000").unwrap();
+    /// let red = Color::from_webcolor("#FF0000").unwrap();
+    /// let (hue, saturation, lightness) = red.relative_to(&orange);
+    /// assert!((hue + 30.0).abs() < 0.5);
+    /// assert_eq!((saturation, lightness), (0.0, 0.0));
+    ///
+    /// let python = Color::from_webcolor("#3572A5").unwrap();
+    /// let rust = Color::from_webcolor("#DEA584").unwrap();
+    /// let delta = rust.relative_to(&python);
+    /// assert_eq!(rust.apply_relative(&python, delta), rust);
+    /// ```
+    pub fn relative_to(&self, base: &Color) -> (f64, f64, f64) {
+        let (hue, saturation, lightness) = self.to_hsl();
+        let (base_hue, base_saturation, base_lightness) = base.to_hsl();
+
+        let mut hue = (hue - base_hue).rem_euclid(360.0);
+        if hue > 180.0 {
+            hue -= 360.0;
+        }
+
+        (
+            hue,
+            saturation - base_saturation,
+            lightness - base_lightness,
+        )
+    }
+
+    /// Inverse of `relative_to`: the color that is offset from `base` by
+    /// `delta`, with the alpha of this color. The saturation and lightness
+    /// are clamped to `0.0..=1.0`.
+    ///
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// let python = Color::from_webcolor("#3572A5").unwrap();
+    /// let rust = Color::from_webcolor("#DEA584").unwrap();
+    /// assert_eq!(rust.apply_relative(&python, rust.relative_to(&python)), rust);
+    ///
+    /// let white = Color::BLACK.apply_relative(&Color::WHITE, (0.0, 0.0, 0.5));
+    /// assert_eq!(white, Color::WHITE);
+    /// ```
+    pub fn apply_relative(&self, base: &Color, delta: (f64, f64, f64)) -> Color {
+        let (hue, saturation, lightness) = delta;
+        let (base_hue, base_saturation, base_lightness) = base.to_hsl();
+
+        Color {
+            alpha: self.alpha,
+            ..Color::from_hsl(
+                base_hue + hue,
+                (base_saturation + saturation).clamp(0.0, 1.0),
+                (base_lightness + lightness).clamp(0.0, 1.0),
+            )
+        }
+    }
+
+    /// The same color with its HSL saturation replaced by `saturation`.
+    pub fn with_saturation(&self, saturation: f64) -> Color {
+        let (hue, _, lightness) = self.to_hsl();
+
+        Color {
+            alpha: self.alpha,
+            ..Color::from_hsl(hue, saturation, lightness)
+        }
+    }
+
+    /// Difference between the largest and the smallest channel from `0.0`
+    /// to `1.0`. Unlike the saturation it does not grow for very dark or
+    /// very light colors.
+    pub fn chroma(&self) -> f64 {
+        let max = self.red.max(self.green).max(self.blue);
+        let min = self.red.min(self.green).min(self.blue);
+
+        (max - min) as f64 / 255.0
+    }
+
+    /// Language with the highest hsl saturation, the first by name on ties.
+    pub fn most_saturated(colors: &BTreeMap<String, Color>) -> Option<(&str, &Color)> {
+        extreme_by(colors, Color::saturation, true)
+    }
+
+    /// Language with the lowest hsl saturation, the first by name on ties.
+    pub fn least_saturated(colors: &BTreeMap<String, Color>) -> Option<(&str, &Color)> {
+        extreme_by(colors, Color::saturation, false)
+    }
+
+    /// Key for sorting colors so that they look ordered: the lightness band
+    /// (dark, medium or light, as `0.0`, `1.0` or `2.0`) first and the hue
+    /// within the band second.
+    pub fn perceptual_sort_key(&self) -> (OrderedFloat<f64>, OrderedFloat<f64>) {
+        let (hue, _, lightness) = self.to_hsl();
+        let band = (lightness * LIGHTNESS_BANDS)
+            .floor()
+            .min(LIGHTNESS_BANDS - 1.0);
+
+        (OrderedFloat(band), OrderedFloat(hue))
+    }
+
+    /// Name of the color family the color belongs to, one of
+    /// `COLOR_FAMILIES`. Very dark and very light colors are `black` and
+    /// `white`, unsaturated colors `gray`, dark oranges and yellows `brown`
+    /// and light reds `pink`, everything else is named by its hue.
+    pub fn perceptual_group(&self) -> &'static str {
+        let (hue, saturation, lightness) = self.to_hsl();
+
+        if lightness < 0.1 {
+            return "black";
+        }
+
+        if lightness > 0.93 {
+            return "white";
+        }
+
+        if saturation < 0.12 {
+            return "gray";
+        }
+
+        match hue {
+            hue if !(15.0..345.0).contains(&hue) => {
+                if lightness > 0.7 {
+                    "pink"
+                } else {
+                    "red"
+                }
+            }
+            hue if hue < 70.0 && lightness < 0.4 => "brown",
+            hue if hue < 45.0 => "orange",
+            hue if hue < 70.0 => "yellow",
+            hue if hue < 165.0 => "green",
+            hue if hue < 195.0 => "cyan",
+            hue if hue < 255.0 => "blue",
+            hue if hue < 290.0 => "purple",
+            hue if hue < 330.0 => "magenta",
+            _ => "pink",
+        }
+    }
+
+    /// Color of an index of the xterm 256 color palette.
+    pub fn from_terminal_256(index: u8) -> Color {
+        match index {
+            0..=15 => {
+                let (red, green, blue) = TERMINAL_SYSTEM_COLORS[usize::from(index)];
+                Color::from_rgb(red, green, blue)
+            }
+            16..=231 => {
+                let cube = usize::from(index - 16);
+                Color::from_rgb(
+                    TERMINAL_CUBE_LEVELS[cube / 36],
+                    TERMINAL_CUBE_LEVELS[cube / 6 % 6],
+                    TERMINAL_CUBE_LEVELS[cube % 6],
+                )
+            }
+            _ => {
+                let gray = 8 + (index - 232) * 10;
+                Color::from_rgb(gray, gray, gray)
+            }
+        }
+    }
+
+    /// Index of the closest color in the xterm 256 color palette for
+    /// terminals without true color support. Only the color cube and the
+    /// grayscale ramp are considered, as terminal themes usually change the
+    /// 16 system colors.
+    pub fn to_terminal_256(&self) -> u8 {
+        (16..=255)
+            .min_by_key(|index| {
+                OrderedFloat(self.euclidean_distance(&Color::from_terminal_256(*index)))
+            })
+            .unwrap()
+    }
+
+    /// Name and distance of the color in `set` closest to this one. On equal
+    /// distances the name that sorts first wins, independent of the order of
+    /// `set`.
+    ///
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// let shared = Color::from_webcolor("#555555").unwrap();
+    /// let set = [("C++", &shared), ("C", &shared)];
+    ///
+    /// let black = Color::from_webcolor("#000000").unwrap();
+    /// assert_eq!(black.nearest_in_set(set.iter().copied()).unwrap().0, "C");
+    /// assert_eq!(black.nearest_in_set(set.iter().rev().copied()).unwrap().0, "C");
+    /// ```
+    pub fn nearest_in_set<'a>(
+        &self,
+        set: impl IntoIterator<Item = (&'a str, &'a Color)>,
+    ) -> Option<(&'a str, f64)> {
+        let mut nearest: Option<(&'a str, f64)> = None;
+
+        for (name, color) in set {
+            let distance = self.euclidean_distance(color);
+
+            match nearest {
+                Some((nearest_name, nearest_distance))
+                    if nearest_distance < distance
+                        || (nearest_distance == distance && nearest_name <= name) => {}
+                _ => nearest = Some((name, distance)),
+            }
+        }
+
+        nearest
+    }
+}
+
+/// `Color` from a hex literal like `color!(#FF8C00)` or `color!(#FF8C0080)`,
+/// checked at compile time.
+///
+/// ```
+/// use language_colors::color;
+/// use language_colors::color::Color;
+///
+/// assert_eq!(color!(#FF8C00), Color::from_webcolor("#FF8C00").unwrap());
+/// assert_eq!(color!(#00ADD8).as_webcolor(), "#00ADD8");
+/// assert_eq!(color!(#1e90ff).as_webcolor(), "#1E90FF");
+/// assert_eq!(color!(#DEA58480).alpha(), 0x80);
+/// ```
+///
+/// ```compile_fail
+/// let color = language_colors::color!(#FF8C0);
+/// ```
+///
+/// ```compile_fail
+/// let color = language_colors::color!(#GG8C00);
+/// ```
+#[macro_export]
+macro_rules! color {
+    (#$($hex:tt)+) => {{
+        const COLOR: $crate::color::Color =
+            $crate::color::Color::from_hex_literal(stringify!($($hex)+));
+        COLOR
+    }};
+}
+
+/// Conversions to and from the clear colors of wgpu, whose channels go from
+/// `0.0` to `1.0`.
+#[cfg(feature = "wgpu")]
+impl Color {
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// let rust = Color::from_webcolor("#DEA58480").unwrap();
+    /// let converted = rust.to_wgpu_color();
+    /// assert_eq!(converted.r, 222.0 / 255.0);
+    /// assert_eq!(converted.a, 128.0 / 255.0);
+    /// assert_eq!(Color::from_wgpu_color(converted), rust);
+    /// ```
+    pub fn to_wgpu_color(&self) -> wgpu::Color {
+        wgpu::Color {
+            r: self.red as f64 / 255.0,
+            g: self.green as f64 / 255.0,
+            b: self.blue as f64 / 255.0,
+            a: self.alpha as f64 / 255.0,
+        }
+    }
+
+    /// Channels outside of `0.0..=1.0`, as used for HDR targets, are
+    /// clamped.
+    pub fn from_wgpu_color(color: wgpu::Color) -> Color {
+        let channel = |value: f64| (value.clamp(0.0, 1.0) * 255.0).round() as i64;
+
+        Color {
+            red: channel(color.r),
+            green: channel(color.g),
+            blue: channel(color.b),
+            alpha: channel(color.a),
+        }
+    }
+}
+
+/// Conversions to and from the colors of egui.
+#[cfg(feature = "egui")]
+impl Color {
+    /// ```
+    /// use language_colors::color::Color;
+    ///
+    /// let rust = Color::from_webcolor("#DEA584").unwrap();
+    /// let converted = rust.to_egui_color32();
+    /// assert_eq!(converted, egui::Color32::from_rgb(0xDE, 0xA5, 0x84));
+    /// assert_eq!(Color::from_egui_color32(converted), rust);
+    /// ```
+    pub fn to_egui_color32(&self) -> egui::Color32 {
+        let (red, green, blue) = self.as_tuple();
+
+        egui::Color32::from_rgba_unmultiplied(red, green, blue, self.alpha())
+    }
+
+    /// egui stores colors with premultiplied alpha, so translucent colors
+    /// can come back slightly different from the color they were created
+    /// from.
+    pub fn from_egui_color32(color: egui::Color32) -> Color {
+        let [red, green, blue, alpha] = color.to_srgba_unmultiplied();
+
+        Color::from_rgba(red, green, blue, alpha)
+    }
+}
+
+/// Iterator over the colors of `Color::transition_to`. A single step yields
+/// only the start color.
+#[derive(Debug, Clone)]
+pub struct ColorTransition {
+    start: Color,
+    end: Color,
+    steps: usize,
+    step: usize,
+}
+
+impl Iterator for ColorTransition {
+    type Item = Color;
+
+    fn next(&mut self) -> Option<Color> {
+        if self.step >= self.steps {
+            return None;
+        }
+
+        let t = if self.steps == 1 {
+            0.0
+        } else {
+            self.step as f64 / (self.steps - 1) as f64
+        };
+        self.step += 1;
+
+        Some(self.start.mix(&self.end, t))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.steps - self.step;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for ColorTransition {}
+
+/// Neutral gray `#808080`, used where a color is missing.
+impl Default for Color {
+    fn default() -> Self {
+        Color::from_rgb(128, 128, 128)
+    }
+}
+
+/// Same as `Color::from_webcolor`.
+impl FromStr for Color {
+    type Err = ColorParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Color::from_webcolor(s)
+    }
+}
+
+/// Parses a color in any of the formats users commonly paste, tried in this
+/// order:
+///
+/// 1. hex with or without `#`, like `str::parse`
+/// 2. `rgb(222, 165, 132)` like `Color::from_css_string`
+/// 3. `hsl(24, 57%, 69%)` with the hue in degrees
+/// 4. a css color name like `rebeccapurple`
+///
+/// Hex is tried first, so an input that is valid hex is never read as a
+/// name. Case and surrounding whitespace are ignored.
+///
+/// ```
+/// use language_colors::color::{
+///     parse_any_color,
+///     Color,
+/// };
+///
+/// let cases = [
+///     ("dea584", Some("#DEA584")),
+///     ("#DEA584", Some("#DEA584")),
+///     ("#dea58480", Some("#DEA58480")),
+///     ("rgb(222,165,132)", Some("#DEA584")),
+///     ("RGB( 222, 165, 132 )", Some("#DEA584")),
+///     ("hsl(24, 57%, 69%)", Some("#DDA783")),
+///     ("hsl(120deg, 100%, 25%)", Some("#008000")),
+///     ("rebeccapurple", Some("#663399")),
+///     (" Red ", Some("#FF0000")),
+///     ("beaded", Some("#BEADED")),
+///     ("#dea58", None),
+///     ("rgb(256, 0, 0)", None),
+///     ("rgb(1, 2)", None),
+///     ("hsl(24, 57%)", None),
+///     ("notacolor", None),
+///     ("", None),
+/// ];
+///
+/// for (input, expected) in &cases {
+///     let parsed = parse_any_color(input).ok().map(|color| color.as_webcolor());
+///     assert_eq!(parsed.as_deref(), *expected, "{}", input);
+/// }
+///
+/// let err = parse_any_color("notacolor").unwrap_err().to_string();
+/// assert!(err.contains("hex") && err.contains("rgb(") && err.contains("hsl("));
+/// assert!(err.contains("css color name"));
+/// ```
+pub fn parse_any_color(s: &str) -> Result<Color, ColorParseError> {
+    let input = s.trim().to_lowercase();
+    let unrecognized = || ColorParseError::Unrecognized(s.to_string());
+
+    if let Ok(color) = input.parse() {
+        return Ok(color);
+    }
+
+    if let Some(arguments) = function_arguments(&input, "rgb") {
+        return rgb_function(&arguments).ok_or_else(unrecognized);
+    }
+
+    if let Some(arguments) = function_arguments(&input, "hsl") {
+        return hsl_function(&arguments).ok_or_else(unrecognized);
+    }
+
+    CSS_NAMED_COLORS
+        .iter()
+        .find(|(name, ..)| *name == input)
+        .map(|(_, red, green, blue)| Color::from_rgb(*red, *green, *blue))
+        .ok_or_else(unrecognized)
+}
+
+/// Comma separated and trimmed arguments of `input` if it is a call of the
+/// function `name`, like `rgb(1, 2, 3)`.
+fn function_arguments<'a>(input: &'a str, name: &str) -> Option<Vec<&'a str>> {
+    let arguments = input
+        .strip_prefix(name)?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+
+    Some(arguments.split(',').map(str::trim).collect())
+}
+
+/// Color of the arguments of `rgb()`, three integers from 0 to 255 or three
+/// percentages.
+fn rgb_function(arguments: &[&str]) -> Option<Color> {
+    let percentages = arguments.iter().all(|channel| channel.ends_with('%'));
+
+    let channels = arguments
+        .iter()
+        .map(|channel| {
+            if !percentages {
+                return channel.parse::<u8>().ok();
+            }
+
+            let percentage = channel.trim_end_matches('%').parse::<f64>().ok()?;
+            if (0.0..=100.0).contains(&percentage) {
+                Some((percentage / 100.0 * 255.0).round() as u8)
+            } else {
+                None
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    match channels[..] {
+        [red, green, blue] => Some(Color::from_rgb(red, green, blue)),
+        _ => None,
+    }
+}
+
+/// Color of the arguments of `hsl()`, the hue in degrees with an optional
+/// `deg` and the saturation and lightness as percentages.
+fn hsl_function(arguments: &[&str]) -> Option<Color> {
+    match arguments {
+        [hue, saturation, lightness] => {
+            let hue = hue.trim_end_matches("deg").parse::<f64>().ok()?;
+            let saturation = saturation.strip_suffix('%')?.parse::<f64>().ok()?;
+            let lightness = lightness.strip_suffix('%')?.parse::<f64>().ok()?;
+
+            if !hue.is_finite()
+                || !(0.0..=100.0).contains(&saturation)
+                || !(0.0..=100.0).contains(&lightness)
+            {
+                return None;
+            }
+
+            Some(Color::from_hsl(hue, saturation / 100.0, lightness / 100.0))
+        }
+        _ => None,
+    }
+}
+
+/// Same as `str::parse`.
+///
+/// ```
+/// use std::convert::TryFrom;
+///
+/// use language_colors::color::Color;
+///
+/// assert_eq!(Color::try_from("#FF0000"), Ok(Color::from_rgb(255, 0, 0)));
+/// assert!(Color::try_from("red").is_err());
+/// ```
+impl TryFrom<&str> for Color {
+    type Error = ColorParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Same as `str::parse`.
+///
+/// ```
+/// use std::convert::TryFrom;
+///
+/// use language_colors::color::Color;
+///
+/// let color = String::from("#dea584");
+/// assert_eq!(Color::try_from(color), Ok(Color::from_rgb(0xDE, 0xA5, 0x84)));
+/// ```
+impl TryFrom<String> for Color {
+    type Error = ColorParseError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// Same as `Color::as_webcolor`, parsing the string gives back the color.
+///
+/// ```
+/// use std::convert::TryFrom;
+///
+/// use language_colors::color::Color;
+///
+/// let rust = Color::from_rgb(0xDE, 0xA5, 0x84);
+/// assert_eq!(Color::try_from(String::from(rust.clone())).unwrap(), rust);
+/// ```
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        color.as_webcolor()
+    }
+}
+
+/// Color ordered by `Color::perceptual_sort_key` instead of by channels.
+/// Colors with the same key fall back to the channel order so the ordering
+/// stays consistent with equality.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PerceptualColor(pub Color);
+
+impl Ord for PerceptualColor {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .perceptual_sort_key()
+            .cmp(&other.0.perceptual_sort_key())
+            .then_with(|| self.0.cmp(&other.0))
+    }
+}
+
+impl PartialOrd for PerceptualColor {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Language whose color has the highest (`most`) or lowest `key`, the first
+/// by name on ties.
+pub(crate) fn extreme_by(
+    colors: &BTreeMap<String, Color>,
+    key: impl Fn(&Color) -> f64,
+    most: bool,
+) -> Option<(&str, &Color)> {
+    let colors = colors.iter().map(|(name, color)| (name.as_str(), color));
+
+    if most {
+        colors.min_by_key(|(_, color)| Reverse(OrderedFloat(key(color))))
+    } else {
+        colors.min_by_key(|(_, color)| OrderedFloat(key(color)))
+    }
+}
+
+/// The 148 named colors of CSS in alphabetical order, including the
+/// aliases like `aqua` and `cyan` that share a color.
+const CSS_NAMED_COLORS: [(&str, u8, u8, u8); 148] = [
+    ("aliceblue", 0xF0, 0xF8, 0xFF),
+    ("antiquewhite", 0xFA, 0xEB, 0xD7),
+    ("aqua", 0x00, 0xFF, 0xFF),
+    ("aquamarine", 0x7F, 0xFF, 0xD4),
+    ("azure", 0xF0, 0xFF, 0xFF),
+    ("beige", 0xF5, 0xF5, 0xDC),
+    ("bisque", 0xFF, 0xE4, 0xC4),
+    ("black", 0x00, 0x00, 0x00),
+    ("blanchedalmond", 0xFF, 0xEB, 0xCD),
+    ("blue", 0x00, 0x00, 0xFF),
+    ("blueviolet", 0x8A, 0x2B, 0xE2),
+    ("brown", 0xA5, 0x2A, 0x2A),
+    ("burlywood", 0xDE, 0xB8, 0x87),
+    ("cadetblue", 0x5F, 0x9E, 0xA0),
+    ("chartreuse", 0x7F, 0xFF, 0x00),
+    ("chocolate", 0xD2, 0x69, 0x1E),
+    ("coral", 0xFF, 0x7F, 0x50),
+    ("cornflowerblue", 0x64, 0x95, 0xED),
+    ("cornsilk", 0xFF, 0xF8, 0xDC),
+    ("crimson", 0xDC, 0x14, 0x3C),
+    ("cyan", 0x00, 0xFF, 0xFF),
+    ("darkblue", 0x00, 0x00, 0x8B),
+    ("darkcyan", 0x00, 0x8B, 0x8B),
+    ("darkgoldenrod", 0xB8, 0x86, 0x0B),
+    ("darkgray", 0xA9, 0xA9, 0xA9),
+    ("darkgreen", 0x00, 0x64, 0x00),
+    ("darkgrey", 0xA9, 0xA9, 0xA9),
+    ("darkkhaki", 0xBD, 0xB7, 0x6B),
+    ("darkmagenta", 0x8B, 0x00, 0x8B),
+    ("darkolivegreen", 0x55, 0x6B, 0x2F),
+    ("darkorange", 0xFF, 0x8C, 0x00),
+    ("darkorchid", 0x99, 0x32, 0xCC),
+    ("darkred", 0x8B, 0x00, 0x00),
+    ("darksalmon", 0xE9, 0x96, 0x7A),
+    ("darkseagreen", 0x8F, 0xBC, 0x8F),
+    ("darkslateblue", 0x48, 0x3D, 0x8B),
+    ("darkslategray", 0x2F, 0x4F, 0x4F),
+    ("darkslategrey", 0x2F, 0x4F, 0x4F),
+    ("darkturquoise", 0x00, 0xCE, 0xD1),
+    ("darkviolet", 0x94, 0x00, 0xD3),
+    ("deeppink", 0xFF, 0x14, 0x93),
+    ("deepskyblue", 0x00, 0xBF, 0xFF),
+    ("dimgray", 0x69, 0x69, 0x69),
+    ("dimgrey", 0x69, 0x69, 0x69),
+    ("dodgerblue", 0x1E, 0x90, 0xFF),
+    ("firebrick", 0xB2, 0x22, 0x22),
+    ("floralwhite", 0xFF, 0xFA, 0xF0),
+    ("forestgreen", 0x22, 0x8B, 0x22),
+    ("fuchsia", 0xFF, 0x00, 0xFF),
+    ("gainsboro", 0xDC, 0xDC, 0xDC),
+    ("ghostwhite", 0xF8, 0xF8, 0xFF),
+    ("gold", 0xFF, 0xD7, 0x00),
+    ("goldenrod", 0xDA, 0xA5, 0x20),
+    ("gray", 0x80, 0x80, 0x80),
+    ("green", 0x00, 0x80, 0x00),
+    ("greenyellow", 0xAD, 0xFF, 0x2F),
+    ("grey", 0x80, 0x80, 0x80),
+    ("honeydew", 0xF0, 0xFF, 0xF0),
+    ("hotpink", 0xFF, 0x69, 0xB4),
+    ("indianred", 0xCD, 0x5C, 0x5C),
+    ("indigo", 0x4B, 0x00, 0x82),
+    ("ivory", 0xFF, 0xFF, 0xF0),
+    ("khaki", 0xF0, 0xE6, 0x8C),
+    ("lavender", 0xE6, 0xE6, 0xFA),
+    ("lavenderblush", 0xFF, 0xF0, 0xF5),
+    ("lawngreen", 0x7C, 0xFC, 0x00),
+    ("lemonchiffon", 0xFF, 0xFA, 0xCD),
+    ("lightblue", 0xAD, 0xD8, 0xE6),
+    ("lightcoral", 0xF0, 0x80, 0x80),
+    ("lightcyan", 0xE0, 0xFF, 0xFF),
+    ("lightgoldenrodyellow", 0xFA, 0xFA, 0xD2),
+    ("lightgray", 0xD3, 0xD3, 0xD3),
+    ("lightgreen", 0x90, 0xEE, 0x90),
+    ("lightgrey", 0xD3, 0xD3, 0xD3),
+    ("lightpink", 0xFF, 0xB6, 0xC1),
+    ("lightsalmon", 0xFF, 0xA0, 0x7A),
+    ("lightseagreen", 0x20, 0xB2, 0xAA),
+    ("lightskyblue", 0x87, 0xCE, 0xFA),
+    ("lightslategray", 0x77, 0x88, 0x99),
+    ("lightslategrey", 0x77, 0x88, 0x99),
+    ("lightsteelblue", 0xB0, 0xC4, 0xDE),
+    ("lightyellow", 0xFF, 0xFF, 0xE0),
+    ("lime", 0x00, 0xFF, 0x00),
+    ("limegreen", 0x32, 0xCD, 0x32),
+    ("linen", 0xFA, 0xF0, 0xE6),
+    ("magenta", 0xFF, 0x00, 0xFF),
+    ("maroon", 0x80, 0x00, 0x00),
+    ("mediumaquamarine", 0x66, 0xCD, 0xAA),
+    ("mediumblue", 0x00, 0x00, 0xCD),
+    ("mediumorchid", 0xBA, 0x55, 0xD3),
+    ("mediumpurple", 0x93, 0x70, 0xDB),
+    ("mediumseagreen", 0x3C, 0xB3, 0x71),
+    ("mediumslateblue", 0x7B, 0x68, 0xEE),
+    ("mediumspringgreen", 0x00, 0xFA, 0x9A),
+    ("mediumturquoise", 0x48, 0xD1, 0xCC),
+    ("mediumvioletred", 0xC7, 0x15, 0x85),
+    ("midnightblue", 0x19, 0x19, 0x70),
+    ("mintcream", 0xF5, 0xFF, 0xFA),
+    ("mistyrose", 0xFF, 0xE4, 0xE1),
+    ("moccasin", 0xFF, 0xE4, 0xB5),
+    ("navajowhite", 0xFF, 0xDE, 0xAD),
+    ("navy", 0x00, 0x00, 0x80),
+    ("oldlace", 0xFD, 0xF5, 0xE6),
+    ("olive", 0x80, 0x80, 0x00),
+    ("olivedrab", 0x6B, 0x8E, 0x23),
+    ("orange", 0xFF, 0xA5, 0x00),
+    ("orangered", 0xFF, 0x45, 0x00),
+    ("orchid", 0xDA, 0x70, 0xD6),
+    ("palegoldenrod", 0xEE, 0xE8, 0xAA),
+    ("palegreen", 0x98, 0xFB, 0x98),
+    ("paleturquoise", 0xAF, 0xEE, 0xEE),
+    ("palevioletred", 0xDB, 0x70, 0x93),
+    ("papayawhip", 0xFF, 0xEF, 0xD5),
+    ("peachpuff", 0xFF, 0xDA, 0xB9),
+    ("peru", 0xCD, 0x85, 0x3F),
+    ("pink", 0xFF, 0xC0, 0xCB),
+    ("plum", 0xDD, 0xA0, 0xDD),
+    ("powderblue", 0xB0, 0xE0, 0xE6),
+    ("purple", 0x80, 0x00, 0x80),
+    ("rebeccapurple", 0x66, 0x33, 0x99),
+    ("red", 0xFF, 0x00, 0x00),
+    ("rosybrown", 0xBC, 0x8F, 0x8F),
+    ("royalblue", 0x41, 0x69, 0xE1),
+    ("saddlebrown", 0x8B, 0x45, 0x13),
+    ("salmon", 0xFA, 0x80, 0x72),
+    ("sandybrown", 0xF4, 0xA4, 0x60),
+    ("seagreen", 0x2E, 0x8B, 0x57),
+    ("seashell", 0xFF, 0xF5, 0xEE),
+    ("sienna", 0xA0, 0x52, 0x2D),
+    ("silver", 0xC0, 0xC0, 0xC0),
+    ("skyblue", 0x87, 0xCE, 0xEB),
+    ("slateblue", 0x6A, 0x5A, 0xCD),
+    ("slategray", 0x70, 0x80, 0x90),
+    ("slategrey", 0x70, 0x80, 0x90),
+    ("snow", 0xFF, 0xFA, 0xFA),
+    ("springgreen", 0x00, 0xFF, 0x7F),
+    ("steelblue", 0x46, 0x82, 0xB4),
+    ("tan", 0xD2, 0xB4, 0x8C),
+    ("teal", 0x00, 0x80, 0x80),
+    ("thistle", 0xD8, 0xBF, 0xD8),
+    ("tomato", 0xFF, 0x63, 0x47),
+    ("turquoise", 0x40, 0xE0, 0xD0),
+    ("violet", 0xEE, 0x82, 0xEE),
+    ("wheat", 0xF5, 0xDE, 0xB3),
+    ("white", 0xFF, 0xFF, 0xFF),
+    ("whitesmoke", 0xF5, 0xF5, 0xF5),
+    ("yellow", 0xFF, 0xFF, 0x00),
+    ("yellowgreen", 0x9A, 0xCD, 0x32),
+];
@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+
+use crate::color::Color;
+
+/// Language whose color differs between the two sides of a diff.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangedColor {
+    pub language: String,
+    pub old: Color,
+    pub new: Color,
+    /// `Color::delta_e_2000` between the old and the new color.
+    pub distance: f64,
+}
+
+/// Differences between two sets of language colors, every list is ordered
+/// by name.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LanguageDiff {
+    pub added: Vec<(String, Color)>,
+    pub removed: Vec<(String, Color)>,
+    pub changed: Vec<ChangedColor>,
+}
+
+impl LanguageDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Languages that only have a color in `new`, only in `old` or have
+/// different colors in both.
+///
+/// ```
+/// use language_colors::diff::diff;
+/// use language_colors::output::html::{
+///     render_diff,
+///     PageOptions,
+/// };
+/// use language_colors::output::Metadata;
+/// use language_colors::render::LanguageColors;
+///
+/// let old = LanguageColors::from_yaml(
+///     r##"
+/// Rust:
+///   type: programming
+///   color: "#dea584"
+///   ace_mode: rust
+///   language_id: 327
+/// Go:
+///   type: programming
+///   color: "#375eab"
+///   ace_mode: golang
+///   language_id: 132
+/// Pascal:
+///   type: programming
+///   color: "#E3F171"
+///   ace_mode: pascal
+///   language_id: 281
+/// "##,
+/// )
+/// .unwrap();
+/// let new = LanguageColors::from_yaml(
+///     r##"
+/// Rust:
+///   type: programming
+///   color: "#dea584"
+///   ace_mode: rust
+///   language_id: 327
+/// Go:
+///   type: programming
+///   color: "#00ADD8"
+///   ace_mode: golang
+///   language_id: 132
+/// Zig:
+///   type: programming
+///   color: "#ec915c"
+///   ace_mode: zig
+///   language_id: 646424281
+/// "##,
+/// )
+/// .unwrap();
+///
+/// let changes = diff(&old.colors, &new.colors);
+/// assert_eq!(changes.added[0].0, "Zig");
+/// assert_eq!(changes.removed[0].0, "Pascal");
+/// assert_eq!(changes.changed.len(), 1);
+/// assert_eq!(changes.changed[0].language, "Go");
+/// assert!(changes.changed[0].distance > 10.0);
+///
+/// let html = render_diff(&changes, &Metadata::default(), &PageOptions::default());
+/// for expected in &["<h2>Added</h2>", "<h2>Removed</h2>", "<h2>Changed</h2>", "Zig", "#375EAB"] {
+///     assert!(html.contains(expected), "{}", expected);
+/// }
+/// assert!(!html.contains("Rust"));
+/// ```
+pub fn diff(old: &BTreeMap<String, Color>, new: &BTreeMap<String, Color>) -> LanguageDiff {
+    let mut diff = LanguageDiff::default();
+
+    for (language, color) in new {
+        match old.get(language) {
+            None => diff.added.push((language.clone(), color.clone())),
+            Some(old_color) if old_color != color => diff.changed.push(ChangedColor {
+                language: language.clone(),
+                old: old_color.clone(),
+                new: color.clone(),
+                distance: old_color.delta_e_2000(color),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    diff.removed = old
+        .iter()
+        .filter(|(language, _)| !new.contains_key(*language))
+        .map(|(language, color)| (language.clone(), color.clone()))
+        .collect();
+
+    diff
+}
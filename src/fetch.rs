@@ -0,0 +1,746 @@
+use std::env;
+use std::error::Error;
+use std::fmt;
+
+use futures::stream::{
+    self,
+    StreamExt,
+    TryStreamExt,
+};
+use language_colors::progress::Progress;
+use language_colors::validate::{
+    self,
+    ValidationError,
+};
+use tokio::runtime::{
+    Builder,
+    Runtime,
+};
+
+pub const LINGUIST_URL: &str =
+    "https://raw.githubusercontent.com/github/linguist/master/lib/linguist/languages.yml";
+const LINGUIST_RELEASE_URL: &str = "https://api.github.com/repos/github/linguist/releases/latest";
+const RAW_URL_PREFIX: &str = "https://raw.githubusercontent.com/";
+const API_URL_PREFIX: &str = "https://api.github.com/repos/";
+
+/// Number of sources that are downloaded at the same time.
+const MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
+#[derive(Debug)]
+pub struct FetchError {
+    url: String,
+    source: FetchErrorSource,
+}
+
+#[derive(Debug)]
+enum FetchErrorSource {
+    Request(reqwest::Error),
+    Invalid(ValidationError),
+    Decode(String),
+}
+
+impl FetchError {
+    /// The server couldn't be reached or the connection broke, as opposed to
+    /// the server answering with an error status or unusable content.
+    fn is_network(&self) -> bool {
+        match self.source {
+            FetchErrorSource::Request(ref err) => !err.is_status(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.source {
+            FetchErrorSource::Request(ref err) => write!(f, "can not fetch {}: {}", self.url, err),
+            FetchErrorSource::Invalid(ref err) => write!(f, "invalid {}: {}", self.url, err),
+            FetchErrorSource::Decode(ref err) => write!(f, "can not decode {}: {}", self.url, err),
+        }
+    }
+}
+
+impl Error for FetchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self.source {
+            FetchErrorSource::Request(ref err) => Some(err),
+            FetchErrorSource::Invalid(ref err) => Some(err),
+            FetchErrorSource::Decode(_) => None,
+        }
+    }
+}
+
+/// Blocking interface to the async http client. Only the downloads run on
+/// the runtime, everything else stays synchronous.
+pub struct Fetcher {
+    runtime: Runtime,
+    client: reqwest::Client,
+    max_bytes: u64,
+    fallback: Option<ContentsApi>,
+}
+
+/// Body of a download and the url that served it, which differs from the
+/// requested url when the download fell back to the contents api.
+pub struct Download {
+    pub url: String,
+    pub body: Vec<u8>,
+    /// ETag header of the response, if the server sent one.
+    pub etag: Option<String>,
+}
+
+impl Fetcher {
+    /// Downloads larger than `max_bytes` are aborted. With `fallback` raw
+    /// github urls that can't be reached are retried through the contents
+    /// api.
+    pub fn new(max_bytes: u64, fallback: bool) -> Self {
+        let runtime = Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("can not start async runtime");
+
+        Self {
+            runtime,
+            client: reqwest::Client::new(),
+            max_bytes,
+            fallback: if fallback {
+                Some(ContentsApi::default())
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Downloads all `urls` concurrently and returns them in the order of
+    /// `urls`. Fails with the url of the first download that failed.
+    pub fn fetch_all(
+        &self,
+        urls: &[String],
+        progress: &dyn Progress,
+    ) -> Result<Vec<Download>, FetchError> {
+        let _span = tracing::info_span!("fetch", urls = urls.len()).entered();
+
+        self.runtime.block_on(fetch_all(
+            &self.client,
+            urls,
+            self.max_bytes,
+            self.fallback.as_ref(),
+            progress,
+        ))
+    }
+
+    /// Tag of the latest release of github/linguist, e.g. `v7.26.0`. The
+    /// tags api lists tags by name and not by version, so the release is
+    /// asked for instead.
+    pub fn fetch_linguist_version(&self) -> Result<Option<String>, FetchError> {
+        self.fetch_release_tag(LINGUIST_RELEASE_URL)
+    }
+
+    fn fetch_release_tag(&self, url: &str) -> Result<Option<String>, FetchError> {
+        let release: serde_json::Value = self
+            .runtime
+            .block_on(async {
+                self.client
+                    .get(url)
+                    .header(reqwest::header::USER_AGENT, "language_colors")
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await
+            })
+            .map_err(|err| FetchError {
+                url: url.to_string(),
+                source: FetchErrorSource::Request(err),
+            })?;
+
+        Ok(release["tag_name"].as_str().map(str::to_string))
+    }
+}
+
+async fn fetch_all(
+    client: &reqwest::Client,
+    urls: &[String],
+    max_bytes: u64,
+    fallback: Option<&ContentsApi>,
+    progress: &dyn Progress,
+) -> Result<Vec<Download>, FetchError> {
+    // A single download gets a byte progress bar with its content length,
+    // multiple downloads share one counter with an unknown total.
+    let single = urls.len() == 1;
+
+    if !single {
+        progress.start("fetching", None);
+    }
+
+    let downloads = stream::iter(urls)
+        .map(|url| fetch_one(client, url, max_bytes, fallback, progress, single))
+        .buffered(MAX_CONCURRENT_DOWNLOADS)
+        .try_collect()
+        .await?;
+
+    if !single {
+        progress.finish();
+    }
+
+    Ok(downloads)
+}
+
+async fn fetch_one(
+    client: &reqwest::Client,
+    url: &str,
+    max_bytes: u64,
+    fallback: Option<&ContentsApi>,
+    progress: &dyn Progress,
+    report_length: bool,
+) -> Result<Download, FetchError> {
+    let error = match fetch_body(client.get(url), url, max_bytes, progress, report_length).await {
+        Ok((body, etag)) => {
+            tracing::info!(url, bytes = body.len(), "fetched");
+            return Ok(Download {
+                url: url.to_string(),
+                body,
+                etag,
+            });
+        }
+        Err(err) => err,
+    };
+
+    match fallback.and_then(|api| api.url(url)) {
+        Some(api_url) if error.is_network() => {
+            tracing::warn!(url, %error, "falling back to the contents api");
+            let body = fetch_contents(client, &api_url, max_bytes, progress).await?;
+            Ok(Download {
+                url: api_url,
+                body,
+                etag: None,
+            })
+        }
+        _ => Err(error),
+    }
+}
+
+/// Fetches a file through the github contents api, which returns the file
+/// base64 encoded inside a json object. Uses the `GITHUB_TOKEN` environment
+/// variable for authentication if it is set.
+async fn fetch_contents(
+    client: &reqwest::Client,
+    url: &str,
+    max_bytes: u64,
+    progress: &dyn Progress,
+) -> Result<Vec<u8>, FetchError> {
+    let mut request = client
+        .get(url)
+        .header(reqwest::header::USER_AGENT, "language_colors")
+        .header(reqwest::header::ACCEPT, "application/vnd.github.v3+json");
+    if let Ok(token) = env::var("GITHUB_TOKEN") {
+        request = request.bearer_auth(token);
+    }
+
+    let decode_error = |err: String| FetchError {
+        url: url.to_string(),
+        source: FetchErrorSource::Decode(err),
+    };
+
+    let (response, _) = fetch_body(request, url, max_bytes, progress, false).await?;
+    let contents: serde_json::Value =
+        serde_json::from_slice(&response).map_err(|err| decode_error(err.to_string()))?;
+    let body = decode_contents(&contents).map_err(decode_error)?;
+
+    validate::check_yaml(&body).map_err(|err| FetchError {
+        url: url.to_string(),
+        source: FetchErrorSource::Invalid(err),
+    })?;
+
+    Ok(body)
+}
+
+fn decode_contents(contents: &serde_json::Value) -> Result<Vec<u8>, String> {
+    if contents["encoding"] != "base64" {
+        return Err(format!("unsupported encoding {}", contents["encoding"]));
+    }
+
+    // The content is wrapped into lines which the decoder doesn't accept.
+    let content = contents["content"]
+        .as_str()
+        .ok_or("missing content")?
+        .split_whitespace()
+        .collect::<String>();
+
+    base64::decode(&content).map_err(|err| err.to_string())
+}
+
+/// Where raw github urls are retried, the hosts are only replaced in tests.
+#[derive(Debug, Clone)]
+struct ContentsApi {
+    raw_prefix: String,
+    api_prefix: String,
+}
+
+impl Default for ContentsApi {
+    fn default() -> Self {
+        Self {
+            raw_prefix: RAW_URL_PREFIX.to_string(),
+            api_prefix: API_URL_PREFIX.to_string(),
+        }
+    }
+}
+
+impl ContentsApi {
+    /// Contents api url of a raw github url, e.g.
+    /// `https://raw.githubusercontent.com/github/linguist/master/lib/linguist/languages.yml`
+    /// becomes
+    /// `https://api.github.com/repos/github/linguist/contents/lib/linguist/languages.yml?ref=master`.
+    fn url(&self, raw_url: &str) -> Option<String> {
+        let mut parts = raw_url.strip_prefix(&self.raw_prefix)?.splitn(4, '/');
+        let owner = parts.next()?;
+        let repository = parts.next()?;
+        let reference = parts.next()?;
+        let path = parts.next()?;
+
+        Some(format!(
+            "{}{}/{}/contents/{}?ref={}",
+            self.api_prefix, owner, repository, path, reference
+        ))
+    }
+}
+
+async fn fetch_body(
+    request: reqwest::RequestBuilder,
+    url: &str,
+    max_bytes: u64,
+    progress: &dyn Progress,
+    report_length: bool,
+) -> Result<(Vec<u8>, Option<String>), FetchError> {
+    let error = |err| FetchError {
+        url: url.to_string(),
+        source: FetchErrorSource::Request(err),
+    };
+    let invalid = |err| FetchError {
+        url: url.to_string(),
+        source: FetchErrorSource::Invalid(err),
+    };
+
+    let mut response = request
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(error)?;
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    validate::check_content_type(content_type).map_err(invalid)?;
+    let etag = response
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    // The content length is only a hint, the limit is enforced again while
+    // the body is streamed.
+    if let Some(length) = response.content_length() {
+        validate::check_size(length, max_bytes).map_err(invalid)?;
+    }
+
+    if report_length {
+        progress.start("fetching", response.content_length());
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(error)? {
+        validate::check_size((body.len() + chunk.len()) as u64, max_bytes).map_err(invalid)?;
+
+        body.extend_from_slice(&chunk);
+        progress.advance(chunk.len() as u64);
+    }
+
+    validate::check_yaml(&body).map_err(invalid)?;
+
+    if report_length {
+        progress.finish();
+    }
+
+    Ok((body, etag))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{
+        Read,
+        Write,
+    };
+    use std::net::{
+        TcpListener,
+        TcpStream,
+    };
+    use std::thread;
+    use std::time::{
+        Duration,
+        Instant,
+    };
+
+    use language_colors::progress::NoProgress;
+
+    use super::*;
+
+    const YAML: &[u8] = b"Rust:\n  type: programming\n  color: \"#dea584\"\n";
+
+    /// Reads the head of a request and returns its path.
+    fn read_path(stream: &mut TcpStream) -> String {
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+            let read = stream.read(&mut buffer).expect("can not read request");
+            if read == 0 {
+                break;
+            }
+            request.extend_from_slice(&buffer[..read]);
+        }
+
+        String::from_utf8_lossy(&request)
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn respond(mut stream: TcpStream, routes: &[(&str, Vec<u8>)]) {
+        let path = read_path(&mut stream);
+        let response = routes
+            .iter()
+            .find(|(route, _)| *route == path)
+            .map(|(_, response)| response.clone())
+            .unwrap_or_else(|| response("404 Not Found", "text/plain", b""));
+
+        // The client may hang up early, e.g. when the body is too large.
+        let _ = stream.write_all(&response);
+    }
+
+    /// Answers one connection per route on a local port and returns the url
+    /// of the server. Unknown paths get a 404.
+    fn serve(routes: Vec<(&'static str, Vec<u8>)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("can not bind test server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            for _ in 0..routes.len() {
+                let (stream, _) = listener.accept().expect("can not accept connection");
+                respond(stream, &routes);
+            }
+        });
+
+        url
+    }
+
+    /// Like `serve` but only answers once all routes were requested at the
+    /// same time. Requests that don't overlap get a 503 after a few seconds.
+    fn serve_concurrently(routes: Vec<(&'static str, Vec<u8>)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("can not bind test server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        listener.set_nonblocking(true).unwrap();
+
+        thread::spawn(move || {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            let mut streams = Vec::new();
+            while streams.len() < routes.len() && Instant::now() < deadline {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        stream.set_nonblocking(false).unwrap();
+                        streams.push(stream);
+                    }
+                    Err(_) => thread::sleep(Duration::from_millis(10)),
+                }
+            }
+
+            let complete = streams.len() == routes.len();
+            for stream in streams {
+                if complete {
+                    respond(stream, &routes);
+                } else {
+                    respond(stream, &[]);
+                }
+            }
+        });
+
+        url
+    }
+
+    fn response(status: &str, content_type: &str, body: &[u8]) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            status,
+            content_type,
+            body.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    fn yaml(body: &[u8]) -> Vec<u8> {
+        response("200 OK", "text/plain; charset=utf-8", body)
+    }
+
+    #[test]
+    fn sources_are_fetched_concurrently_in_order() {
+        let url = serve_concurrently(vec![
+            ("/first.yml", yaml(b"First:\n  type: data\n")),
+            ("/second.yml", yaml(b"Second:\n  type: data\n")),
+        ]);
+        let urls = vec![format!("{}/first.yml", url), format!("{}/second.yml", url)];
+
+        let fetcher = Fetcher::new(1024, false);
+        let downloads = fetcher.fetch_all(&urls, &NoProgress).unwrap();
+
+        assert_eq!(downloads.len(), 2);
+        assert_eq!(downloads[0].url, urls[0]);
+        assert_eq!(downloads[0].body, b"First:\n  type: data\n");
+        assert_eq!(downloads[1].url, urls[1]);
+        assert_eq!(downloads[1].body, b"Second:\n  type: data\n");
+    }
+
+    #[test]
+    fn failed_source_is_named_in_the_error() {
+        let url = serve(vec![
+            ("/languages.yml", yaml(YAML)),
+            ("/missing.yml", response("404 Not Found", "text/plain", b"")),
+        ]);
+        let urls = vec![
+            format!("{}/languages.yml", url),
+            format!("{}/missing.yml", url),
+        ];
+
+        let fetcher = Fetcher::new(1024, false);
+        let err = fetcher.fetch_all(&urls, &NoProgress).err().unwrap();
+
+        assert_eq!(err.url, urls[1]);
+        assert!(err
+            .to_string()
+            .starts_with(&format!("can not fetch {}", urls[1])));
+    }
+
+    fn fetch_error(body: Vec<u8>, max_bytes: u64) -> FetchError {
+        let urls = vec![format!(
+            "{}/languages.yml",
+            serve(vec![("/languages.yml", body)])
+        )];
+
+        let fetcher = Fetcher::new(max_bytes, false);
+        let err = fetcher.fetch_all(&urls, &NoProgress).err().unwrap();
+        assert_eq!(err.url, urls[0]);
+
+        err
+    }
+
+    #[test]
+    fn oversized_content_length_is_rejected() {
+        let err = fetch_error(yaml(&[b'a'; 64]), 32);
+
+        assert!(matches!(
+            err.source,
+            FetchErrorSource::Invalid(ValidationError::TooLarge(32))
+        ));
+        assert!(err.to_string().contains("larger than 32 bytes"));
+    }
+
+    #[test]
+    fn oversized_stream_is_rejected() {
+        // Without a content length the limit can only be checked while
+        // streaming.
+        let mut body =
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nConnection: close\r\n\r\n".to_vec();
+        body.extend_from_slice(&[b'a'; 64]);
+
+        let err = fetch_error(body, 32);
+
+        assert!(matches!(
+            err.source,
+            FetchErrorSource::Invalid(ValidationError::TooLarge(32))
+        ));
+    }
+
+    #[test]
+    fn html_content_type_is_rejected() {
+        let page = response("200 OK", "text/html; charset=utf-8", b"Rust: {}\n");
+        let err = fetch_error(page, 1024);
+
+        assert!(matches!(
+            err.source,
+            FetchErrorSource::Invalid(ValidationError::Html)
+        ));
+        assert!(err.to_string().contains("html page instead of yaml"));
+    }
+
+    #[test]
+    fn html_body_is_rejected() {
+        let page = yaml(b"\n  <!DOCTYPE html><html><body>Sign in</body></html>");
+        let err = fetch_error(page, 1024);
+
+        assert!(matches!(
+            err.source,
+            FetchErrorSource::Invalid(ValidationError::Html)
+        ));
+    }
+
+    #[test]
+    fn raw_urls_map_to_the_contents_api() {
+        let api = ContentsApi::default();
+
+        assert_eq!(
+            api.url(LINGUIST_URL).as_deref(),
+            Some(
+                "https://api.github.com/repos/github/linguist/contents/lib/linguist/languages.yml?ref=master"
+            )
+        );
+        assert_eq!(
+            api.url("https://raw.githubusercontent.com/acme/colors/v1.2/languages.yml")
+                .as_deref(),
+            Some("https://api.github.com/repos/acme/colors/contents/languages.yml?ref=v1.2")
+        );
+        assert_eq!(
+            api.url("https://raw.githubusercontent.com/acme/colors/main"),
+            None
+        );
+        assert_eq!(
+            api.url("https://example.com/github/linguist/master/languages.yml"),
+            None
+        );
+    }
+
+    /// Contents api response for `body`, wrapped into lines like github does.
+    fn contents(body: &[u8]) -> serde_json::Value {
+        let encoded = base64::encode(body);
+        let wrapped = encoded
+            .as_bytes()
+            .chunks(16)
+            .map(|line| std::str::from_utf8(line).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        serde_json::json!({ "encoding": "base64", "content": wrapped })
+    }
+
+    #[test]
+    fn contents_are_base64_decoded() {
+        assert_eq!(decode_contents(&contents(YAML)).unwrap(), YAML);
+    }
+
+    #[test]
+    fn contents_need_base64() {
+        let err = decode_contents(&serde_json::json!({ "encoding": "none", "content": "" }));
+        assert_eq!(err.unwrap_err(), "unsupported encoding \"none\"");
+
+        let err = decode_contents(&serde_json::json!({ "encoding": "base64" }));
+        assert_eq!(err.unwrap_err(), "missing content");
+
+        let err = decode_contents(&serde_json::json!({ "encoding": "base64", "content": "!!" }));
+        assert!(err.is_err());
+    }
+
+    /// Url of a local port nothing listens on.
+    fn unreachable_url() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("can not bind test server");
+        format!("http://{}/", listener.local_addr().unwrap())
+    }
+
+    /// Fetcher that treats `raw_prefix` as raw github and falls back to the
+    /// contents api at `api`.
+    fn fallback_fetcher(raw_prefix: &str, api: &str) -> Fetcher {
+        let mut fetcher = Fetcher::new(1024, true);
+        fetcher.fallback = Some(ContentsApi {
+            raw_prefix: raw_prefix.to_string(),
+            api_prefix: format!("{}/repos/", api),
+        });
+
+        fetcher
+    }
+
+    #[test]
+    fn unreachable_raw_url_falls_back_to_the_contents_api() {
+        let contents = serde_json::to_vec(&contents(YAML)).unwrap();
+        let api = serve(vec![(
+            "/repos/github/linguist/contents/lib/linguist/languages.yml?ref=master",
+            response("200 OK", "application/json", &contents),
+        )]);
+        let raw_prefix = unreachable_url();
+        let urls = vec![format!(
+            "{}github/linguist/master/lib/linguist/languages.yml",
+            raw_prefix
+        )];
+
+        let fetcher = fallback_fetcher(&raw_prefix, &api);
+        let downloads = fetcher.fetch_all(&urls, &NoProgress).unwrap();
+
+        assert_eq!(
+            downloads[0].url,
+            format!(
+                "{}/repos/github/linguist/contents/lib/linguist/languages.yml?ref=master",
+                api
+            )
+        );
+        assert_eq!(downloads[0].body, YAML);
+    }
+
+    #[test]
+    fn status_errors_do_not_fall_back() {
+        let raw = serve(vec![(
+            "/github/linguist/master/languages.yml",
+            response("404 Not Found", "text/plain", b""),
+        )]);
+        let raw_prefix = format!("{}/", raw);
+        let urls = vec![format!(
+            "{}github/linguist/master/languages.yml",
+            raw_prefix
+        )];
+
+        let fetcher = fallback_fetcher(&raw_prefix, &unreachable_url());
+        let err = fetcher.fetch_all(&urls, &NoProgress).err().unwrap();
+
+        assert_eq!(err.url, urls[0]);
+        assert!(!err.is_network());
+    }
+
+    #[test]
+    fn no_fallback_keeps_the_network_error() {
+        let urls = vec![format!("{}languages.yml", unreachable_url())];
+
+        let fetcher = Fetcher::new(1024, false);
+        let err = fetcher.fetch_all(&urls, &NoProgress).err().unwrap();
+
+        assert_eq!(err.url, urls[0]);
+        assert!(err.is_network());
+    }
+
+    #[test]
+    fn release_tag_is_read_from_the_latest_release() {
+        let body = br#"{"tag_name": "v9.1.0", "name": "v9.1.0"}"#;
+        let url = serve(vec![(
+            "/latest",
+            response("200 OK", "application/json", body),
+        )]);
+
+        let fetcher = Fetcher::new(1024, false);
+        let tag = fetcher
+            .fetch_release_tag(&format!("{}/latest", url))
+            .unwrap();
+
+        assert_eq!(tag.as_deref(), Some("v9.1.0"));
+    }
+
+    #[test]
+    fn release_tag_reports_the_url_on_errors() {
+        let url = serve(vec![(
+            "/latest",
+            response("404 Not Found", "application/json", b"{}"),
+        )]);
+        let latest = format!("{}/latest", url);
+
+        let fetcher = Fetcher::new(1024, false);
+        let err = fetcher.fetch_release_tag(&latest).unwrap_err();
+
+        assert!(err
+            .to_string()
+            .starts_with(&format!("can not fetch {}", latest)));
+    }
+}
@@ -0,0 +1,309 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::language::{
+    LanguageInfo,
+    LanguageType,
+};
+use crate::output::SCHEMA_VERSION;
+
+/// Type of the languages read from outputs that don't record it.
+const UNKNOWN_TYPE: &str = "unknown";
+/// Ace mode of the languages read from outputs that don't record it, the one
+/// linguist uses for plain text.
+const UNKNOWN_ACE_MODE: &str = "text";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputFormat {
+    Yaml,
+    Json,
+    /// See `parse_simple`.
+    Simple,
+}
+
+impl FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "yaml" => Ok(InputFormat::Yaml),
+            "json" => Ok(InputFormat::Json),
+            "simple" => Ok(InputFormat::Simple),
+            _ => Err(format!("unknown input format: {}", s)),
+        }
+    }
+}
+
+/// Languages read from an `--input` that is not in the format of linguist.
+#[derive(Debug, Default)]
+pub struct Imported {
+    pub languages: BTreeMap<String, LanguageInfo>,
+    /// Problems that don't stop the import, like a different schema version.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChainRecord {
+    language: String,
+    color: String,
+    #[serde(default)]
+    default: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClassRecord {
+    color: String,
+    #[serde(default)]
+    default: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct FullColor {
+    hex: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct FullRecord {
+    name: String,
+    language_id: i64,
+    color: Option<FullColor>,
+    #[serde(rename = "type")]
+    language_type: LanguageType,
+    extensions: Option<Vec<String>>,
+    filenames: Option<Vec<String>>,
+    aliases: Option<Vec<String>>,
+    tm_scope: Option<String>,
+    ace_mode: String,
+    group: Option<String>,
+}
+
+/// Shape of the simple input, used in its error messages.
+const SIMPLE_SCHEMA: &str =
+    "a map from names to a color (\"#RRGGBB\") or to {color: \"#RRGGBB\", type: ...}";
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SimpleEntry {
+    Color(String),
+    Details(SimpleDetails),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SimpleDetails {
+    color: Option<String>,
+    #[serde(rename = "type")]
+    language_type: Option<LanguageType>,
+}
+
+/// Reads a hand written palette in yaml or json: a map from names to a
+/// color, or to an object with an optional `color` and `type`. Names get
+/// ids counting up from 1 in name order, the type defaults to `unknown` and
+/// the ace mode to `text`.
+///
+/// ```
+/// use language_colors::import::parse_simple;
+/// use language_colors::output::OutputFormat;
+/// use language_colors::render::{
+///     render,
+///     LanguageColors,
+///     RenderOptions,
+/// };
+///
+/// let palette = br##"
+/// Primary: "#0055FF"
+/// Secondary: "#FF8800"
+/// Accent: {color: "#22CC88", type: markup}
+/// Muted: {color: "#888888"}
+/// Placeholder: {type: data}
+/// "##;
+///
+/// let imported = parse_simple(palette).unwrap();
+/// assert_eq!(imported.languages.len(), 5);
+/// assert_eq!(imported.languages["Accent"].language_id, 1);
+/// assert_eq!(imported.languages["Accent"].language_type.as_str(), "markup");
+/// assert_eq!(imported.languages["Primary"].language_type.as_str(), "unknown");
+///
+/// let (languages, invalid) = LanguageColors::new(imported.languages, None);
+/// assert!(invalid.is_empty());
+/// assert_eq!(languages.colorless, ["Placeholder"]);
+///
+/// let options = RenderOptions::default();
+/// let html = String::from_utf8(render(&languages, OutputFormat::Html, &options)).unwrap();
+/// assert!(html.contains("Secondary") && html.contains("#FF8800"));
+///
+/// let json = String::from_utf8(render(&languages, OutputFormat::Json, &options)).unwrap();
+/// let json = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+/// assert_eq!(json["languages"].as_array().unwrap().len(), 4);
+/// assert_eq!(json["languages"][0]["language"], "Accent");
+///
+/// let err = parse_simple(b"Primary: [1, 2]").unwrap_err();
+/// assert!(err.contains("a map from names to a color"));
+/// ```
+pub fn parse_simple(body: &[u8]) -> Result<Imported, String> {
+    let entries = serde_yaml::from_slice::<BTreeMap<String, SimpleEntry>>(body)
+        .map_err(|err| format!("expected {}: {}", SIMPLE_SCHEMA, err))?;
+
+    let languages = entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, entry))| {
+            let (color, language_type) = match entry {
+                SimpleEntry::Color(color) => (Some(color), None),
+                SimpleEntry::Details(details) => (details.color, details.language_type),
+            };
+
+            let mut info = unknown_language(color, false);
+            info.language_id = index as i64 + 1;
+            if let Some(language_type) = language_type {
+                info.language_type = language_type;
+            }
+
+            (name, info)
+        })
+        .collect();
+
+    Ok(Imported {
+        languages,
+        warnings: Vec::new(),
+    })
+}
+
+/// Reads the languages from the json output, the class map or the `--full`
+/// output of a language. The json output and the class map only have names
+/// and colors, their languages get the type `unknown`, the ace mode `text`
+/// and the id `0`. Languages marked as `default` had no color of their own
+/// and are read without one, `--default-color` gives them a color again.
+///
+/// Rendering the imported json output gives the same html as rendering the
+/// languages it was written from, except for the types:
+///
+/// ```
+/// use language_colors::import::parse_json;
+/// use language_colors::output::html::Section;
+/// use language_colors::output::OutputFormat;
+/// use language_colors::render::{
+///     render,
+///     LanguageColors,
+///     RenderOptions,
+/// };
+///
+/// let fixture = LanguageColors::from_yaml(
+///     r##"
+/// Rust:
+///   type: programming
+///   color: "#dea584"
+///   ace_mode: rust
+///   language_id: 327
+/// Go:
+///   type: programming
+///   color: "#00ADD8"
+///   ace_mode: golang
+///   language_id: 132
+/// HTML:
+///   type: markup
+///   color: "#e34c26"
+///   ace_mode: html
+///   language_id: 146
+/// "##,
+/// )
+/// .unwrap();
+/// let options = RenderOptions {
+///     sections: vec![Section::Name, Section::Nearest],
+///     ..RenderOptions::default()
+/// };
+///
+/// for format in &[OutputFormat::Json, OutputFormat::ClassMap] {
+///     let imported = parse_json(&render(&fixture, *format, &options)).unwrap();
+///     assert!(imported.warnings.is_empty());
+///     assert_eq!(imported.languages["Go"].language_type.as_str(), "unknown");
+///
+///     let reimported = LanguageColors::new(imported.languages, None).0;
+///     assert_eq!(
+///         render(&reimported, OutputFormat::Html, &options),
+///         render(&fixture, OutputFormat::Html, &options)
+///     );
+/// }
+///
+/// let old = parse_json(br##"{"schema_version": 0, "languages": []}"##).unwrap();
+/// assert_eq!(old.warnings.len(), 1);
+/// assert!(parse_json(b"[]").is_err());
+/// ```
+pub fn parse_json(body: &[u8]) -> Result<Imported, String> {
+    let value = serde_json::from_slice::<Value>(body).map_err(|err| err.to_string())?;
+    let mut imported = Imported::default();
+
+    match value.get("schema_version").and_then(Value::as_u64) {
+        Some(version) if version == u64::from(SCHEMA_VERSION) => {}
+        Some(version) => imported.warnings.push(format!(
+            "schema version {} differs from the supported version {}",
+            version, SCHEMA_VERSION
+        )),
+        None => imported
+            .warnings
+            .push("no schema version, reading it as the current one".to_string()),
+    }
+
+    let languages = value.get("languages");
+    if value.get("name").is_some() {
+        let record = serde_json::from_value::<FullRecord>(value).map_err(|err| err.to_string())?;
+
+        imported.languages.insert(
+            record.name,
+            LanguageInfo {
+                language_id: record.language_id,
+                ace_mode: record.ace_mode,
+                color: record.color.map(|color| color.hex),
+                extensions: record.extensions,
+                filenames: record.filenames,
+                aliases: record.aliases,
+                group: record.group,
+                tm_scope: record.tm_scope,
+                language_type: record.language_type,
+            },
+        );
+    } else if let Some(Value::Array(_)) = languages {
+        let records = serde_json::from_value::<Vec<ChainRecord>>(languages.unwrap().clone())
+            .map_err(|err| err.to_string())?;
+
+        for record in records {
+            imported.languages.insert(
+                record.language,
+                unknown_language(Some(record.color), record.default),
+            );
+        }
+    } else if let Some(Value::Object(_)) = languages {
+        let records =
+            serde_json::from_value::<BTreeMap<String, ClassRecord>>(languages.unwrap().clone())
+                .map_err(|err| err.to_string())?;
+
+        for (name, record) in records {
+            imported
+                .languages
+                .insert(name, unknown_language(Some(record.color), record.default));
+        }
+    } else {
+        return Err(
+            "expected the json output, the class map or the --full output of a language"
+                .to_string(),
+        );
+    }
+
+    Ok(imported)
+}
+
+fn unknown_language(color: Option<String>, default: bool) -> LanguageInfo {
+    LanguageInfo {
+        language_id: 0,
+        ace_mode: UNKNOWN_ACE_MODE.to_string(),
+        color: if default { None } else { color },
+        extensions: None,
+        filenames: None,
+        aliases: None,
+        group: None,
+        tm_scope: None,
+        language_type: LanguageType::from(UNKNOWN_TYPE.to_string()),
+    }
+}
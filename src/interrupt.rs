@@ -0,0 +1,110 @@
+use std::fs;
+use std::io;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::sync::atomic::{
+    AtomicBool,
+    Ordering,
+};
+use std::sync::Arc;
+
+/// Exit code after an interrupt, the one shells use for SIGINT.
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// Files and directories written by a run with several output files. When
+/// the run is interrupted they are removed again, so no half written output
+/// is left behind.
+///
+/// ```
+/// use std::sync::atomic::{
+///     AtomicBool,
+///     Ordering,
+/// };
+/// use std::sync::Arc;
+///
+/// use language_colors::interrupt::OutputFiles;
+///
+/// let root = std::env::temp_dir().join(format!("language_colors_{}", std::process::id()));
+/// std::fs::create_dir_all(&root).unwrap();
+/// let out_dir = root.join("site").join("colors");
+///
+/// let interrupted = Arc::new(AtomicBool::new(false));
+/// let mut files = OutputFiles::new(Arc::clone(&interrupted));
+/// files.create_dir_all(&out_dir).unwrap();
+///
+/// assert!(!files.cleanup_if_interrupted().unwrap());
+/// files.write(&out_dir.join("languages.html"), b"<html>").unwrap();
+///
+/// interrupted.store(true, Ordering::SeqCst);
+/// assert!(files.cleanup_if_interrupted().unwrap());
+/// assert!(!root.join("site").exists());
+/// assert!(root.exists());
+///
+/// std::fs::remove_dir(&root).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct OutputFiles {
+    interrupted: Arc<AtomicBool>,
+    files: Vec<PathBuf>,
+    /// Created directories, the deepest first.
+    directories: Vec<PathBuf>,
+}
+
+impl OutputFiles {
+    /// `interrupted` is set by the signal handler.
+    pub fn new(interrupted: Arc<AtomicBool>) -> Self {
+        Self {
+            interrupted,
+            files: Vec::new(),
+            directories: Vec::new(),
+        }
+    }
+
+    /// Creates `path` and its missing parents.
+    pub fn create_dir_all(&mut self, path: &Path) -> io::Result<()> {
+        let missing = path
+            .ancestors()
+            .take_while(|ancestor| !ancestor.as_os_str().is_empty() && !ancestor.exists())
+            .map(Path::to_path_buf)
+            .collect::<Vec<_>>();
+
+        fs::create_dir_all(path)?;
+        self.directories.extend(missing);
+
+        Ok(())
+    }
+
+    /// Writes `contents` to `path`, replacing an existing file.
+    pub fn write(&mut self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        // Tracked before writing so a partially written file is removed too.
+        self.files.push(path.to_path_buf());
+
+        fs::write(path, contents)
+    }
+
+    /// Whether the run was interrupted. If it was, the files written so far
+    /// and the directories created for them are removed. Files that existed
+    /// before and were overwritten are removed as well, nothing is restored.
+    pub fn cleanup_if_interrupted(&mut self) -> io::Result<bool> {
+        if !self.interrupted.load(Ordering::SeqCst) {
+            return Ok(false);
+        }
+
+        for file in self.files.drain(..) {
+            match fs::remove_file(&file) {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
+
+        for directory in self.directories.drain(..) {
+            // Fails for directories that got files from somewhere else in the
+            // meantime, those are kept.
+            let _ = fs::remove_dir(directory);
+        }
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,371 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::de::{
+    Deserialize,
+    Deserializer,
+};
+use serde::ser::{
+    Serialize,
+    Serializer,
+};
+
+/// Type of a language as used by linguist. Unknown types are kept as
+/// `Other`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LanguageType {
+    Programming,
+    Markup,
+    Data,
+    Prose,
+    Nil,
+    Other(String),
+}
+
+impl LanguageType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            LanguageType::Programming => "programming",
+            LanguageType::Markup => "markup",
+            LanguageType::Data => "data",
+            LanguageType::Prose => "prose",
+            LanguageType::Nil => "nil",
+            LanguageType::Other(other) => other,
+        }
+    }
+}
+
+impl From<String> for LanguageType {
+    fn from(s: String) -> Self {
+        match s.as_str() {
+            "programming" => LanguageType::Programming,
+            "markup" => LanguageType::Markup,
+            "data" => LanguageType::Data,
+            "prose" => LanguageType::Prose,
+            "nil" => LanguageType::Nil,
+            _ => LanguageType::Other(s),
+        }
+    }
+}
+
+impl FromStr for LanguageType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match LanguageType::from(s.to_lowercase()) {
+            LanguageType::Other(_) => Err(format!("unknown language type: {}", s)),
+            language_type => Ok(language_type),
+        }
+    }
+}
+
+impl fmt::Display for LanguageType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+impl Serialize for LanguageType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for LanguageType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(LanguageType::from)
+    }
+}
+
+/// Which languages are rendered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Profile {
+    /// Every language.
+    #[default]
+    All,
+    /// Like the language bar of github.com: only programming and markup
+    /// languages, colorless languages get the color of their group.
+    Github,
+}
+
+impl Profile {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Profile::All => "all",
+            Profile::Github => "github",
+        }
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "all" => Ok(Profile::All),
+            "github" => Ok(Profile::Github),
+            _ => Err(format!("unknown profile: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+/// Keeps the languages selected by `profile`. Explicit `types` replace the
+/// types of the profile, the group color inheritance of `Profile::Github`
+/// still applies. A group color is taken from the unfiltered languages, so
+/// a data language can pass its color on to a programming language.
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use language_colors::language::{
+///     apply_profile,
+///     LanguageInfo,
+///     LanguageType,
+///     Profile,
+/// };
+///
+/// let languages = || {
+///     serde_yaml::from_str::<BTreeMap<String, LanguageInfo>>(
+///         r##"
+/// Rust:
+///   type: programming
+///   color: "#dea584"
+///   ace_mode: rust
+///   language_id: 327
+/// HTML:
+///   type: markup
+///   color: "#e34c26"
+///   ace_mode: html
+///   language_id: 146
+/// HTML+ERB:
+///   type: markup
+///   group: HTML
+///   ace_mode: erb
+///   language_id: 150
+/// JSON:
+///   type: data
+///   color: "#292929"
+///   ace_mode: json
+///   language_id: 174
+/// Markdown:
+///   type: prose
+///   color: "#083fa1"
+///   ace_mode: markdown
+///   language_id: 222
+/// "##,
+///     )
+///     .unwrap()
+/// };
+///
+/// let all = apply_profile(languages(), Profile::All, &[]);
+/// assert_eq!(all.len(), 5);
+/// assert_eq!(all["HTML+ERB"].color, None);
+///
+/// let github = apply_profile(languages(), Profile::Github, &[]);
+/// assert_eq!(github.keys().collect::<Vec<_>>(), ["HTML", "HTML+ERB", "Rust"]);
+/// assert_eq!(github["HTML+ERB"].color.as_deref(), Some("#e34c26"));
+///
+/// let data = apply_profile(languages(), Profile::Github, &[LanguageType::Data]);
+/// assert_eq!(data.keys().collect::<Vec<_>>(), ["JSON"]);
+/// ```
+pub fn apply_profile(
+    mut languages: BTreeMap<String, LanguageInfo>,
+    profile: Profile,
+    types: &[LanguageType],
+) -> BTreeMap<String, LanguageInfo> {
+    if profile == Profile::Github {
+        let inherited = languages
+            .iter()
+            .filter(|(_, info)| info.color.is_none())
+            .filter_map(|(name, info)| {
+                let group = info.group.as_ref()?;
+                let color = languages.get(group)?.color.clone()?;
+
+                Some((name.clone(), color))
+            })
+            .collect::<Vec<_>>();
+
+        for (name, color) in inherited {
+            languages.get_mut(&name).unwrap().color = Some(color);
+        }
+    }
+
+    let types = match (types, profile) {
+        ([], Profile::All) => return languages,
+        ([], Profile::Github) => &[LanguageType::Programming, LanguageType::Markup][..],
+        (types, _) => types,
+    };
+
+    languages
+        .into_iter()
+        .filter(|(_, info)| types.contains(&info.language_type))
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageInfo {
+    pub language_id: i64,
+
+    pub ace_mode: String,
+    pub color: Option<String>,
+    pub extensions: Option<Vec<String>>,
+    pub filenames: Option<Vec<String>>,
+    pub aliases: Option<Vec<String>>,
+    /// Name of the language this one is grouped under in linguist's
+    /// statistics.
+    pub group: Option<String>,
+    pub tm_scope: Option<String>,
+
+    #[serde(rename = "type")]
+    pub language_type: LanguageType,
+}
+
+impl LanguageInfo {
+    pub fn is_programming(&self) -> bool {
+        self.language_type == LanguageType::Programming
+    }
+
+    pub fn is_markup(&self) -> bool {
+        self.language_type == LanguageType::Markup
+    }
+
+    pub fn is_data(&self) -> bool {
+        self.language_type == LanguageType::Data
+    }
+
+    pub fn is_prose(&self) -> bool {
+        self.language_type == LanguageType::Prose
+    }
+
+    /// Linguist uses `nil` for languages that have no type.
+    pub fn is_nil(&self) -> bool {
+        self.language_type == LanguageType::Nil
+    }
+
+    /// The first extension, which linguist lists as the most common one.
+    pub fn primary_extension(&self) -> Option<&str> {
+        self.extensions
+            .as_deref()
+            .and_then(|extensions| extensions.first().map(String::as_str))
+    }
+
+    /// Whether `extension` is one of the extensions of the language. The
+    /// leading dot is optional and case is ignored.
+    ///
+    /// ```
+    /// use language_colors::language::{
+    ///     LanguageInfo,
+    ///     LanguageType,
+    /// };
+    ///
+    /// let rust = LanguageInfo {
+    ///     language_id: 327,
+    ///     ace_mode: "rust".to_string(),
+    ///     color: Some("#dea584".to_string()),
+    ///     extensions: Some(vec![".rs".to_string(), ".rs.in".to_string()]),
+    ///     filenames: None,
+    ///     aliases: Some(vec!["rs".to_string()]),
+    ///     group: None,
+    ///     tm_scope: Some("source.rust".to_string()),
+    ///     language_type: LanguageType::Programming,
+    /// };
+    ///
+    /// assert!(rust.has_extension(".rs"));
+    /// assert!(rust.has_extension("RS"));
+    /// assert!(!rust.has_extension("r"));
+    /// ```
+    pub fn has_extension(&self, extension: &str) -> bool {
+        let extension = extension.trim_start_matches('.');
+
+        self.extensions.iter().flatten().any(|candidate| {
+            candidate
+                .trim_start_matches('.')
+                .eq_ignore_ascii_case(extension)
+        })
+    }
+}
+
+/// Language names by their linguist `language_id`, which stays the same when
+/// a language is renamed.
+#[derive(Debug, Default)]
+pub struct IdIndex {
+    names: BTreeMap<i64, String>,
+    /// Languages whose id is already used by a language earlier in name
+    /// order, they are left out of the index.
+    pub duplicates: Vec<(i64, String)>,
+}
+
+impl IdIndex {
+    pub fn new(languages: &BTreeMap<String, LanguageInfo>) -> Self {
+        let mut index = Self::default();
+
+        for (name, info) in languages {
+            match index.names.entry(info.language_id) {
+                Entry::Vacant(entry) => {
+                    entry.insert(name.clone());
+                }
+                Entry::Occupied(_) => index.duplicates.push((info.language_id, name.clone())),
+            }
+        }
+
+        index
+    }
+
+    pub fn by_id(&self, id: i64) -> Option<&str> {
+        self.names.get(&id).map(String::as_str)
+    }
+}
+
+/// Language names by their extensions. Extensions are compared like in
+/// `LanguageInfo::has_extension`, so they are stored lowercased and without
+/// the leading dot.
+#[derive(Debug, Default)]
+pub struct ExtensionIndex {
+    languages: BTreeMap<String, Vec<String>>,
+}
+
+impl ExtensionIndex {
+    pub fn new(languages: &BTreeMap<String, LanguageInfo>) -> Self {
+        let mut index = Self::default();
+
+        for (name, info) in languages {
+            for extension in info.extensions.iter().flatten() {
+                let claimants = index
+                    .languages
+                    .entry(extension.trim_start_matches('.').to_lowercase())
+                    .or_default();
+
+                if !claimants.contains(name) {
+                    claimants.push(name.clone());
+                }
+            }
+        }
+
+        index
+    }
+
+    /// Languages with `extension` in name order, empty if no language has
+    /// it.
+    pub fn by_extension(&self, extension: &str) -> &[String] {
+        self.languages
+            .get(&extension.trim_start_matches('.').to_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Every extension without the leading dot together with its languages,
+    /// in extension order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.languages
+            .iter()
+            .map(|(extension, languages)| (extension.as_str(), languages.as_slice()))
+    }
+}
@@ -0,0 +1,50 @@
+#[cfg(feature = "clipboard")]
+extern crate arboard;
+extern crate base64;
+#[cfg(feature = "tui")]
+extern crate crossterm;
+#[cfg(feature = "egui")]
+extern crate egui;
+extern crate indicatif;
+extern crate ordered_float;
+extern crate rand;
+extern crate rand_chacha;
+#[cfg(feature = "tui")]
+extern crate ratatui;
+extern crate schemars;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate serde_yaml;
+extern crate sha2;
+extern crate strsim;
+extern crate tracing;
+extern crate unicode_normalization;
+#[cfg(feature = "wgpu")]
+extern crate wgpu;
+
+pub mod browse;
+pub mod cache;
+pub mod card;
+pub mod checksum;
+pub mod cluster;
+pub mod color;
+pub mod diff;
+pub mod import;
+pub mod interrupt;
+pub mod language;
+pub mod merge;
+pub mod order;
+pub mod output;
+pub mod palette;
+pub mod parameters;
+pub mod progress;
+pub mod render;
+pub mod report;
+pub mod sample;
+pub mod separate;
+pub mod stats;
+pub mod suggest;
+pub mod timings;
+pub mod validate;
@@ -1,215 +1,1702 @@
-use std::collections::{
-    BTreeMap,
-    BTreeSet,
+use std::collections::BTreeMap;
+use std::fs::{
+    self,
+    File,
+};
+use std::io::{
+    self,
+    BufWriter,
+    Write,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::str::FromStr;
+use std::sync::atomic::{
+    AtomicBool,
+    Ordering,
+};
+use std::sync::Arc;
+use std::time::{
+    SystemTime,
+    UNIX_EPOCH,
 };
 
+extern crate base64;
+extern crate ctrlc;
+extern crate futures;
+extern crate language_colors;
 extern crate reqwest;
-
-#[macro_use]
-extern crate serde_derive;
+extern crate serde_json;
 extern crate serde_yaml;
+extern crate structopt;
+extern crate tokio;
+extern crate tracing;
+extern crate tracing_subscriber;
+
+mod fetch;
+
+use language_colors::cache::{
+    self,
+    Cache,
+    CacheMetadata,
+};
+use language_colors::card;
+use language_colors::checksum;
+use language_colors::color::{
+    parse_any_color,
+    AverageSpace,
+    Color,
+};
+use language_colors::diff;
+use language_colors::import::{
+    self,
+    InputFormat,
+};
+use language_colors::interrupt::{
+    OutputFiles,
+    EXIT_INTERRUPTED,
+};
+use language_colors::language::{
+    self,
+    ExtensionIndex,
+    LanguageInfo,
+    LanguageType,
+    Profile,
+};
+use language_colors::merge;
+use language_colors::order::{
+    self,
+    DistanceMetric,
+    Order,
+    OrderList,
+    Slice,
+};
+use language_colors::output::card::{
+    CardFormat,
+    CARD_WIDTH,
+};
+use language_colors::output::csv::{
+    Column,
+    CsvOptions,
+};
+use language_colors::output::html::Section;
+use language_colors::output::markdown::MarkdownOptions;
+use language_colors::output::report::{
+    DiffFormat,
+    MatrixFormat,
+    ReportFormat,
+};
+use language_colors::output::schema::SchemaFormat;
+use language_colors::output::{
+    self,
+    Metadata,
+    OutputFormat,
+    Paginate,
+    Source,
+    SplitBy,
+};
+use language_colors::palette;
+use language_colors::parameters::Parameters;
+use language_colors::progress::{
+    self,
+    CountingProgress,
+};
+use language_colors::render::{
+    self,
+    LanguageColors,
+    RenderOptions,
+    Renderer,
+};
+use language_colors::report::{
+    self,
+    GroupOrder,
+};
+use language_colors::sample::{
+    self,
+    Sample,
+};
+use language_colors::separate;
+use language_colors::stats;
+use language_colors::suggest;
+use language_colors::timings::Timings;
+use language_colors::validate;
+use structopt::StructOpt;
+use tracing_subscriber::EnvFilter;
+
+use crate::fetch::{
+    Download,
+    Fetcher,
+};
+
+/// Languages above which the matrix report needs --force, the matrix grows
+/// quadratically.
+const MAX_MATRIX_LANGUAGES: usize = 5000;
+
+/// Exit code used when the languages don't match --expect-sha256.
+const EXIT_CHECKSUM_MISMATCH: i32 = 3;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "language_colors")]
+struct Opt {
+    /// Format of the generated output (html, json, less, class-map, markdown,
+    /// csv), can be repeated together with --out-dir to write several formats
+    /// at once
+    #[structopt(long = "output-format", default_value = "html", number_of_values = 1)]
+    output_format: Vec<OutputFormat>,
+
+    /// Comma separated columns of the csv output (language, color, hue,
+    /// saturation, lightness, lab_l, lab_a, lab_b, distance to the previous
+    /// row)
+    #[structopt(
+        long = "columns",
+        use_delimiter = true,
+        default_value = "language,color"
+    )]
+    columns: Vec<Column>,
+
+    /// Sections of the html output in the order they are rendered (legend,
+    /// name, nearest, groups, clusters, colorless, wheel, types, families)
+    #[structopt(
+        long = "sections",
+        use_delimiter = true,
+        default_value = "legend,name,nearest"
+    )]
+    sections: Vec<Section>,
+
+    /// Order of the colors in the groups section and the shared report
+    /// (size, hex, perceptual)
+    #[structopt(long = "sort-groups", default_value = "size")]
+    sort_groups: GroupOrder,
+
+    /// Start a new band of the nearest color chain wherever the distance
+    /// to the previous color is larger than this, 0 disables banding
+    #[structopt(long = "band-threshold", default_value = "0")]
+    band_threshold: f64,
+
+    /// Number of clusters in the clusters section
+    #[structopt(long = "clusters", default_value = "8")]
+    clusters: usize,
+
+    /// Seed for --sample and the random initial centroids of the clusters
+    /// section, without a seed the centroids are picked evenly spaced by name
+    #[structopt(long = "seed")]
+    seed: Option<u64>,
+
+    /// Space in which the cluster centroids and the average colors per type
+    /// are averaged (srgb, linear, lab)
+    #[structopt(long = "average-space", default_value = "linear")]
+    average_space: AverageSpace,
+
+    /// Include languages without a color using this color, e.g. '#CCCCCC'
+    #[structopt(long = "default-color", parse(try_from_str = parse_any_color))]
+    default_color: Option<Color>,
+
+    /// Only use a random sample of this many languages, the same --seed
+    /// always gives the same sample
+    #[structopt(long = "sample")]
+    sample: Option<usize>,
+
+    /// Nudge colors that are closer than --min-distance to the color of a
+    /// language earlier by name until they are far enough apart
+    #[structopt(long = "separate")]
+    separate: bool,
+
+    /// Smallest distance between two colors for --separate
+    #[structopt(long = "min-distance", default_value = "15")]
+    min_distance: f64,
+
+    /// Print this many language colors that are as distinguishable as
+    /// possible and readable against black and white
+    #[structopt(long = "accessible-palette")]
+    accessible_palette: Option<usize>,
+
+    /// Read the options listed under parameters in the metadata of an
+    /// earlier run from this json file, they replace the ones given on the
+    /// command line
+    #[structopt(long = "params-file", parse(from_os_str))]
+    params_file: Option<PathBuf>,
+
+    /// Order of the languages in list outputs (name, nearest, perceptual,
+    /// hilbert, file)
+    #[structopt(long = "order", default_value = "name")]
+    order: Order,
+
+    /// File with one language name per line for --order file, blank lines and
+    /// lines starting with # are ignored
+    #[structopt(long = "order-file", parse(from_os_str), required_if("order", "file"))]
+    order_file: Option<PathBuf>,
+
+    /// Leave out languages missing from the order file instead of appending
+    /// them in name order
+    #[structopt(long = "order-file-exclusive")]
+    order_file_exclusive: bool,
+
+    /// Fail instead of warning when the order file lists unknown languages,
+    /// language names only differ in case or whitespace, a language changes
+    /// its id between sources or a color can not be parsed
+    #[structopt(long = "strict")]
+    strict: bool,
+
+    /// Language the nearest color ordering starts with
+    #[structopt(long = "start-language")]
+    start_language: Option<String>,
+
+    /// Only output this many languages
+    #[structopt(long = "limit")]
+    limit: Option<usize>,
+
+    /// Skip this many languages at the start of the output
+    #[structopt(long = "offset", default_value = "0")]
+    offset: usize,
+
+    /// Reverse the order of the output
+    #[structopt(long = "reverse")]
+    reverse: bool,
+
+    /// Write the output to this file instead of stdout
+    #[structopt(short = "o", long = "output", parse(from_os_str))]
+    output: Option<PathBuf>,
+
+    /// Split the html output into multiple pages (letter)
+    #[structopt(long = "paginate", requires = "out-dir", conflicts_with = "output")]
+    paginate: Option<Paginate>,
+
+    /// Split every file written to --out-dir (type): one file per language
+    /// type named after the type and one named all with every language
+    #[structopt(long = "split-by", requires = "out-dir", conflicts_with = "paginate")]
+    split_by: Option<SplitBy>,
+
+    /// Directory the pages are written to when using --paginate, otherwise
+    /// every --output-format is written to a file in it
+    #[structopt(long = "out-dir", parse(from_os_str), conflicts_with = "output")]
+    out_dir: Option<PathBuf>,
+
+    /// File name without extension of the outputs written to --out-dir
+    #[structopt(long = "out-name", default_value = "languages")]
+    out_name: String,
+
+    /// Title of the html page
+    #[structopt(long = "title")]
+    title: Option<String>,
+
+    /// Heading at the top of the html page
+    #[structopt(long = "heading")]
+    heading: Option<String>,
+
+    /// Plain text shown below the heading of the html page
+    #[structopt(long = "description")]
+    description: Option<String>,
+
+    /// Leave the javascript out of the html output
+    #[structopt(long = "no-js")]
+    no_js: bool,
+
+    /// Replace the by name and nearest color sections of the html output
+    /// with a section per language type
+    #[structopt(long = "group-by-type")]
+    group_by_type: bool,
+
+    /// Replace the by name and nearest color sections of the html output
+    /// with a section that groups the languages by color family
+    #[structopt(long = "group-by-color-family")]
+    group_by_color_family: bool,
+
+    /// Rows per page of the by name table in the html output, 0 shows all
+    /// rows on one page
+    #[structopt(long = "page-size", default_value = "0")]
+    page_size: usize,
+
+    /// Languages that are rendered: all, or github for only programming and
+    /// markup languages with colorless languages taking the color of their
+    /// group
+    #[structopt(long = "profile", default_value = "all")]
+    profile: Profile,
+
+    /// Only render languages of this type (programming, markup, data,
+    /// prose, nil), can be repeated and replaces the types of --profile
+    #[structopt(long = "type", number_of_values = 1)]
+    types: Vec<LanguageType>,
+
+    /// Only print the details of this language
+    #[structopt(long = "language")]
+    language: Option<String>,
+
+    /// Only print the details of the language with this linguist
+    /// language_id
+    #[structopt(long = "id", conflicts_with = "language")]
+    id: Option<i64>,
+
+    /// Print everything known about the language of --language or --id as
+    /// json
+    #[structopt(long = "full")]
+    full: bool,
+
+    /// Compare the colors of two languages, has to be given twice
+    #[structopt(
+        long = "compare-language",
+        number_of_values = 1,
+        conflicts_with_all = &["language", "id"]
+    )]
+    compare_language: Vec<String>,
+
+    /// Decimals of the distances in the html output and on the terminal and of
+    /// the color components in the csv output and with --full
+    #[structopt(long = "precision", default_value = "2")]
+    precision: usize,
+
+    /// Number of nearest languages printed with --language
+    #[structopt(long = "nearest-count", default_value = "5")]
+    nearest_count: usize,
+
+    /// Print statistics about the languages to stderr
+    #[structopt(long = "stats")]
+    stats: bool,
+
+    /// Print the languages with the most and least saturated, brightest and
+    /// darkest and most and least chromatic colors to stderr
+    #[structopt(long = "extremes")]
+    extremes: bool,
+
+    /// Only fetch, parse and validate the languages and print a summary of
+    /// the issues, exits with 1 if there are any. The warnings of --validate
+    /// are counted in the summary but are informational and do not change
+    /// the exit status
+    #[structopt(long = "dry-run")]
+    dry_run: bool,
+
+    /// Print warnings about suspicious language data like missing colors,
+    /// duplicate extensions or duplicate language ids to stderr, always
+    /// done by --dry-run
+    #[structopt(long = "validate")]
+    validate: bool,
+
+    /// Start the markdown output with the number of languages per type and
+    /// the linguist version
+    #[structopt(long = "with-summary")]
+    with_summary: bool,
+
+    /// Add shields badges for the number of languages and the linguist
+    /// version to the markdown output
+    #[structopt(long = "summary-badges")]
+    summary_badges: bool,
+
+    /// Don't print progress information
+    #[structopt(short = "q", long = "quiet")]
+    quiet: bool,
+
+    /// Filter of the log output, a level like 'warn' or directives like
+    /// 'language_colors=debug'
+    #[structopt(long = "log-level", default_value = "info")]
+    log_level: String,
+
+    /// Format of the log output (text, json)
+    #[structopt(long = "log-format", default_value = "text")]
+    log_format: LogFormat,
+
+    /// Write the duration of every phase, a few counters and the age of the
+    /// cache read by --offline as json to this file after the output is
+    /// written
+    #[structopt(long = "timings", parse(from_os_str))]
+    timings: Option<PathBuf>,
+
+    /// Read the languages from this file instead of fetching them from github
+    #[structopt(long = "input", parse(from_os_str))]
+    input: Option<PathBuf>,
+
+    /// Format of --input (yaml, json for the json, class map or --full
+    /// output of this tool, simple for a yaml or json map from names to
+    /// colors), json or yaml by the extension if not given
+    #[structopt(long = "input-format", requires = "input")]
+    input_format: Option<InputFormat>,
+
+    /// Fetch the languages from this url, can be given multiple times to
+    /// merge languages from several sources with later ones winning
+    #[structopt(long = "source-url", number_of_values = 1)]
+    source_urls: Vec<String>,
+
+    /// Merge the languages of this url or file over the other sources, can be
+    /// given multiple times with later ones winning
+    #[structopt(long = "merge", number_of_values = 1)]
+    merge: Vec<String>,
+
+    /// Abort when the languages are larger than this many bytes (20 MiB by
+    /// default)
+    #[structopt(long = "max-bytes", default_value = "20971520")]
+    max_bytes: u64,
+
+    /// Don't retry unreachable raw github urls through the github contents
+    /// api
+    #[structopt(long = "no-fallback")]
+    no_fallback: bool,
+
+    /// Directory the languages are cached in when they are fetched from a
+    /// single url (default: $XDG_CACHE_HOME/language_colors or
+    /// ~/.cache/language_colors)
+    #[structopt(long = "cache-dir", parse(from_os_str))]
+    cache_dir: Option<PathBuf>,
+
+    /// Read the languages from the cache instead of fetching them
+    #[structopt(long = "offline", conflicts_with_all = &["input", "source-urls"])]
+    offline: bool,
+
+    /// Warn when --offline reads a cache older than this many days
+    #[structopt(long = "stale-after-days", default_value = "30")]
+    stale_after_days: u64,
 
-#[derive(Debug, Serialize, Deserialize)]
-struct LanguageInfo {
-    language_id: i64,
+    /// Don't warn when --offline reads a stale cache
+    #[structopt(long = "no-stale-warning")]
+    no_stale_warning: bool,
 
-    ace_mode: String,
-    color: Option<String>,
-    extensions: Option<Vec<String>>,
-    tm_scope: Option<String>,
+    /// Embed the latest github/linguist release tag in the output
+    #[structopt(long = "version-header")]
+    version_header: bool,
 
-    #[serde(rename = "type")]
-    _type: String,
+    /// Don't write the metadata comments, e.g. to compare outputs generated
+    /// from different sources
+    #[structopt(long = "no-meta", conflicts_with = "version-header")]
+    no_meta: bool,
+
+    /// Abort if the SHA-256 of the languages doesn't match this hex hash
+    #[structopt(long = "expect-sha256")]
+    expect_sha256: Option<String>,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Print the languages file without rendering it
+    #[structopt(name = "fetch")]
+    Fetch {
+        /// Print the SHA-256 of the languages file instead of its content
+        #[structopt(long = "print-sha256")]
+        print_sha256: bool,
+    },
+
+    /// Print reports about the language colors
+    #[structopt(name = "report")]
+    Report(Report),
+
+    /// Render a strip of the colors of a few languages, written to --output
+    #[structopt(name = "card")]
+    Card {
+        /// Comma separated languages in the order they are shown
+        #[structopt(long = "languages", use_delimiter = true, required = true)]
+        languages: Vec<String>,
+
+        /// Comma separated weights of the languages, the width of a segment
+        /// is proportional to its weight (default: equal widths)
+        #[structopt(
+            long = "weights",
+            use_delimiter = true,
+            parse(try_from_str = card::parse_weight)
+        )]
+        weights: Vec<f64>,
+
+        /// Format of the card (svg)
+        #[structopt(long = "format", default_value = "svg")]
+        format: CardFormat,
+    },
+
+    /// Print the languages that were added, removed or changed their color
+    /// between two languages files, written to --output
+    #[structopt(name = "diff")]
+    Diff {
+        /// Path or url of the older languages file
+        old: String,
+
+        /// Path or url of the newer languages file
+        new: String,
+
+        /// Format of the diff (text, html)
+        #[structopt(long = "format", default_value = "text")]
+        format: DiffFormat,
+    },
+
+    /// Inspect or clear the languages cache used by --offline
+    #[structopt(name = "cache")]
+    Cache(CacheCommand),
+
+    /// Print the schema of the json outputs
+    #[structopt(name = "schema")]
+    Schema {
+        /// Format of the schema (json-schema)
+        #[structopt(long = "format", default_value = "json-schema")]
+        format: SchemaFormat,
+    },
+
+    /// Browse the language colors in an interactive terminal interface
+    #[cfg(feature = "tui")]
+    #[structopt(name = "browse")]
+    Browse,
 }
 
-#[derive(Debug, Ord, PartialOrd, PartialEq, Eq, Clone)]
-struct Color {
-    red: i64,
-    green: i64,
-    blue: i64,
+#[derive(Debug, StructOpt)]
+enum CacheCommand {
+    /// Print the path, size, age, url, ETag and SHA-256 of the cached
+    /// languages
+    #[structopt(name = "info")]
+    Info,
+
+    /// Remove the cached languages
+    #[structopt(name = "clear")]
+    Clear,
 }
 
-impl Color {
-    fn from_webcolor(color: &str) -> Self {
-        let color = color.trim_start_matches("#");
+#[derive(Debug, StructOpt)]
+enum Report {
+    /// List languages whose colors are nearly gray with a more saturated
+    /// suggestion
+    #[structopt(name = "grayscale")]
+    Grayscale {
+        /// Highest HSL saturation (0 to 1) that counts as gray
+        #[structopt(long = "max-saturation", default_value = "0.08")]
+        max_saturation: f64,
 
-        let chars = color.chars().collect::<Vec<_>>();
-        let mut chars = chars.chunks(2);
+        /// Saturation of the suggested colors
+        #[structopt(long = "saturation-floor", default_value = "0.25")]
+        saturation_floor: f64,
 
-        let red = i64::from_str_radix(&char_array_to_string(chars.next().unwrap()), 16).unwrap();
-        let green = i64::from_str_radix(&char_array_to_string(chars.next().unwrap()), 16).unwrap();
-        let blue = i64::from_str_radix(&char_array_to_string(chars.next().unwrap()), 16).unwrap();
+        /// Format of the report (text, json)
+        #[structopt(long = "format", default_value = "text")]
+        format: ReportFormat,
+    },
 
-        Self { red, green, blue }
-    }
+    /// List colors that are used by more than one language, ordered by
+    /// --sort-groups
+    #[structopt(name = "shared")]
+    Shared {
+        /// Format of the report (text, json)
+        #[structopt(long = "format", default_value = "text")]
+        format: ReportFormat,
+    },
 
-    fn as_webcolor(&self) -> String {
-        format!("#{:02X}{:02X}{:02X}", self.red, self.green, self.blue)
-    }
+    /// Print the extensions that are claimed by several languages, the most
+    /// claimed first
+    #[structopt(name = "extension-conflicts")]
+    ExtensionConflicts {
+        /// Format of the report (text, json)
+        #[structopt(long = "format", default_value = "text")]
+        format: ReportFormat,
 
-    fn euclidean_distance(&self, other: &Color) -> f64 {
-        let p_red = (other.red - self.red).pow(2);
-        let p_green = (other.green - self.green).pow(2);
-        let p_blue = (other.blue - self.blue).pow(2);
+        /// Only list extensions claimed by at least this many languages
+        #[structopt(long = "min-claimants", default_value = "2")]
+        min_claimants: usize,
+    },
 
-        ((p_red + p_green + p_blue) as f64).sqrt()
-    }
+    /// Print the distances between all languages as a matrix
+    #[structopt(name = "matrix")]
+    Matrix {
+        /// Format of the matrix (csv, json)
+        #[structopt(long = "format", default_value = "csv")]
+        format: MatrixFormat,
+
+        /// Print the matrix even for more than 5000 languages
+        #[structopt(long = "force")]
+        force: bool,
+    },
 }
 
-fn char_array_to_string(chars: &[char]) -> String {
-    chars.iter().fold(String::new(), |mut x, c| {
-        x.push(*c);
-        x
-    })
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("unknown log format: {}", s)),
+        }
+    }
 }
 
 fn main() {
-    eprintln!("fetching");
+    let mut opt = Opt::from_args();
+    init_logging(&opt.log_level, opt.log_format);
+
+    if let Some(path) = opt.params_file.clone() {
+        apply_parameters(&mut opt, &path);
+    }
+
+    if opt.output_format.len() > 1 && opt.out_dir.is_none() {
+        eprintln!("more than one --output-format needs --out-dir");
+        std::process::exit(1);
+    }
+
+    if let Some(Command::Schema { format }) = opt.command {
+        let schema = match format {
+            SchemaFormat::JsonSchema => output::schema::json_schema(),
+        };
+
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&schema).expect("can not serialize schema")
+        );
+        return;
+    }
+
+    let cache = opt
+        .cache_dir
+        .clone()
+        .or_else(Cache::default_dir)
+        .map(Cache::new);
+
+    if let Some(Command::Cache(ref command)) = opt.command {
+        let cache = required_cache(cache.as_ref());
+
+        match command {
+            CacheCommand::Info => match cache.info().expect("can not read cache") {
+                Some(info) => {
+                    for line in info.lines(SystemTime::now()) {
+                        println!("{}", line);
+                    }
+                }
+                None => println!("no languages cached in {}", cache.dir().display()),
+            },
+            CacheCommand::Clear => {
+                for path in cache.clear().expect("can not clear cache") {
+                    println!("removed {}", path.display());
+                }
+            }
+        }
+
+        return;
+    }
+
+    let mut timings = Timings::new();
+    timings.start("fetch");
+
+    let reporter = progress::reporter(opt.quiet);
+    let progress = CountingProgress::new(reporter.as_ref());
+    let fetcher = Fetcher::new(opt.max_bytes, !opt.no_fallback);
+
+    if let Some(Command::Diff {
+        ref old,
+        ref new,
+        format,
+    }) = opt.command
+    {
+        let [old, new] = [old, new].map(|location| {
+            let body = if is_url(location) {
+                fetch_all(
+                    &fetcher,
+                    std::slice::from_ref(location),
+                    &progress,
+                    opt.quiet,
+                )
+                .remove(0)
+                .body
+            } else {
+                read_input(Path::new(location), opt.max_bytes)
+            };
 
-    let body = reqwest::get(
-        "https://raw.githubusercontent.com/github/linguist/master/lib/linguist/languages.yml",
-    )
-    .expect("can not fetch languages from github")
-    .text()
-    .expect("can not get body from request");
+            let languages = serde_yaml::from_slice::<BTreeMap<String, LanguageInfo>>(&body)
+                .expect("can not parse languages");
+            let languages = LanguageColors::new(languages, None).0;
 
-    let languages: BTreeMap<String, LanguageInfo> =
-        serde_yaml::from_str(&body).expect("can not deserialize languages");
+            (
+                Source {
+                    location: location.clone(),
+                    sha256: checksum::sha256_hex(&body),
+                },
+                languages,
+            )
+        });
+
+        let changes = diff::diff(&old.1.colors, &new.1.colors);
+        let output = match format {
+            DiffFormat::Text => output::report::render_diff_text(&changes),
+            DiffFormat::Html => {
+                let metadata = if opt.no_meta {
+                    Metadata::default()
+                } else {
+                    Metadata {
+                        sources: vec![old.0, new.0],
+                        ..Metadata::default()
+                    }
+                };
+                let default_page = output::html::PageOptions::default();
+                let page = output::html::PageOptions {
+                    script: !opt.no_js,
+                    title: opt.title.clone().unwrap_or(default_page.title),
+                    heading: opt.heading.clone().unwrap_or(default_page.heading),
+                    description: opt.description.clone(),
+                    page_size: opt.page_size,
+                    precision: opt.precision,
+                };
+
+                output::html::render_diff(&changes, &metadata, &page)
+            }
+        };
+
+        write_output(opt.output.as_deref(), output.as_bytes());
+        return;
+    }
+
+    let mut sources = Vec::new();
+    let mut bodies = match opt.input {
+        Some(ref path) => {
+            sources.push(path.display().to_string());
+            vec![read_input(path, opt.max_bytes)]
+        }
+        None if opt.offline => {
+            let cache = required_cache(cache.as_ref());
+            let (body, metadata) = cache.read().unwrap_or_else(|err| {
+                eprintln!(
+                    "can not read the cached languages in {}: {}",
+                    cache.dir().display(),
+                    err
+                );
+                std::process::exit(1);
+            });
+
+            let now = SystemTime::now();
+            if !opt.no_stale_warning && metadata.is_stale(now, opt.stale_after_days) {
+                eprintln!(
+                    "WARNING: the cached languages are {} old, run without --offline to refresh \
+                     them",
+                    cache::format_age(metadata.age(now))
+                );
+            }
+
+            timings.cache_age = Some(metadata.age(now));
+            sources.push(cache.languages_path().display().to_string());
+            vec![body]
+        }
+        None => {
+            let urls = if opt.source_urls.is_empty() {
+                vec![fetch::LINGUIST_URL.to_string()]
+            } else {
+                opt.source_urls.clone()
+            };
+
+            let mut bodies = Vec::new();
+            for download in fetch_all(&fetcher, &urls, &progress, opt.quiet) {
+                if urls.len() == 1 {
+                    if let Some(ref cache) = cache {
+                        write_cache(cache, &download);
+                    }
+                }
 
-    let languages_colors: BTreeMap<String, Color> = languages
+                sources.push(download.url);
+                bodies.push(download.body);
+            }
+
+            bodies
+        }
+    };
+
+    if !opt.merge.is_empty() {
+        let urls = opt
+            .merge
+            .iter()
+            .filter(|merge| is_url(merge))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut downloads = fetch_all(&fetcher, &urls, &progress, opt.quiet).into_iter();
+
+        for merge in &opt.merge {
+            if is_url(merge) {
+                let download = downloads.next().expect("can not find merged download");
+                sources.push(download.url);
+                bodies.push(download.body);
+            } else {
+                sources.push(merge.clone());
+                bodies.push(read_input(Path::new(merge), opt.max_bytes));
+            }
+        }
+    }
+
+    for (location, body) in sources.iter().zip(&bodies) {
+        if is_url(location) {
+            timings.bytes_downloaded += body.len() as u64;
+        } else {
+            timings.bytes_read += body.len() as u64;
+        }
+    }
+
+    let sources = sources
         .into_iter()
-        .filter(|(_, info)| info.color.is_some())
-        .map(|(name, info)| {
-            let color = Color::from_webcolor(info.color.as_ref().unwrap());
-            (name, color)
+        .zip(&bodies)
+        .map(|(location, body)| Source {
+            location,
+            sha256: checksum::sha256_hex(body),
         })
-        .collect();
+        .collect::<Vec<_>>();
 
-    let mut used_languages: BTreeSet<String> = BTreeSet::default();
-    let mut is_first_color = true;
-    let mut nearest_colors: Vec<(String, Color)> = Vec::default();
+    let is_fetch = matches!(opt.command, Some(Command::Fetch { .. }));
 
-    eprintln!("sorting");
-    for (f_lang, f_color) in languages_colors.clone() {
-        let mut shortest_distance = 0.0;
-        let mut shortest: Option<(String, Color)> = None;
+    if bodies.len() > 1 && (opt.expect_sha256.is_some() || is_fetch) {
+        eprintln!("--expect-sha256 and fetch only support a single source");
+        std::process::exit(1);
+    }
 
-        for (s_lang, s_color) in &languages_colors {
-            if &f_lang == s_lang {
-                continue;
+    if let Some(ref expected) = opt.expect_sha256 {
+        if let Err(actual) = checksum::verify_sha256(&bodies[0], expected) {
+            eprintln!(
+                "checksum mismatch for languages: expected sha256 {} but got {}",
+                expected, actual
+            );
+            std::process::exit(EXIT_CHECKSUM_MISMATCH);
+        }
+    }
+
+    if let Some(Command::Fetch { print_sha256 }) = opt.command {
+        if print_sha256 {
+            println!("{}", checksum::sha256_hex(&bodies[0]));
+        } else {
+            match opt.output {
+                Some(ref path) => fs::write(path, &bodies[0]).expect("can not write output"),
+                None => io::stdout()
+                    .write_all(&bodies[0])
+                    .expect("can not write languages to stdout"),
             }
+        }
 
-            if used_languages.contains(s_lang) {
-                continue;
-            };
+        return;
+    }
+
+    timings.start("parse");
+    let merged = {
+        let _span = tracing::info_span!("parse", sources = bodies.len()).entered();
+
+        let input_format = opt.input_format.unwrap_or_else(|| {
+            match opt.input.as_ref().and_then(|path| path.extension()) {
+                Some(extension) if extension == "json" => InputFormat::Json,
+                _ => InputFormat::Yaml,
+            }
+        });
+
+        let sources_languages = bodies.iter().enumerate().map(|(index, body)| {
+            if index == 0 && opt.input.is_some() && input_format != InputFormat::Yaml {
+                let imported = match input_format {
+                    InputFormat::Simple => import::parse_simple(body),
+                    _ => import::parse_json(body),
+                }
+                .unwrap_or_else(|err| {
+                    eprintln!("can not parse languages: {}", err);
+                    std::process::exit(1);
+                });
+                for warning in &imported.warnings {
+                    eprintln!("{}", warning);
+                }
+
+                return imported.languages;
+            }
+
+            serde_yaml::from_slice::<BTreeMap<String, LanguageInfo>>(body).unwrap_or_else(|err| {
+                eprintln!("can not parse languages: {}", err);
+                std::process::exit(1);
+            })
+        });
+        let merged = merge::merge_languages(sources_languages);
+        tracing::info!(languages = merged.languages.len(), "parsed");
+
+        merged
+    };
+    timings.languages_parsed = merged.languages.len();
+
+    for collision in &merged.collisions {
+        eprintln!("{}", collision);
+    }
+
+    for conflict in &merged.id_conflicts {
+        eprintln!("{}", conflict);
+    }
+
+    let warnings = if opt.validate || opt.dry_run {
+        merged
+            .languages
+            .iter()
+            .flat_map(|(name, info)| validate::validate_language_info(name, info))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    for warning in &warnings {
+        eprintln!("{}", warning);
+    }
+
+    if opt.strict
+        && !opt.dry_run
+        && (!merged.collisions.is_empty() || !merged.id_conflicts.is_empty())
+    {
+        std::process::exit(1);
+    }
+
+    let (language_colors, invalid_colors) = LanguageColors::new(
+        language::apply_profile(merged.languages, opt.profile, &opt.types),
+        opt.default_color.clone(),
+    );
+    let LanguageColors {
+        languages,
+        colors: languages_colors,
+        defaults,
+        colorless,
+        normalized_names,
+        ids,
+        ..
+    } = language_colors;
+
+    for (name, err) in &invalid_colors {
+        eprintln!("invalid color for language {}: {}", name, err);
+    }
+
+    if opt.validate || opt.dry_run {
+        for (id, name) in &ids.duplicates {
+            eprintln!(
+                "language {:?} has id {} which is already used by {:?}",
+                name,
+                id,
+                ids.by_id(*id).unwrap_or_default()
+            );
+        }
+    }
+
+    if opt.dry_run {
+        let by_name = order::sort_languages(
+            &languages_colors,
+            Order::Name,
+            DistanceMetric::Euclidean,
+            None,
+        );
+        let duplicates = report::shared_colors(&by_name, GroupOrder::Size);
+        for group in &duplicates {
+            eprintln!(
+                "color {} is used by {}",
+                group.color.as_webcolor(),
+                group.languages.join(", ")
+            );
+        }
+
+        let issues = [
+            ("name collisions", merged.collisions.len()),
+            ("language id conflicts", merged.id_conflicts.len()),
+            ("duplicate language ids", ids.duplicates.len()),
+            ("invalid colors", invalid_colors.len()),
+            ("duplicate colors", duplicates.len()),
+        ];
+        let total = issues.iter().map(|(_, count)| count).sum::<usize>();
+
+        eprintln!(
+            "{} languages, {} issues, {} warnings",
+            languages.len(),
+            total,
+            warnings.len()
+        );
+        for (issue, count) in &issues {
+            eprintln!("  {:<22} {}", issue, count);
+        }
+
+        std::process::exit(if total == 0 { 0 } else { 1 });
+    }
+
+    if opt.strict && !invalid_colors.is_empty() {
+        std::process::exit(1);
+    }
+
+    let metric = DistanceMetric::Euclidean;
+
+    if opt.stats {
+        print_stats(&languages, &languages_colors, metric);
+    }
+
+    if opt.extremes {
+        for (label, name, color) in stats::extremes(&languages_colors) {
+            eprintln!("{:<16}  {}  {}", label, color.as_webcolor(), name);
+        }
+    }
+
+    let sample = opt.sample.map(|size| Sample {
+        size,
+        seed: opt.seed.unwrap_or(sample::DEFAULT_SEED),
+    });
+    let languages_colors = match sample {
+        Some(sample) => sample::sample(&languages_colors, sample),
+        None => languages_colors,
+    };
+
+    let (languages_colors, adjusted) = if opt.separate {
+        let separated = separate::separate(&languages_colors, opt.min_distance, metric);
+        for name in &separated.unresolved {
+            eprintln!(
+                "can not separate the color of {} from similar colors, keeping it",
+                name
+            );
+        }
+
+        (separated.colors, separated.adjusted)
+    } else {
+        (languages_colors, BTreeMap::new())
+    };
+    timings.languages_filtered = languages_colors.len();
+
+    let mut order = opt.order.clone();
+    if let Order::List(ref mut list) = order {
+        let path = opt.order_file.as_ref().unwrap();
+        let content = fs::read_to_string(path).expect("can not read order file");
+        *list = OrderList::parse(&content, opt.order_file_exclusive);
+
+        let unknown = list.unknown(&languages_colors);
+        for name in &unknown {
+            eprintln!(
+                "{}: {}",
+                path.display(),
+                suggest::not_found_message(name, languages_colors.keys().map(String::as_str))
+            );
+        }
+
+        if opt.strict && !unknown.is_empty() {
+            std::process::exit(1);
+        }
+    }
+
+    let slice = Slice {
+        offset: opt.offset,
+        limit: opt.limit,
+        reverse: opt.reverse,
+    };
+    timings.languages_rendered = slice.apply(vec![(); languages_colors.len()]).len();
+
+    #[cfg(feature = "tui")]
+    {
+        if let Some(Command::Browse) = opt.command {
+            language_colors::browse::run(&languages, &languages_colors)
+                .expect("can not run terminal browser");
+            return;
+        }
+    }
 
-            let distance = f_color.euclidean_distance(s_color);
-            if shortest.is_none() || distance < shortest_distance {
-                shortest_distance = distance;
-                shortest = Some((s_lang.clone(), s_color.clone()));
+    if let Some(Command::Report(ref report)) = opt.command {
+        match *report {
+            Report::Grayscale {
+                max_saturation,
+                saturation_floor,
+                format,
+            } => {
+                let output = output::report::render_grayscale(
+                    &report::grayscale(&languages_colors, max_saturation, saturation_floor),
+                    format,
+                );
+
+                write_output(opt.output.as_deref(), output.as_bytes());
+            }
+            Report::Shared { format } => {
+                let languages = order::sort_languages(&languages_colors, Order::Name, metric, None);
+                let output = output::report::render_shared_colors(
+                    &report::shared_colors(&slice.apply(languages), opt.sort_groups),
+                    format,
+                );
+
+                write_output(opt.output.as_deref(), output.as_bytes());
+            }
+            Report::ExtensionConflicts {
+                format,
+                min_claimants,
+            } => {
+                let conflicts = report::extension_conflicts(
+                    &ExtensionIndex::new(&languages),
+                    &languages_colors,
+                    min_claimants,
+                );
+                let output = output::report::render_extension_conflicts(&conflicts, format);
+
+                write_output(opt.output.as_deref(), output.as_bytes());
+            }
+            Report::Matrix { format, force } => {
+                let languages = slice.apply(order::sort_languages(
+                    &languages_colors,
+                    order.clone(),
+                    metric,
+                    None,
+                ));
+
+                if languages.len() > MAX_MATRIX_LANGUAGES && !force {
+                    eprintln!(
+                        "refusing to print a matrix of {} languages without --force",
+                        languages.len()
+                    );
+                    std::process::exit(1);
+                }
+
+                let mut out: Box<dyn Write> = match opt.output {
+                    Some(ref path) => Box::new(BufWriter::new(
+                        File::create(path).expect("can not create output"),
+                    )),
+                    None => Box::new(io::stdout()),
+                };
+
+                output::report::write_matrix(&mut out, &languages, metric, format)
+                    .and_then(|_| out.flush())
+                    .expect("can not write matrix");
+            }
+        }
+
+        return;
+    }
+
+    if let Some(Command::Card {
+        ref languages,
+        ref weights,
+        format,
+    }) = opt.command
+    {
+        let languages = languages
+            .iter()
+            .map(|name| {
+                let name = find_language(languages_colors.keys(), name);
+                (name.to_string(), languages_colors[name].clone())
+            })
+            .collect::<Vec<_>>();
+
+        let weights = if weights.is_empty() {
+            None
+        } else {
+            Some(weights.as_slice())
+        };
+        let segments = card::layout(&languages, weights, CARD_WIDTH).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        });
+        let output = match format {
+            CardFormat::Svg => output::card::render_svg(&segments),
+        };
+
+        write_output(opt.output.as_deref(), output.as_bytes());
+        return;
+    }
+
+    let lookup = match (opt.language.as_ref(), opt.id) {
+        (Some(name), _) => Some(find_language(languages.keys(), name)),
+        (None, Some(id)) => match ids.by_id(id) {
+            Some(name) => Some(languages.get_key_value(name).unwrap().0.as_str()),
+            None => {
+                eprintln!("no language with id {}", id);
+                std::process::exit(1);
             }
+        },
+        (None, None) => None,
+    };
+
+    if !opt.compare_language.is_empty() {
+        if opt.compare_language.len() != 2 {
+            eprintln!("--compare-language has to be given exactly twice");
+            std::process::exit(1);
         }
 
-        if is_first_color {
-            used_languages.insert(f_lang.clone());
-            nearest_colors.push((f_lang.clone(), f_color.clone()));
-            is_first_color = false;
+        let compared = opt
+            .compare_language
+            .iter()
+            .map(|name| {
+                let name = find_language(languages.keys(), name);
+                match languages_colors.get(name) {
+                    Some(color) => (name, color),
+                    None => {
+                        eprintln!("language {} has no color", name);
+                        std::process::exit(1);
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        println!(
+            "{}",
+            output::lookup::render_compare(compared[0], compared[1], opt.precision)
+        );
+        return;
+    }
+
+    if let Some(n) = opt.accessible_palette {
+        for (name, color) in palette::generate_accessible_palette(&languages_colors, n) {
+            println!("{}  {}", color.as_webcolor(), name);
         }
 
-        if shortest.is_some() {
-            used_languages.insert(shortest.as_ref().unwrap().0.clone());
-            nearest_colors.push(shortest.unwrap());
+        return;
+    }
+
+    if let Some(name) = lookup {
+        let color = languages_colors.get(name);
+        let nearest = match color {
+            Some(color) => {
+                order::nearest_languages(&languages_colors, color, metric, name, opt.nearest_count)
+            }
+            None => Vec::new(),
+        };
+
+        let rendered = if opt.full {
+            output::lookup::render_full(name, &languages[name], color, &nearest, opt.precision)
+        } else {
+            output::lookup::render(name, &languages[name], color, &nearest, opt.precision)
+        };
+
+        println!("{}", rendered);
+        return;
+    }
+
+    let start_language = opt
+        .start_language
+        .as_ref()
+        .map(|name| find_language(languages_colors.keys(), name));
+    let linguist_version = if opt.version_header {
+        let version = fetcher.fetch_linguist_version().unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        });
+
+        Some(version.expect("linguist has no tags"))
+    } else {
+        None
+    };
+    let mut sections = opt.sections.clone();
+    if opt.group_by_type {
+        replace_tables(&mut sections, Section::Types);
+    }
+
+    if opt.group_by_color_family {
+        replace_tables(&mut sections, Section::Families);
+    }
+
+    let metadata = if opt.no_meta {
+        Metadata::default()
+    } else {
+        Metadata {
+            slice,
+            linguist_version,
+            sources,
+            sample,
+            invalid_colors: invalid_colors.len(),
+            profile: opt.profile,
+            types: opt.types.clone(),
+            parameters: Some(parameters(&opt)),
+        }
+    };
+
+    let default_page = output::html::PageOptions::default();
+    let options = RenderOptions {
+        order,
+        metric,
+        start_language: start_language.map(str::to_string),
+        slice,
+        sections,
+        clusters: opt.clusters,
+        seed: opt.seed,
+        average_space: opt.average_space,
+        sort_groups: opt.sort_groups,
+        band_threshold: opt.band_threshold,
+        metadata,
+        page: output::html::PageOptions {
+            script: !opt.no_js,
+            title: opt.title.clone().unwrap_or(default_page.title),
+            heading: opt.heading.clone().unwrap_or(default_page.heading),
+            description: opt.description.clone(),
+            page_size: opt.page_size,
+            precision: opt.precision,
+        },
+        markdown: MarkdownOptions {
+            summary: opt.with_summary,
+            badges: opt.summary_badges,
+        },
+        csv: CsvOptions {
+            columns: opt.columns.clone(),
+            precision: opt.precision,
+        },
+    };
+
+    if let Some(Paginate::Letter) = opt.paginate {
+        if opt.output_format != [OutputFormat::Html] {
+            eprintln!("--paginate is only supported for the html output format");
+            std::process::exit(1);
+        }
+
+        let out_dir = opt.out_dir.as_ref().unwrap();
+        let by_name = order::sort_languages(&languages_colors, Order::Name, metric, None);
+
+        timings.start("render");
+        let mut files = output_files();
+        files
+            .create_dir_all(out_dir)
+            .expect("can not create output directory");
+        for (file_name, page) in output::html::render_paginated(
+            &slice.apply(by_name),
+            &defaults,
+            &options.metadata,
+            &options.page,
+        ) {
+            exit_if_interrupted(&mut files);
+            files
+                .write(&out_dir.join(file_name), page.as_bytes())
+                .expect("can not write page");
+        }
+        exit_if_interrupted(&mut files);
+
+        write_timings(opt.timings.as_deref(), timings, &progress);
+        return;
+    }
+
+    let language_colors = LanguageColors {
+        languages,
+        colors: languages_colors,
+        defaults,
+        colorless,
+        normalized_names,
+        adjusted,
+        ids,
+    };
+    let renderer = Renderer::new(&language_colors, &options, &progress);
+
+    if opt.timings.is_some() {
+        timings.start("order");
+        renderer.ordered();
+    }
+
+    timings.start("render");
+
+    match opt.out_dir {
+        Some(ref out_dir) => {
+            let mut files = output_files();
+            files
+                .create_dir_all(out_dir)
+                .expect("can not create output directory");
+
+            match opt.split_by {
+                Some(SplitBy::Type) => {
+                    for (stem, part) in render::split_by_type(&language_colors) {
+                        let renderer = Renderer::new(&part, &options, &progress);
+
+                        for format in &opt.output_format {
+                            let file_name = format!("{}.{}", stem, format.extension());
+                            write_file(
+                                &mut files,
+                                &out_dir.join(file_name),
+                                &renderer.render(*format),
+                            );
+                        }
+                    }
+                }
+                None => {
+                    for format in &opt.output_format {
+                        let file_name = format!("{}.{}", opt.out_name, format.extension());
+                        write_file(
+                            &mut files,
+                            &out_dir.join(file_name),
+                            &renderer.render(*format),
+                        );
+                    }
+                }
+            }
+
+            exit_if_interrupted(&mut files);
         }
+        None => write_output(
+            opt.output.as_deref(),
+            &renderer.render(opt.output_format[0]),
+        ),
+    }
+
+    write_timings(opt.timings.as_deref(), timings, &progress);
+}
+
+/// Options of `opt` that are written to the metadata.
+fn parameters(opt: &Opt) -> Parameters {
+    Parameters {
+        order: opt.order.as_str().to_string(),
+        order_file: opt
+            .order_file
+            .as_ref()
+            .map(|path| path.display().to_string()),
+        order_file_exclusive: opt.order_file_exclusive,
+        metric: DistanceMetric::Euclidean.as_str().to_string(),
+        start_language: opt.start_language.clone(),
+        clusters: opt.clusters,
+        seed: opt.seed,
+        average_space: opt.average_space.as_str().to_string(),
+        band_threshold: opt.band_threshold,
+        sort_groups: opt.sort_groups.as_str().to_string(),
+        sample: opt.sample,
+        separate: opt.separate,
+        min_distance: opt.min_distance,
+        profile: opt.profile.as_str().to_string(),
+        types: opt
+            .types
+            .iter()
+            .map(|language_type| language_type.as_str().to_string())
+            .collect(),
+        default_color: opt.default_color.as_ref().map(Color::as_webcolor),
+        offset: opt.offset,
+        limit: opt.limit,
+        reverse: opt.reverse,
     }
+}
 
-    let languages_html_name = languages_colors
+/// Replaces the options of `opt` with the parameters in the json file at
+/// `path`.
+fn apply_parameters(opt: &mut Opt, path: &Path) {
+    let content = fs::read_to_string(path).expect("can not read params file");
+    let parameters = serde_json::from_str::<Parameters>(&content).unwrap_or_else(|err| {
+        eprintln!("invalid {}: {}", path.display(), err);
+        std::process::exit(1);
+    });
+
+    let invalid = |err: String| -> ! {
+        eprintln!("invalid {}: {}", path.display(), err);
+        std::process::exit(1);
+    };
+
+    if parameters.metric.parse::<DistanceMetric>().is_err() {
+        invalid(format!("unknown distance metric: {}", parameters.metric));
+    }
+
+    opt.order = parameters.order.parse().unwrap_or_else(|err| invalid(err));
+    opt.order_file = parameters.order_file.map(PathBuf::from);
+    if let (Order::List(_), None) = (&opt.order, &opt.order_file) {
+        invalid("order file has no order_file".to_string());
+    }
+
+    opt.order_file_exclusive = parameters.order_file_exclusive;
+    opt.start_language = parameters.start_language;
+    opt.clusters = parameters.clusters;
+    opt.seed = parameters.seed;
+    opt.average_space = parameters
+        .average_space
+        .parse()
+        .unwrap_or_else(|err| invalid(err));
+    opt.band_threshold = parameters.band_threshold;
+    opt.sort_groups = parameters
+        .sort_groups
+        .parse()
+        .unwrap_or_else(|err| invalid(err));
+    opt.sample = parameters.sample;
+    opt.separate = parameters.separate;
+    opt.min_distance = parameters.min_distance;
+    opt.profile = parameters
+        .profile
+        .parse()
+        .unwrap_or_else(|err| invalid(err));
+    opt.types = parameters
+        .types
         .iter()
-        .map(|(name, color)| {
-            format!(
-                r#"<tr class="outline_text">
-                    <td bgcolor="{color}">{name}</td>
-                    <td bgcolor="{color}"><code>{color}</code></td>
-                    </tr>"#,
-                name = name,
-                color = color.as_webcolor(),
-            )
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
+        .map(|language_type| language_type.parse().unwrap_or_else(|err| invalid(err)))
+        .collect();
+    opt.default_color = parameters
+        .default_color
+        .map(|color| parse_any_color(&color).unwrap_or_else(|err| invalid(err.to_string())));
+    opt.offset = parameters.offset;
+    opt.limit = parameters.limit;
+    opt.reverse = parameters.reverse;
+}
+
+/// Logs to stderr, filtered by `level` in the syntax of `RUST_LOG`.
+fn init_logging(level: &str, format: LogFormat) {
+    let filter = EnvFilter::try_new(level).unwrap_or_else(|err| {
+        eprintln!("invalid log level {}: {}", level, err);
+        std::process::exit(1);
+    });
+
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(io::stderr)
+        .with_target(false);
+
+    match format {
+        LogFormat::Text => subscriber.init(),
+        LogFormat::Json => subscriber.json().init(),
+    }
+}
+
+fn write_timings(path: Option<&Path>, mut timings: Timings, progress: &CountingProgress) {
+    let path = match path {
+        Some(path) => path,
+        None => return,
+    };
+
+    timings.finish();
+    timings.pairs_compared = progress.pairs();
+
+    let report =
+        serde_json::to_string_pretty(&timings.to_json()).expect("can not serialize timings");
+    fs::write(path, report).expect("can not write timings");
+}
 
-    let languages_html_nearest = nearest_colors
+fn print_stats(
+    languages: &BTreeMap<String, LanguageInfo>,
+    languages_colors: &BTreeMap<String, Color>,
+    metric: DistanceMetric,
+) {
+    eprintln!("languages:        {}", languages.len());
+    for (language_type, count) in stats::count_by_type(languages) {
+        eprintln!("  {:<15} {}", language_type, count);
+    }
+
+    eprintln!("with color:       {}", languages_colors.len());
+    if let Some(average) = stats::average_distance(languages_colors, metric) {
+        eprintln!("average distance: {:.2}", average);
+    }
+}
+
+/// Replaces the by name and nearest color sections with `grouped`, placed
+/// where the first of them was or at the end.
+fn replace_tables(sections: &mut Vec<Section>, grouped: Section) {
+    let position = sections
         .iter()
-        .map(|(name, color)| {
-            format!(
-                r#"<tr class="outline_text">
-                    <td bgcolor="{color}">{name}</td>
-                    <td bgcolor="{color}"><code>{color}</code></td>
-                    </tr>"#,
-                name = name,
-                color = color.as_webcolor(),
-            )
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
-
-    eprintln!("printing");
-    println!(
-        r#"
-    <!DOCTYPE html>
-    <html lang="en">
-    <head>
-    <meta charset="utf-8">
-    <title>title</title>
-    <style>
-    body {{
-      font-size: 30px
-    }}
-
-    tr {{
-      line-height: 50px;
-    }}
-
-    td {{
-      padding-left: 15px;
-    }}
-
-    table {{
-      width: 100%;
-    }}
-
-    .outline_text {{
-      color: white;
-      text-shadow:
-        -1px -1px 0 #000,
-        1px -1px 0 #000,
-        -1px 1px 0 #000,
-        1px 1px 0 #000;
-    }}
-    </style>
-    </head>
-    <body>
-    <h1>Github Programming Language Colors</h1>
-
-    </h2>By Name</h2>
-    <table>
-    <tr>
-    <th>Language</th><th>Color</th>
-    </tr>
-    {}
-    </table>
-    </body>
-    </html>
-
-    </h2>By Nearest Color</h2>
-    <table>
-    <tr>
-    <th>Language</th><th>Color</th>
-    </tr>
-    {}
-    </table>
-    </body>
-    </html>
-                 "#,
-        languages_html_name, languages_html_nearest
-    );
+        .position(|section| matches!(section, Section::Name | Section::Nearest))
+        .unwrap_or(sections.len());
+
+    sections.retain(|section| !matches!(section, Section::Name | Section::Nearest));
+    if !sections.contains(&grouped) {
+        sections.insert(position, grouped);
+    }
+}
+
+/// Writes `output` to `path` or stdout if there is no path.
+fn write_output(path: Option<&Path>, output: &[u8]) {
+    let mut output = output.to_vec();
+    output.push(b'\n');
+
+    match path {
+        Some(path) => fs::write(path, output),
+        None => io::stdout().write_all(&output),
+    }
+    .expect("can not write output");
+}
+
+/// Like `write_output` for one of several output files, exits if the run
+/// was interrupted before.
+fn write_file(files: &mut OutputFiles, path: &Path, output: &[u8]) {
+    exit_if_interrupted(files);
+
+    let mut output = output.to_vec();
+    output.push(b'\n');
+
+    files.write(path, &output).expect("can not write output");
+}
+
+/// Tracks the output files of the modes that write several of them. The
+/// interrupt handler is installed here, before that Ctrl-C and SIGTERM stop
+/// the run right away as there is nothing to clean up.
+fn output_files() -> OutputFiles {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let handler = Arc::clone(&interrupted);
+
+    ctrlc::set_handler(move || handler.store(true, Ordering::SeqCst))
+        .expect("can not install interrupt handler");
+
+    OutputFiles::new(interrupted)
+}
+
+/// Removes the output written so far and exits if the run was interrupted.
+fn exit_if_interrupted(files: &mut OutputFiles) {
+    if files
+        .cleanup_if_interrupted()
+        .expect("can not remove partial output")
+    {
+        eprintln!("interrupted, removed the partial output");
+        std::process::exit(EXIT_INTERRUPTED);
+    }
+}
+
+/// Downloads all `urls`, exits if one of them fails.
+fn fetch_all(
+    fetcher: &Fetcher,
+    urls: &[String],
+    progress: &dyn progress::Progress,
+    quiet: bool,
+) -> Vec<Download> {
+    if urls.is_empty() {
+        return Vec::new();
+    }
+
+    let downloads = fetcher.fetch_all(urls, progress).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    });
+
+    for (url, download) in urls.iter().zip(&downloads) {
+        if !quiet && download.url != *url {
+            eprintln!("{} is unreachable, fetched {}", url, download.url);
+        }
+    }
+
+    downloads
+}
+
+/// Exits if there is no cache directory.
+fn required_cache(cache: Option<&Cache>) -> &Cache {
+    cache.unwrap_or_else(|| {
+        eprintln!("can not find a cache directory, set --cache-dir");
+        std::process::exit(1);
+    })
+}
+
+/// Caches the languages of `download` for `--offline`, a cache that can't
+/// be written only logs a warning.
+fn write_cache(cache: &Cache, download: &Download) {
+    let fetched_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let metadata = CacheMetadata {
+        url: download.url.clone(),
+        fetched_at,
+        etag: download.etag.clone(),
+        sha256: checksum::sha256_hex(&download.body),
+    };
+
+    if let Err(err) = cache.write(&download.body, &metadata) {
+        tracing::warn!(dir = %cache.dir().display(), %err, "can not write cache");
+    }
+}
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+fn read_input(path: &Path, max_bytes: u64) -> Vec<u8> {
+    let size = fs::metadata(path).expect("can not read input file").len();
+
+    let body = validate::check_size(size, max_bytes).and_then(|_| {
+        let body = fs::read(path).expect("can not read input file");
+        validate::check_yaml(&body)?;
+        Ok(body)
+    });
+
+    match body {
+        Ok(body) => body,
+        Err(err) => {
+            eprintln!("invalid {}: {}", path.display(), err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn find_language<'a, I>(languages: I, name: &str) -> &'a str
+where
+    I: Iterator<Item = &'a String> + Clone,
+{
+    let found = languages
+        .clone()
+        .find(|language| language.as_str() == name)
+        .or_else(|| {
+            languages
+                .clone()
+                .find(|language| language.to_lowercase() == name.to_lowercase())
+        });
+
+    match found {
+        Some(language) => language,
+        None => {
+            eprintln!(
+                "{}",
+                suggest::not_found_message(name, languages.map(String::as_str))
+            );
+            std::process::exit(1);
+        }
+    }
 }
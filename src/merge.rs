@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::language::LanguageInfo;
+
+/// Two language names that only differ in case or whitespace. Only `kept`
+/// ends up in the merged languages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameCollision {
+    pub kept: String,
+    pub dropped: String,
+}
+
+impl fmt::Display for NameCollision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "languages {:?} and {:?} only differ in case or whitespace, keeping {:?}",
+            self.dropped, self.kept, self.kept
+        )
+    }
+}
+
+/// A language whose `language_id` changed between two sources.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdConflict {
+    pub language: String,
+    pub previous: i64,
+    pub id: i64,
+}
+
+impl fmt::Display for IdConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "language {:?} has id {} in a later source but {} in an earlier one, using {}",
+            self.language, self.id, self.previous, self.id
+        )
+    }
+}
+
+/// Result of `merge_languages`.
+#[derive(Debug, Default)]
+pub struct Merged {
+    pub languages: BTreeMap<String, LanguageInfo>,
+    pub collisions: Vec<NameCollision>,
+    pub id_conflicts: Vec<IdConflict>,
+}
+
+/// Key two names collide on: lowercased with runs of whitespace collapsed
+/// into a single space and no leading or trailing whitespace.
+pub fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Merges the languages of all `sources`, later sources override earlier
+/// ones. Names that normalize to the same key are reported as collisions
+/// and only one of them is kept: the one from the later source, or the
+/// alphabetically first one if both are in the same source. A language that
+/// changes its `language_id` between sources is reported as an id conflict.
+pub fn merge_languages(
+    sources: impl IntoIterator<Item = BTreeMap<String, LanguageInfo>>,
+) -> Merged {
+    let mut merged = Merged::default();
+    let mut names: BTreeMap<String, String> = BTreeMap::new();
+
+    for source in sources {
+        let mut source_names: BTreeMap<String, String> = BTreeMap::new();
+
+        for (name, info) in source {
+            let key = normalize_name(&name);
+
+            if let Some(kept) = source_names.get(&key) {
+                merged.collisions.push(NameCollision {
+                    kept: kept.clone(),
+                    dropped: name,
+                });
+                continue;
+            }
+
+            if let Some(previous) = names.get(&key) {
+                if let Some(previous_info) = merged.languages.remove(previous) {
+                    if previous_info.language_id != info.language_id {
+                        merged.id_conflicts.push(IdConflict {
+                            language: name.clone(),
+                            previous: previous_info.language_id,
+                            id: info.language_id,
+                        });
+                    }
+                }
+
+                if *previous != name {
+                    merged.collisions.push(NameCollision {
+                        kept: name.clone(),
+                        dropped: previous.clone(),
+                    });
+                }
+            }
+
+            source_names.insert(key.clone(), name.clone());
+            names.insert(key, name.clone());
+            merged.languages.insert(name, info);
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(yaml: &str) -> BTreeMap<String, LanguageInfo> {
+        serde_yaml::from_str(yaml).expect("can not parse test languages")
+    }
+
+    #[test]
+    fn names_normalize_case_and_whitespace() {
+        assert_eq!(normalize_name("Rust"), "rust");
+        assert_eq!(normalize_name("  Common \t Lisp "), "common lisp");
+        assert_ne!(normalize_name("C++"), normalize_name("C"));
+    }
+
+    #[test]
+    fn collisions_in_one_source_keep_the_first_name() {
+        let merged = merge_languages(vec![source(
+            "{rust: {type: programming, ace_mode: rust, language_id: 1}, Rust: {type: \
+             programming, ace_mode: rust, language_id: 1}}",
+        )]);
+
+        assert_eq!(merged.languages.keys().collect::<Vec<_>>(), ["Rust"]);
+        assert_eq!(
+            merged.collisions,
+            [NameCollision {
+                kept: "Rust".to_string(),
+                dropped: "rust".to_string(),
+            }]
+        );
+        assert_eq!(
+            merged.collisions[0].to_string(),
+            r#"languages "rust" and "Rust" only differ in case or whitespace, keeping "Rust""#
+        );
+    }
+
+    #[test]
+    fn collisions_across_sources_keep_the_later_name() {
+        let merged = merge_languages(vec![
+            source("{Rust: {type: programming, color: '#dea584', ace_mode: rust, language_id: 1}}"),
+            source(
+                "{'rust ': {type: programming, color: '#b7410e', ace_mode: rust, language_id: 1}}",
+            ),
+        ]);
+
+        assert_eq!(merged.languages.keys().collect::<Vec<_>>(), ["rust "]);
+        assert_eq!(merged.languages["rust "].color.as_deref(), Some("#b7410e"));
+        assert_eq!(
+            merged.collisions,
+            [NameCollision {
+                kept: "rust ".to_string(),
+                dropped: "Rust".to_string(),
+            }]
+        );
+        assert!(merged.id_conflicts.is_empty());
+    }
+
+    #[test]
+    fn later_sources_override_and_extend() {
+        let merged = merge_languages(vec![
+            source(
+                "{Ruby: {type: programming, color: '#701516', ace_mode: ruby, language_id: 326}, \
+                 Go: {type: programming, color: '#00add8', ace_mode: golang, language_id: 132}}",
+            ),
+            source(
+                "{Ruby: {type: programming, color: '#cc342d', ace_mode: ruby, language_id: 326}, \
+                 DSL: {type: programming, color: '#123456', ace_mode: text, language_id: 9001}}",
+            ),
+        ]);
+
+        assert_eq!(
+            merged.languages.keys().collect::<Vec<_>>(),
+            ["DSL", "Go", "Ruby"]
+        );
+        assert_eq!(merged.languages["Ruby"].color.as_deref(), Some("#cc342d"));
+        assert_eq!(merged.languages["Go"].color.as_deref(), Some("#00add8"));
+        assert!(merged.collisions.is_empty());
+        assert!(merged.id_conflicts.is_empty());
+    }
+
+    #[test]
+    fn changed_ids_are_conflicts() {
+        let merged = merge_languages(vec![
+            source("{Go: {type: programming, ace_mode: golang, language_id: 132}}"),
+            source("{Go: {type: programming, ace_mode: golang, language_id: 9000}}"),
+        ]);
+
+        assert_eq!(merged.languages["Go"].language_id, 9000);
+        assert_eq!(
+            merged.id_conflicts,
+            [IdConflict {
+                language: "Go".to_string(),
+                previous: 132,
+                id: 9000,
+            }]
+        );
+        assert_eq!(
+            merged.id_conflicts[0].to_string(),
+            r#"language "Go" has id 9000 in a later source but 132 in an earlier one, using 9000"#
+        );
+    }
+}
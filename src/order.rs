@@ -0,0 +1,703 @@
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+use std::str::FromStr;
+
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::color::Color;
+use crate::progress::{
+    NoProgress,
+    Progress,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Order {
+    #[default]
+    Name,
+    Nearest,
+    Perceptual,
+    Hilbert,
+    /// Parsed from `file`, the list is filled in from the order file.
+    List(OrderList),
+}
+
+impl Order {
+    /// Name of the order as accepted by `from_str`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Order::Name => "name",
+            Order::Nearest => "nearest",
+            Order::Perceptual => "perceptual",
+            Order::Hilbert => "hilbert",
+            Order::List(_) => "file",
+        }
+    }
+}
+
+impl FromStr for Order {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "name" => Ok(Order::Name),
+            "nearest" => Ok(Order::Nearest),
+            "perceptual" => Ok(Order::Perceptual),
+            "hilbert" => Ok(Order::Hilbert),
+            "file" => Ok(Order::List(OrderList::default())),
+            _ => Err(format!("unknown order: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistanceMetric {
+    Euclidean,
+}
+
+impl DistanceMetric {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DistanceMetric::Euclidean => "euclidean",
+        }
+    }
+
+    pub fn distance(self, a: &Color, b: &Color) -> f64 {
+        match self {
+            DistanceMetric::Euclidean => a.euclidean_distance(b),
+        }
+    }
+}
+
+impl FromStr for DistanceMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "euclidean" => Ok(DistanceMetric::Euclidean),
+            _ => Err(format!("unknown distance metric: {}", s)),
+        }
+    }
+}
+
+/// Orders the languages for output. `start_language` is only used by
+/// `Order::Nearest` and defaults to the first language by name.
+pub fn sort_languages(
+    languages_colors: &BTreeMap<String, Color>,
+    order: Order,
+    metric: DistanceMetric,
+    start_language: Option<&str>,
+) -> Vec<(String, Color)> {
+    sort_languages_with_progress(languages_colors, order, metric, start_language, &NoProgress)
+}
+
+/// Same as `sort_languages` but reports the progress of the slower orderings
+/// to `progress`.
+pub fn sort_languages_with_progress(
+    languages_colors: &BTreeMap<String, Color>,
+    order: Order,
+    metric: DistanceMetric,
+    start_language: Option<&str>,
+    progress: &dyn Progress,
+) -> Vec<(String, Color)> {
+    let _span = tracing::info_span!("sort", ?order, languages = languages_colors.len()).entered();
+
+    match order {
+        Order::Name => sort_by_name(languages_colors),
+        Order::Nearest => nearest_colors(languages_colors, metric, start_language, progress),
+        Order::Perceptual => sort_perceptual(languages_colors),
+        Order::Hilbert => sort_hilbert(languages_colors),
+        Order::List(list) => sort_by_list(languages_colors, &list),
+    }
+}
+
+/// Hand-curated order of languages.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderList {
+    pub names: Vec<String>,
+    /// Leave out languages that are not in `names` instead of appending them
+    /// in name order.
+    pub exclusive: bool,
+}
+
+impl OrderList {
+    /// Reads one language name per line. Blank lines and lines starting with
+    /// `#` are ignored.
+    pub fn parse(content: &str, exclusive: bool) -> Self {
+        let names = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect();
+
+        Self { names, exclusive }
+    }
+
+    /// Names in the list that are not in `languages_colors`.
+    pub fn unknown(&self, languages_colors: &BTreeMap<String, Color>) -> Vec<&str> {
+        self.names
+            .iter()
+            .filter(|name| !languages_colors.contains_key(*name))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Language in an ordered list together with the distance of its color to
+/// the color of the previous language, `None` for the first language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainEntry {
+    pub language: String,
+    pub color: Color,
+    pub distance: Option<f64>,
+    /// Index of the band the entry is in, see `band`.
+    pub band: usize,
+}
+
+/// Pairs every language with the distance to the one before it.
+pub fn chain(languages: &[(String, Color)], metric: DistanceMetric) -> Vec<ChainEntry> {
+    let previous = std::iter::once(None).chain(languages.iter().map(Some));
+
+    languages
+        .iter()
+        .zip(previous)
+        .map(|((language, color), previous)| ChainEntry {
+            language: language.clone(),
+            color: color.clone(),
+            distance: previous.map(|(_, previous)| metric.distance(previous, color)),
+            band: 0,
+        })
+        .collect()
+}
+
+/// Splits the chain into bands: a new band starts at every entry that is
+/// further than `threshold` from the previous one. A threshold of `0.0`
+/// keeps everything in band `0`.
+pub fn band(mut chain: Vec<ChainEntry>, threshold: f64) -> Vec<ChainEntry> {
+    if threshold <= 0.0 {
+        return chain;
+    }
+
+    let mut band = 0;
+    for entry in &mut chain {
+        if entry.distance.is_some_and(|distance| distance > threshold) {
+            band += 1;
+        }
+
+        entry.band = band;
+    }
+
+    chain
+}
+
+/// The `Order::Nearest` ordering with the distance of each step. Equal
+/// distances are resolved by ascending language name, so languages that
+/// share a color always end up in the same order.
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use language_colors::color::Color;
+/// use language_colors::order::{
+///     nearest_chain,
+///     DistanceMetric,
+/// };
+///
+/// let languages_colors = [("Black", "#000000"), ("Zig", "#555555"), ("C", "#555555")]
+///     .iter()
+///     .map(|(name, color)| (name.to_string(), Color::from_webcolor(color).unwrap()))
+///     .collect::<BTreeMap<_, _>>();
+///
+/// let chain = nearest_chain(&languages_colors, DistanceMetric::Euclidean, Some("Black"));
+/// let names = chain.iter().map(|entry| entry.language.as_str()).collect::<Vec<_>>();
+/// assert_eq!(names, ["Black", "C", "Zig"]);
+/// ```
+pub fn nearest_chain(
+    languages_colors: &BTreeMap<String, Color>,
+    metric: DistanceMetric,
+    start_language: Option<&str>,
+) -> Vec<ChainEntry> {
+    let languages = sort_languages(languages_colors, Order::Nearest, metric, start_language);
+
+    chain(&languages, metric)
+}
+
+/// The `count` languages closest to `color`, excluding `name` itself. Equal
+/// distances are ordered by ascending language name.
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use language_colors::color::Color;
+/// use language_colors::order::{
+///     nearest_languages,
+///     DistanceMetric,
+/// };
+///
+/// let languages_colors = [("Zig", "#555555"), ("C", "#555555"), ("Rust", "#DEA584")]
+///     .iter()
+///     .map(|(name, color)| (name.to_string(), Color::from_webcolor(color).unwrap()))
+///     .collect::<BTreeMap<_, _>>();
+///
+/// let nearest = nearest_languages(
+///     &languages_colors,
+///     &Color::from_webcolor("#000000").unwrap(),
+///     DistanceMetric::Euclidean,
+///     "Rust",
+///     1,
+/// );
+/// assert_eq!(nearest[0].0, "C");
+/// ```
+pub fn nearest_languages<'a>(
+    languages_colors: &'a BTreeMap<String, Color>,
+    color: &Color,
+    metric: DistanceMetric,
+    name: &str,
+    count: usize,
+) -> Vec<(&'a str, f64)> {
+    let mut distances = languages_colors
+        .iter()
+        .filter(|(other, _)| other.as_str() != name)
+        .map(|(other, other_color)| (other.as_str(), metric.distance(color, other_color)))
+        .collect::<Vec<_>>();
+
+    distances.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap().then_with(|| a.0.cmp(b.0)));
+    distances.truncate(count);
+
+    distances
+}
+
+/// Key used to collate language names: diacritics are stripped and the name
+/// is lowercased, so `Émacs` sorts next to `emacs` and before `Erlang`.
+pub fn collation_key(name: &str) -> String {
+    name.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Case-insensitive, accent-insensitive alphabetical ordering. Names that
+/// collate equally are ordered by their lowercased form and finally by the
+/// original name, so the result does not depend on the input order.
+fn sort_by_name(languages_colors: &BTreeMap<String, Color>) -> Vec<(String, Color)> {
+    let mut languages = languages_colors
+        .iter()
+        .map(|(name, color)| (name.clone(), color.clone()))
+        .collect::<Vec<_>>();
+
+    languages
+        .sort_by_cached_key(|(name, _)| (collation_key(name), name.to_lowercase(), name.clone()));
+
+    languages
+}
+
+/// Orders by `Color::perceptual_sort_key`, languages with the same key are
+/// ordered by name.
+fn sort_perceptual(languages_colors: &BTreeMap<String, Color>) -> Vec<(String, Color)> {
+    let mut languages = sort_by_name(languages_colors);
+    languages.sort_by_key(|(_, color)| color.perceptual_sort_key());
+
+    languages
+}
+
+/// Orders by the position of the colors on a 3D hilbert curve through the
+/// rgb cube, languages with the same color are ordered by name.
+fn sort_hilbert(languages_colors: &BTreeMap<String, Color>) -> Vec<(String, Color)> {
+    let mut languages = sort_by_name(languages_colors);
+    languages.sort_by_cached_key(|(_, color)| hilbert_index(color.as_array().map(u32::from), 8));
+
+    languages
+}
+
+/// Distance along a 3D hilbert curve through a cube with `bits` bits per
+/// axis. Neighbouring indexes are neighbouring points, so sorting by the
+/// index keeps close points close. Uses the transpose algorithm from John
+/// Skilling, "Programming the Hilbert curve" (2004), and interleaves the
+/// transposed bits into the index.
+pub fn hilbert_index(point: [u32; 3], bits: u32) -> u64 {
+    let mut x = point;
+    let highest = 1 << (bits - 1);
+
+    // Inverse undo of the rotations and reflections.
+    let mut q = highest;
+    while q > 1 {
+        let p = q - 1;
+        for i in 0..x.len() {
+            if x[i] & q != 0 {
+                x[0] ^= p;
+            } else {
+                let t = (x[0] ^ x[i]) & p;
+                x[0] ^= t;
+                x[i] ^= t;
+            }
+        }
+        q >>= 1;
+    }
+
+    // Gray encode.
+    for i in 1..x.len() {
+        x[i] ^= x[i - 1];
+    }
+
+    let mut t = 0;
+    let mut q = highest;
+    while q > 1 {
+        if x[x.len() - 1] & q != 0 {
+            t ^= q - 1;
+        }
+        q >>= 1;
+    }
+
+    for axis in x.iter_mut() {
+        *axis ^= t;
+    }
+
+    let mut index = 0;
+    for bit in (0..bits).rev() {
+        for axis in &x {
+            index = (index << 1) | u64::from((axis >> bit) & 1);
+        }
+    }
+
+    index
+}
+
+/// Languages in the order of `list`. Unknown and repeated names are skipped
+/// and the remaining languages follow in name order unless the list is
+/// exclusive.
+fn sort_by_list(
+    languages_colors: &BTreeMap<String, Color>,
+    list: &OrderList,
+) -> Vec<(String, Color)> {
+    let mut listed = BTreeSet::new();
+    let mut languages = Vec::new();
+
+    for name in &list.names {
+        if let Some(color) = languages_colors.get(name) {
+            if listed.insert(name.as_str()) {
+                languages.push((name.clone(), color.clone()));
+            }
+        }
+    }
+
+    if !list.exclusive {
+        languages.extend(
+            sort_by_name(languages_colors)
+                .into_iter()
+                .filter(|(name, _)| !listed.contains(name.as_str())),
+        );
+    }
+
+    languages
+}
+
+/// Greedy chain that starts at `start_language`, or the first language by
+/// name, and repeatedly appends the unused language closest to the last
+/// appended one. Equal distances go to the language with the name that sorts
+/// first, see `Color::nearest_in_set`.
+fn nearest_colors(
+    languages_colors: &BTreeMap<String, Color>,
+    metric: DistanceMetric,
+    start_language: Option<&str>,
+    progress: &dyn Progress,
+) -> Vec<(String, Color)> {
+    let languages = languages_colors.iter().collect::<Vec<_>>();
+    if languages.is_empty() {
+        return Vec::new();
+    }
+
+    let mut used = vec![false; languages.len()];
+
+    let mut current = languages
+        .iter()
+        .position(|(name, _)| Some(name.as_str()) == start_language)
+        .unwrap_or(0);
+    used[current] = true;
+    let mut nearest_colors = vec![(languages[current].0.clone(), languages[current].1.clone())];
+
+    progress.start("sorting", Some(languages.len() as u64));
+    progress.advance(1);
+
+    while nearest_colors.len() < languages.len() {
+        progress.compared((languages.len() - nearest_colors.len()) as u64);
+
+        // Same tie-break as `Color::nearest_in_set`: the smaller name wins.
+        let mut shortest: Option<(usize, f64)> = None;
+        for s in (0..languages.len()).filter(|&s| !used[s]) {
+            let distance = metric.distance(languages[current].1, languages[s].1);
+
+            match shortest {
+                Some((nearest, nearest_distance))
+                    if nearest_distance < distance
+                        || (nearest_distance == distance
+                            && languages[nearest].0 <= languages[s].0) => {}
+                _ => shortest = Some((s, distance)),
+            }
+        }
+
+        let (nearest, _) = shortest.expect("unused languages left");
+        used[nearest] = true;
+        nearest_colors.push((languages[nearest].0.clone(), languages[nearest].1.clone()));
+        current = nearest;
+
+        progress.advance(1);
+    }
+
+    progress.finish();
+
+    nearest_colors
+}
+
+/// Part of an ordered list selected by `--reverse`, `--offset` and `--limit`.
+/// The slice is taken from the already ordered list, so for
+/// `Order::Nearest` a limit of `n` yields the first `n` entries of the full
+/// chain rather than a chain computed over `n` languages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Slice {
+    pub offset: usize,
+    pub limit: Option<usize>,
+    pub reverse: bool,
+}
+
+impl Slice {
+    pub fn is_full(&self) -> bool {
+        self.offset == 0 && self.limit.is_none() && !self.reverse
+    }
+
+    pub fn apply<T>(&self, mut languages: Vec<T>) -> Vec<T> {
+        if self.reverse {
+            languages.reverse();
+        }
+
+        let languages = languages.into_iter().skip(self.offset);
+
+        match self.limit {
+            Some(limit) => languages.take(limit).collect(),
+            None => languages.collect(),
+        }
+    }
+}
+
+impl std::fmt::Display for Slice {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "offset {}", self.offset)?;
+
+        if let Some(limit) = self.limit {
+            write!(f, ", limit {}", limit)?;
+        }
+
+        if self.reverse {
+            write!(f, ", reversed")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn languages(colors: &[(&str, &str)]) -> BTreeMap<String, Color> {
+        colors
+            .iter()
+            .map(|(name, color)| (name.to_string(), Color::from_webcolor(color).unwrap()))
+            .collect()
+    }
+
+    fn names(languages: &[(String, Color)]) -> Vec<&str> {
+        languages.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    #[test]
+    fn collation_key_folds_case_and_accents() {
+        assert_eq!(collation_key("Élixir"), "elixir");
+        assert_eq!(collation_key("C#"), "c#");
+        assert_eq!(collation_key("Ruby"), collation_key("rUBY"));
+    }
+
+    #[test]
+    fn name_order_collates() {
+        let languages = languages(&[
+            ("Elm", "#60B5CC"),
+            ("Élixir", "#6E4A7E"),
+            ("C#", "#178600"),
+            ("c", "#555555"),
+            ("C", "#555555"),
+            ("zig", "#EC915C"),
+            ("Ada", "#02F88C"),
+        ]);
+
+        let sorted = sort_languages(&languages, Order::Name, DistanceMetric::Euclidean, None);
+        assert_eq!(
+            names(&sorted),
+            ["Ada", "C", "c", "C#", "Élixir", "Elm", "zig"]
+        );
+    }
+
+    #[test]
+    fn hilbert_index_walks_the_corners_of_a_cube() {
+        let corners = [
+            [0, 0, 0],
+            [0, 0, 1],
+            [0, 1, 1],
+            [0, 1, 0],
+            [1, 1, 0],
+            [1, 1, 1],
+            [1, 0, 1],
+            [1, 0, 0],
+        ];
+
+        for (index, corner) in corners.iter().enumerate() {
+            assert_eq!(hilbert_index(*corner, 1), index as u64, "{:?}", corner);
+        }
+    }
+
+    #[test]
+    fn hilbert_index_steps_to_neighbours() {
+        let mut points = Vec::new();
+        for x in 0..4 {
+            for y in 0..4 {
+                for z in 0..4 {
+                    points.push([x, y, z]);
+                }
+            }
+        }
+        points.sort_by_key(|point| hilbert_index(*point, 2));
+
+        let indexes = points
+            .iter()
+            .map(|point| hilbert_index(*point, 2))
+            .collect::<Vec<_>>();
+        assert_eq!(indexes, (0..64).collect::<Vec<_>>());
+
+        for pair in points.windows(2) {
+            let steps = (0..3)
+                .map(|axis| (pair[0][axis] as i32 - pair[1][axis] as i32).abs())
+                .sum::<i32>();
+            assert_eq!(steps, 1, "{:?}", pair);
+        }
+    }
+
+    #[test]
+    fn nearest_chain_extends_from_the_last_language() {
+        let languages = languages(&[
+            ("Ada", "#000000"),
+            ("Bash", "#c8c8c8"),
+            ("C", "#0a0a0a"),
+            ("D", "#bebebe"),
+            ("Elm", "#141414"),
+        ]);
+
+        let sorted = sort_languages(&languages, Order::Nearest, DistanceMetric::Euclidean, None);
+        assert_eq!(names(&sorted), ["Ada", "C", "Elm", "D", "Bash"]);
+
+        let sorted = sort_languages(
+            &languages,
+            Order::Nearest,
+            DistanceMetric::Euclidean,
+            Some("Bash"),
+        );
+        assert_eq!(names(&sorted), ["Bash", "D", "Elm", "C", "Ada"]);
+    }
+
+    #[test]
+    fn nearest_chain_breaks_ties_by_name() {
+        let languages = languages(&[
+            ("Go", "#101010"),
+            ("Ada", "#000000"),
+            ("Zig", "#202020"),
+            ("C", "#101010"),
+        ]);
+
+        let sorted = sort_languages(&languages, Order::Nearest, DistanceMetric::Euclidean, None);
+        assert_eq!(names(&sorted), ["Ada", "C", "Go", "Zig"]);
+    }
+
+    #[test]
+    fn band_splits_the_chain_at_large_steps() {
+        let languages = languages(&[
+            ("Ada", "#000000"),
+            ("Bash", "#0a0a0a"),
+            ("C", "#c8c8c8"),
+            ("D", "#d2d2d2"),
+        ]);
+        let chain = nearest_chain(&languages, DistanceMetric::Euclidean, None);
+        let step = chain[1].distance.unwrap();
+
+        let bands = |threshold| {
+            band(chain.clone(), threshold)
+                .into_iter()
+                .map(|entry| (entry.language, entry.band))
+                .collect::<Vec<_>>()
+        };
+        let expected = |bands: [usize; 4]| {
+            ["Ada", "Bash", "C", "D"]
+                .iter()
+                .map(|name| name.to_string())
+                .zip(bands.iter().copied())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(bands(50.0), expected([0, 0, 1, 1]));
+        assert_eq!(bands(step), expected([0, 0, 1, 1]));
+        assert_eq!(bands(step / 2.0), expected([0, 1, 2, 3]));
+        assert_eq!(bands(0.0), expected([0, 0, 0, 0]));
+        assert_eq!(bands(1000.0), expected([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn order_list_skips_blank_lines_and_comments() {
+        let list = OrderList::parse("# favourites\n  Rust \n\nGo\n#Ada\n", false);
+
+        assert_eq!(list.names, ["Rust", "Go"]);
+        assert!(!list.exclusive);
+    }
+
+    #[test]
+    fn order_list_reports_unknown_names() {
+        let languages = languages(&[("Go", "#00add8"), ("Rust", "#dea584")]);
+        let list = OrderList::parse("Rust\nCobol\nGo\nrust\n", false);
+
+        assert_eq!(list.unknown(&languages), ["Cobol", "rust"]);
+    }
+
+    fn list_languages() -> BTreeMap<String, Color> {
+        languages(&[
+            ("Ada", "#02f88c"),
+            ("C", "#555555"),
+            ("élan", "#123456"),
+            ("Go", "#00add8"),
+            ("Rust", "#dea584"),
+        ])
+    }
+
+    #[test]
+    fn order_list_appends_the_rest_by_name() {
+        let list = OrderList::parse("Rust\nCobol\nGo\nRust\n", false);
+
+        let sorted = sort_languages(
+            &list_languages(),
+            Order::List(list),
+            DistanceMetric::Euclidean,
+            None,
+        );
+        assert_eq!(names(&sorted), ["Rust", "Go", "Ada", "C", "élan"]);
+    }
+
+    #[test]
+    fn exclusive_order_list_drops_the_rest() {
+        let list = OrderList::parse("Rust\nCobol\nGo\nRust\n", true);
+
+        let sorted = sort_languages(
+            &list_languages(),
+            Order::List(list),
+            DistanceMetric::Euclidean,
+            None,
+        );
+        assert_eq!(names(&sorted), ["Rust", "Go"]);
+    }
+}
@@ -0,0 +1,64 @@
+use std::str::FromStr;
+
+use crate::card::Segment;
+use crate::output::html::escape;
+
+/// Total width of a card, the segments share it.
+pub const CARD_WIDTH: f64 = 800.0;
+const CARD_HEIGHT: f64 = 60.0;
+const FONT_SIZE: f64 = 12.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardFormat {
+    Svg,
+}
+
+impl FromStr for CardFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "svg" => Ok(CardFormat::Svg),
+            _ => Err(format!("unknown card format: {}", s)),
+        }
+    }
+}
+
+/// Horizontal strip with one rectangle per segment, labeled with the
+/// language name and its color in the suggested text color.
+pub fn render_svg(segments: &[Segment]) -> String {
+    let rects = segments
+        .iter()
+        .map(|segment| {
+            let center = segment.x + segment.width / 2.0;
+
+            format!(
+                r#"  <g>
+    <rect x="{x:.2}" y="0" width="{width:.2}" height="{height}" fill="{color}"/>
+    <text x="{center:.2}" y="{name_y}" fill="{text}" text-anchor="middle">{name}</text>
+    <text x="{center:.2}" y="{color_y}" fill="{text}" text-anchor="middle">{color}</text>
+  </g>
+"#,
+                x = segment.x,
+                width = segment.width,
+                height = CARD_HEIGHT,
+                color = segment.color.as_webcolor(),
+                center = center,
+                name_y = CARD_HEIGHT / 2.0 - 2.0,
+                color_y = CARD_HEIGHT / 2.0 + FONT_SIZE,
+                text = segment.color.suggested_text_color().as_webcolor(),
+                name = escape(&segment.language),
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        r#"<svg width="{width}" height="{height}" viewBox="0 0 {width} {height}" xmlns="http://www.w3.org/2000/svg" font-family="sans-serif" font-size="{font_size}">
+{rects}</svg>
+"#,
+        width = CARD_WIDTH,
+        height = CARD_HEIGHT,
+        font_size = FONT_SIZE,
+        rects = rects,
+    )
+}
@@ -0,0 +1,183 @@
+use std::str::FromStr;
+
+use crate::order::ChainEntry;
+use crate::output::report::csv_field;
+use crate::output::{
+    format_distance,
+    Metadata,
+};
+
+/// Column of the csv output. The color components come from `Color::to_hsl`
+/// and `Color::to_lab`, which the orders and distance metrics use as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Language,
+    Color,
+    /// HSL hue in degrees.
+    Hue,
+    /// HSL saturation from `0` to `1`.
+    Saturation,
+    /// HSL lightness from `0` to `1`.
+    Lightness,
+    LabL,
+    LabA,
+    LabB,
+    /// Distance to the color of the previous row, empty for the first one.
+    Distance,
+}
+
+/// Columns of the csv output without `--columns`.
+pub const DEFAULT_COLUMNS: [Column; 2] = [Column::Language, Column::Color];
+
+impl Column {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Column::Language => "language",
+            Column::Color => "color",
+            Column::Hue => "hue",
+            Column::Saturation => "saturation",
+            Column::Lightness => "lightness",
+            Column::LabL => "lab_l",
+            Column::LabA => "lab_a",
+            Column::LabB => "lab_b",
+            Column::Distance => "distance",
+        }
+    }
+
+    fn value(self, entry: &ChainEntry, precision: usize) -> String {
+        let (hue, saturation, lightness) = entry.color.to_hsl();
+        let (lab_l, lab_a, lab_b) = entry.color.to_lab();
+
+        let component = match self {
+            Column::Language => return csv_field(&entry.language),
+            Column::Color => return entry.color.as_webcolor(),
+            Column::Distance => match entry.distance {
+                Some(distance) => distance,
+                None => return String::new(),
+            },
+            Column::Hue => hue,
+            Column::Saturation => saturation,
+            Column::Lightness => lightness,
+            Column::LabL => lab_l,
+            Column::LabA => lab_a,
+            Column::LabB => lab_b,
+        };
+
+        format_distance(component, precision)
+    }
+}
+
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "language" => Ok(Column::Language),
+            "color" => Ok(Column::Color),
+            "hue" => Ok(Column::Hue),
+            "saturation" => Ok(Column::Saturation),
+            "lightness" => Ok(Column::Lightness),
+            "lab_l" => Ok(Column::LabL),
+            "lab_a" => Ok(Column::LabA),
+            "lab_b" => Ok(Column::LabB),
+            "distance" => Ok(Column::Distance),
+            _ => Err(format!("unknown column: {}", s)),
+        }
+    }
+}
+
+/// Settings of the csv output.
+#[derive(Debug)]
+pub struct CsvOptions {
+    pub columns: Vec<Column>,
+    /// Decimals of the color components and distances, see
+    /// `output::format_distance`.
+    pub precision: usize,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            columns: DEFAULT_COLUMNS.to_vec(),
+            precision: 2,
+        }
+    }
+}
+
+/// The languages as csv with the metadata as `# ` comment lines and a
+/// header row, one row per language in output order.
+///
+/// ```
+/// use language_colors::color::Color;
+/// use language_colors::order::{
+///     chain,
+///     DistanceMetric,
+/// };
+/// use language_colors::output::csv::{
+///     render,
+///     Column,
+///     CsvOptions,
+/// };
+/// use language_colors::output::Metadata;
+///
+/// let languages = chain(
+///     &[
+///         ("Red".to_string(), Color::from_webcolor("#FF0000").unwrap()),
+///         ("Rust".to_string(), Color::from_webcolor("#DEA584").unwrap()),
+///     ],
+///     DistanceMetric::Euclidean,
+/// );
+/// let options = CsvOptions {
+///     columns: "language,hue,saturation,lightness,lab_l,lab_a,lab_b,distance"
+///         .split(',')
+///         .map(|column| column.parse().unwrap())
+///         .collect(),
+///     precision: 3,
+/// };
+///
+/// assert_eq!(
+///     render(&languages, &Metadata::default(), &options),
+///     "language,hue,saturation,lightness,lab_l,lab_a,lab_b,distance\n\
+///      Red,0.000,1.000,0.500,53.233,80.109,67.220,\n\
+///      Rust,22.000,0.577,0.694,72.299,16.864,25.320,213.864"
+/// );
+///
+/// let metadata = Metadata {
+///     linguist_version: Some("v7.26.0".to_string()),
+///     ..Metadata::default()
+/// };
+/// assert_eq!(
+///     render(&languages, &metadata, &CsvOptions::default()),
+///     "# Generated from github/linguist v7.26.0\n\
+///      language,color\nRed,#FF0000\nRust,#DEA584"
+/// );
+/// assert!("hsl".parse::<Column>().is_err());
+/// ```
+pub fn render(languages: &[ChainEntry], metadata: &Metadata, options: &CsvOptions) -> String {
+    let comments = metadata
+        .lines()
+        .into_iter()
+        .map(|line| format!("# {}", line));
+
+    let header = options
+        .columns
+        .iter()
+        .map(|column| column.as_str())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let rows = languages.iter().map(|entry| {
+        options
+            .columns
+            .iter()
+            .map(|column| column.value(entry, options.precision))
+            .collect::<Vec<_>>()
+            .join(",")
+    });
+
+    comments
+        .chain(std::iter::once(header))
+        .chain(rows)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
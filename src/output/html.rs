@@ -0,0 +1,1077 @@
+use std::collections::BTreeMap;
+use std::io::{
+    self,
+    Write,
+};
+use std::str::FromStr;
+
+use crate::color::{
+    Color,
+    COLOR_FAMILIES,
+};
+use crate::diff::LanguageDiff;
+use crate::language::LanguageType;
+use crate::order::{
+    collation_key,
+    ChainEntry,
+    Order,
+};
+use crate::output::{
+    format_distance,
+    Metadata,
+};
+use crate::report::SharedColor;
+
+const OTHER_PAGE: &str = "other";
+const DEFAULT_HEADING: &str = "Github Programming Language Colors";
+
+/// Size of the color wheel svg in pixels.
+const WHEEL_SIZE: f64 = 600.0;
+
+/// Legend color of language types without any colored language.
+const LEGEND_COLORLESS: Color = Color {
+    red: 128,
+    green: 128,
+    blue: 128,
+    alpha: 255,
+};
+const WHEEL_DOT_RADIUS: f64 = 6.0;
+/// Distance in pixels between dots of languages that share a color.
+const WHEEL_JITTER: f64 = 4.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Legend,
+    Name,
+    Nearest,
+    Groups,
+    Clusters,
+    Colorless,
+    Wheel,
+    Types,
+    Families,
+}
+
+impl FromStr for Section {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "legend" => Ok(Section::Legend),
+            "name" => Ok(Section::Name),
+            "nearest" => Ok(Section::Nearest),
+            "groups" => Ok(Section::Groups),
+            "clusters" => Ok(Section::Clusters),
+            "colorless" => Ok(Section::Colorless),
+            "wheel" => Ok(Section::Wheel),
+            "types" => Ok(Section::Types),
+            "families" => Ok(Section::Families),
+            _ => Err(format!("unknown section: {}", s)),
+        }
+    }
+}
+
+/// Copies the hex color of a clicked `<code data-color>` element to the
+/// clipboard. Selects the text instead when the clipboard api is not
+/// available, e.g. on pages opened from the file system in some browsers.
+const COPY_SCRIPT: &str = r#"<style>
+    code[data-color] {
+      cursor: pointer;
+    }
+    </style>
+    <script>
+    document.addEventListener("click", function (event) {
+      var code = event.target.closest("code[data-color]");
+      if (!code) {
+        return;
+      }
+
+      var color = code.dataset.color;
+
+      function copied() {
+        code.textContent = "copied";
+        setTimeout(function () {
+          code.textContent = color;
+        }, 1000);
+      }
+
+      function select() {
+        var range = document.createRange();
+        range.selectNodeContents(code);
+        var selection = window.getSelection();
+        selection.removeAllRanges();
+        selection.addRange(range);
+      }
+
+      if (navigator.clipboard) {
+        navigator.clipboard.writeText(color).then(copied, select);
+      } else {
+        select();
+      }
+    });
+    </script>"#;
+
+/// Shows one page of the rows of a `.paged` table at a time. The page is kept
+/// in the url hash as `#page-N` so it can be shared, a `#language-...` hash
+/// opens the page of that language.
+const PAGER_SCRIPT: &str = r#"<script>
+    document.addEventListener("DOMContentLoaded", function () {
+      document.querySelectorAll(".paged").forEach(function (paged) {
+        var size = parseInt(paged.dataset.pageSize, 10);
+        var rows = Array.prototype.slice.call(paged.querySelectorAll("tr[id]"));
+        var pages = Math.max(1, Math.ceil(rows.length / size));
+        var previous = paged.querySelector(".previous");
+        var next = paged.querySelector(".next");
+        var label = paged.querySelector(".page");
+
+        function current() {
+          var hash = decodeURIComponent(location.hash.slice(1));
+          var match = /^page-(\d+)$/.exec(hash);
+          if (match) {
+            return Math.min(Math.max(parseInt(match[1], 10), 1), pages);
+          }
+
+          var index = rows.findIndex(function (row) {
+            return row.id === hash;
+          });
+          return index < 0 ? 1 : Math.floor(index / size) + 1;
+        }
+
+        function show() {
+          var page = current();
+          rows.forEach(function (row, index) {
+            row.hidden = Math.floor(index / size) + 1 !== page;
+          });
+
+          label.textContent = page + " / " + pages;
+          previous.disabled = page === 1;
+          next.disabled = page === pages;
+
+          var target = document.getElementById(location.hash.slice(1));
+          if (target && rows.indexOf(target) >= 0) {
+            target.scrollIntoView();
+          }
+        }
+
+        previous.addEventListener("click", function () {
+          location.hash = "page-" + (current() - 1);
+        });
+        next.addEventListener("click", function () {
+          location.hash = "page-" + (current() + 1);
+        });
+        window.addEventListener("hashchange", show);
+
+        show();
+      });
+    });
+    </script>"#;
+
+/// Settings of the page around the sections.
+#[derive(Debug)]
+pub struct PageOptions {
+    /// Include the inline javascript, e.g. for copying colors on click.
+    pub script: bool,
+    pub title: String,
+    pub heading: String,
+    /// Plain text paragraph below the heading.
+    pub description: Option<String>,
+    /// Rows per page of the by name table, `0` shows all rows. Paging needs
+    /// the script, without it all rows are shown.
+    pub page_size: usize,
+    /// Decimals of the distances, see `output::format_distance`.
+    pub precision: usize,
+}
+
+impl Default for PageOptions {
+    fn default() -> Self {
+        Self {
+            script: true,
+            title: DEFAULT_HEADING.to_string(),
+            heading: DEFAULT_HEADING.to_string(),
+            description: None,
+            page_size: 0,
+            precision: 2,
+        }
+    }
+}
+
+/// Languages of one type for `Section::Types`.
+#[derive(Debug)]
+pub struct TypeGroup {
+    pub language_type: LanguageType,
+    pub by_name: Vec<(String, Color)>,
+    pub nearest: Vec<(String, Color)>,
+}
+
+/// Everything the html sections are rendered from. Data for sections that
+/// are not rendered can be left empty.
+#[derive(Debug, Default)]
+pub struct Languages<'a> {
+    pub by_name: &'a [(String, Color)],
+    /// Languages in `--order` for `Section::Name`.
+    pub ordered: &'a [(String, Color)],
+    pub order: Order,
+    /// Languages without a color of their own that got the default color.
+    pub defaults: &'a [String],
+    pub nearest: &'a [ChainEntry],
+    pub clusters: &'a [Vec<(String, Color)>],
+    /// Colors shared by several languages for `Section::Groups`.
+    pub groups: &'a [SharedColor],
+    pub colorless: &'a [String],
+    /// Number of languages and average color per type.
+    pub types: &'a [(LanguageType, usize, Option<Color>)],
+    pub by_type: &'a [TypeGroup],
+    /// Original colors of languages nudged by `separate::separate`, listed
+    /// in a footnote.
+    pub adjusted: &'a [(String, Color)],
+}
+
+pub fn render(
+    sections: &[Section],
+    languages: &Languages,
+    metadata: &Metadata,
+    options: &PageOptions,
+) -> String {
+    let mut body = Vec::new();
+
+    for section in sections {
+        match section {
+            Section::Legend => render_section_legend(&mut body, languages.types),
+            Section::Name => {
+                let page_size = if options.script { options.page_size } else { 0 };
+                render_section_name(
+                    &mut body,
+                    languages.ordered,
+                    &languages.order,
+                    languages.defaults,
+                    page_size,
+                )
+            }
+            Section::Nearest => render_section_nearest(
+                &mut body,
+                languages.nearest,
+                languages.defaults,
+                options.precision,
+            ),
+            Section::Groups => render_section_groups(&mut body, languages.groups),
+            Section::Clusters => {
+                render_section_clusters(&mut body, languages.clusters, languages.defaults)
+            }
+            Section::Colorless => render_section_colorless(&mut body, languages.colorless),
+            Section::Wheel => render_section_wheel(&mut body, languages.by_name),
+            Section::Types => {
+                render_section_types(&mut body, languages.by_type, languages.defaults)
+            }
+            Section::Families => {
+                render_section_families(&mut body, languages.by_name, languages.defaults)
+            }
+        }
+        .expect("can not write to memory");
+    }
+
+    if !languages.adjusted.is_empty() {
+        render_footnote_adjusted(&mut body, languages.adjusted).expect("can not write to memory");
+    }
+
+    page(
+        metadata,
+        options,
+        String::from_utf8(body)
+            .expect("sections are valid utf-8")
+            .trim(),
+    )
+}
+
+/// Languages whose color was nudged apart from a similar color together with
+/// their original color.
+pub fn render_footnote_adjusted(
+    out: &mut dyn Write,
+    adjusted: &[(String, Color)],
+) -> io::Result<()> {
+    let entries = adjusted
+        .iter()
+        .map(|(name, original)| {
+            format!(
+                r#"<li>{name} was <code data-color="{color}">{color}</code></li>"#,
+                name = escape(name),
+                color = original.as_webcolor(),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(
+            "
+    ",
+        );
+
+    writeln!(
+        out,
+        r#"    <p class="footnote">Colors adjusted to keep similar colors apart:</p>
+    <ul class="footnote">
+    {}
+    </ul>
+"#,
+        entries
+    )
+}
+
+/// Bar with a segment per language type, as wide as the share of the
+/// languages of that type and colored with their average color.
+pub fn render_section_legend(
+    out: &mut dyn Write,
+    types: &[(LanguageType, usize, Option<Color>)],
+) -> io::Result<()> {
+    let total = types
+        .iter()
+        .map(|(_, count, _)| count)
+        .sum::<usize>()
+        .max(1);
+
+    let segments = types
+        .iter()
+        .map(|(language_type, count, color)| {
+            let color = color.as_ref().unwrap_or(&LEGEND_COLORLESS);
+
+            format!(
+                r#"<div title="{language_type}: {count}" style="width: {width:.2}%; background-color: {color}"></div>"#,
+                language_type = escape(language_type.as_str()),
+                count = count,
+                width = *count as f64 * 100.0 / total as f64,
+                color = color.as_webcolor(),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n    ");
+
+    writeln!(
+        out,
+        "    <div class=\"legend\">\n    {}\n    </div>\n",
+        segments
+    )
+}
+
+/// Heading of the languages table in `order`.
+fn order_heading(order: &Order) -> &'static str {
+    match order {
+        Order::Name => "By Name",
+        Order::Nearest => "By Nearest Neighbour",
+        Order::Perceptual => "By Perceptual Color",
+        Order::Hilbert => "Along the Hilbert Curve",
+        Order::List(_) => "By Order File",
+    }
+}
+
+/// The `languages` are already in `order`. With a `page_size` above `0` the
+/// table is wrapped in a pager that is driven by `PAGER_SCRIPT`.
+pub fn render_section_name(
+    out: &mut dyn Write,
+    languages: &[(String, Color)],
+    order: &Order,
+    defaults: &[String],
+    page_size: usize,
+) -> io::Result<()> {
+    let table = table(languages, defaults, true);
+    let heading = order_heading(order);
+
+    if page_size == 0 {
+        return writeln!(out, "    <h2>{}</h2>\n    {}\n", heading, table);
+    }
+
+    writeln!(
+        out,
+        r#"    <h2>{}</h2>
+    <div class="paged" data-page-size="{}">
+    <div class="pager"><button class="previous">Previous</button> <span class="page"></span> <button class="next">Next</button></div>
+    {}
+    </div>
+"#,
+        heading, page_size, table
+    )
+}
+
+/// The chain is split into bands at large jumps when it has more than one
+/// band, see `order::band`.
+pub fn render_section_nearest(
+    out: &mut dyn Write,
+    nearest: &[ChainEntry],
+    defaults: &[String],
+    precision: usize,
+) -> io::Result<()> {
+    let table = if nearest.iter().any(|entry| entry.band > 0) {
+        banded_table(nearest, defaults, precision)
+    } else {
+        let languages = nearest
+            .iter()
+            .map(|entry| (entry.language.clone(), entry.color.clone()))
+            .collect::<Vec<_>>();
+
+        table(&languages, defaults, false)
+    };
+
+    writeln!(out, "    <h2>By Nearest Color</h2>\n    {}\n", table)
+}
+
+/// Colors that are used by more than one language, in the order of
+/// `report::shared_colors`.
+pub fn render_section_groups(out: &mut dyn Write, groups: &[SharedColor]) -> io::Result<()> {
+    let rows = groups
+        .iter()
+        .map(|group| {
+            format!(
+                r#"<tr class="outline_text" style="color: {text_color}">
+                    <td bgcolor="{color}"><code data-color="{color}">{color}</code></td>
+                    <td bgcolor="{color}">{count}</td>
+                    <td bgcolor="{color}">{names}</td>
+                    </tr>"#,
+                color = group.color.as_webcolor(),
+                text_color = group.color.suggested_text_color().as_webcolor(),
+                count = group.languages.len(),
+                names = escape(&group.languages.join(", ")),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    writeln!(
+        out,
+        r#"    <h2>Shared Colors</h2>
+    <table>
+    <tr>
+    <th>Color</th><th>Count</th><th>Languages</th>
+    </tr>
+    {}
+    </table>
+"#,
+        rows
+    )
+}
+
+pub fn render_section_clusters(
+    out: &mut dyn Write,
+    clusters: &[Vec<(String, Color)>],
+    defaults: &[String],
+) -> io::Result<()> {
+    writeln!(out, "    <h2>Clusters</h2>")?;
+
+    for (index, cluster) in clusters.iter().enumerate() {
+        writeln!(
+            out,
+            "    <h3>Cluster {}</h3>\n    {}",
+            index + 1,
+            table(cluster, defaults, false)
+        )?;
+    }
+
+    writeln!(out)
+}
+
+/// A section per language type with its own by name and nearest color
+/// tables, the nearest color table is collapsed.
+pub fn render_section_types(
+    out: &mut dyn Write,
+    by_type: &[TypeGroup],
+    defaults: &[String],
+) -> io::Result<()> {
+    for group in by_type {
+        writeln!(
+            out,
+            r#"    <section class="language_type">
+    <h2>{language_type} ({count})</h2>
+    <details open>
+    <summary>By Name</summary>
+    {by_name}
+    </details>
+    <details>
+    <summary>By Nearest Color</summary>
+    {nearest}
+    </details>
+    </section>
+"#,
+            language_type = escape(group.language_type.as_str()),
+            count = group.by_name.len(),
+            by_name = table(&group.by_name, defaults, false),
+            nearest = table(&group.nearest, defaults, false),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Languages grouped by `Color::perceptual_group`, families without any
+/// language are left out.
+pub fn render_section_families(
+    out: &mut dyn Write,
+    by_name: &[(String, Color)],
+    defaults: &[String],
+) -> io::Result<()> {
+    writeln!(out, "    <h2>By Color Family</h2>")?;
+
+    for family in COLOR_FAMILIES.iter() {
+        let languages = by_name
+            .iter()
+            .filter(|(_, color)| color.perceptual_group() == *family)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if languages.is_empty() {
+            continue;
+        }
+
+        writeln!(
+            out,
+            "    <h3 class=\"color_family\">{} ({})</h3>\n    {}",
+            family,
+            languages.len(),
+            table(&languages, defaults, false)
+        )?;
+    }
+
+    writeln!(out)
+}
+
+/// Languages without a color.
+pub fn render_section_colorless(out: &mut dyn Write, colorless: &[String]) -> io::Result<()> {
+    let items = colorless
+        .iter()
+        .map(|name| format!("    <li>{}</li>", escape(name)))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    writeln!(
+        out,
+        "    <h2>Without Color</h2>\n    <ul>\n{}\n    </ul>\n",
+        items
+    )
+}
+
+/// Svg with every language as a dot placed by the hue and saturation of its
+/// color. Dots link to the row of the language in the by name section.
+pub fn render_section_wheel(out: &mut dyn Write, by_name: &[(String, Color)]) -> io::Result<()> {
+    let center = WHEEL_SIZE / 2.0;
+    let radius = center - WHEEL_DOT_RADIUS - WHEEL_JITTER * 2.0;
+
+    let mut seen: BTreeMap<&Color, usize> = BTreeMap::new();
+    let dots = by_name
+        .iter()
+        .map(|(name, color)| {
+            let duplicates = seen.entry(color).or_default();
+            let (hue, saturation, _) = color.to_hsl();
+            let (x, y) = jitter(wheel_position(hue, saturation, radius), *duplicates);
+            *duplicates += 1;
+
+            format!(
+                r##"<a href="#{anchor}"><circle cx="{x:.1}" cy="{y:.1}" r="{r}" fill="{color}" stroke="black"><title>{name} {color}</title></circle></a>"##,
+                anchor = anchor(name),
+                x = center + x,
+                y = center + y,
+                r = WHEEL_DOT_RADIUS,
+                color = color.as_webcolor(),
+                name = escape(name),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n    ");
+
+    writeln!(
+        out,
+        r#"    <h2>Color Wheel</h2>
+    <svg width="{size}" height="{size}" viewBox="0 0 {size} {size}" xmlns="http://www.w3.org/2000/svg">
+    <circle cx="{center}" cy="{center}" r="{radius}" fill="none" stroke="gray"/>
+    {dots}
+    </svg>
+"#,
+        size = WHEEL_SIZE,
+        center = center,
+        radius = radius,
+        dots = dots,
+    )
+}
+
+/// Projects a hue in degrees and a saturation from `0.0` to `1.0` onto a
+/// wheel with the given `radius` centered on the origin. Hue 0 (red) points
+/// right and the hue grows counter-clockwise. The y axis points down like in
+/// svg.
+pub fn wheel_position(hue: f64, saturation: f64, radius: f64) -> (f64, f64) {
+    let angle = hue.to_radians();
+    let distance = saturation * radius;
+
+    (distance * angle.cos(), -distance * angle.sin())
+}
+
+/// Moves the `index`th dot of the same color along a small spiral so every
+/// dot stays hoverable. The first dot stays in place.
+fn jitter((x, y): (f64, f64), index: usize) -> (f64, f64) {
+    if index == 0 {
+        return (x, y);
+    }
+
+    // Golden angle, spreads consecutive dots evenly around the original.
+    let angle = index as f64 * 2.399_963;
+    let distance = WHEEL_JITTER * (index as f64).sqrt();
+
+    (x + distance * angle.cos(), y + distance * angle.sin())
+}
+
+/// Id of the row of a language. Every name maps to a different id, e.g. `C`
+/// and `C++` become `language-C` and `language-C_2b__2b_`.
+fn anchor(name: &str) -> String {
+    let mut anchor = "language-".to_string();
+
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            anchor.push(c);
+        } else {
+            anchor.push_str(&format!("_{:x}_", u32::from(c)));
+        }
+    }
+
+    anchor
+}
+
+/// Splits the by name table into one page per starting letter. Returns the
+/// file names and contents of `index.html` and the letter pages. Names that
+/// don't start with a letter from `a` to `z` end up on `other.html`.
+pub fn render_paginated(
+    by_name: &[(String, Color)],
+    defaults: &[String],
+    metadata: &Metadata,
+    options: &PageOptions,
+) -> Vec<(String, String)> {
+    let mut pages: BTreeMap<String, Vec<(String, Color)>> = BTreeMap::new();
+    for (name, color) in by_name {
+        pages
+            .entry(page_name(name))
+            .or_default()
+            .push((name.clone(), color.clone()));
+    }
+
+    let navigation = navigation(&pages);
+
+    let index_entries = pages
+        .iter()
+        .map(|(page, languages)| {
+            format!(
+                r#"<li><a href="{page}.html">{label}</a> ({count})</li>"#,
+                page = page,
+                label = page_label(page),
+                count = languages.len(),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut files = vec![(
+        "index.html".to_string(),
+        page(
+            metadata,
+            options,
+            &format!("{}\n    <ul>\n{}\n    </ul>", navigation, index_entries),
+        ),
+    )];
+
+    for (page_name, languages) in &pages {
+        let body = format!(
+            "{}\n    <h2>{}</h2>\n    {}",
+            navigation,
+            page_label(page_name),
+            table(languages, defaults, false)
+        );
+
+        files.push((
+            format!("{}.html", page_name),
+            page(metadata, options, &body),
+        ));
+    }
+
+    files
+}
+
+/// Page with the added, removed and changed languages of `diff`. Changed
+/// languages show the old and the new color next to each other with the
+/// distance of the change, sections without languages are left out.
+pub fn render_diff(diff: &LanguageDiff, metadata: &Metadata, options: &PageOptions) -> String {
+    let mut body = String::new();
+
+    if !diff.added.is_empty() {
+        body.push_str(&format!(
+            "    <h2>Added</h2>\n    {}\n",
+            table(&diff.added, &[], false)
+        ));
+    }
+
+    if !diff.removed.is_empty() {
+        body.push_str(&format!(
+            "    <h2>Removed</h2>\n    {}\n",
+            table(&diff.removed, &[], false)
+        ));
+    }
+
+    if !diff.changed.is_empty() {
+        let rows = diff
+            .changed
+            .iter()
+            .map(|change| {
+                format!(
+                    r#"<tr class="outline_text">
+                    <td>{name}</td>
+                    <td bgcolor="{old}" style="color: {old_text}"><code data-color="{old}">{old}</code></td>
+                    <td bgcolor="{new}" style="color: {new_text}"><code data-color="{new}">{new}</code></td>
+                    <td class="distance" data-distance="{distance}">{rounded}</td>
+                    </tr>"#,
+                    name = escape(&change.language),
+                    old = change.old.as_webcolor(),
+                    old_text = change.old.suggested_text_color().as_webcolor(),
+                    new = change.new.as_webcolor(),
+                    new_text = change.new.suggested_text_color().as_webcolor(),
+                    distance = change.distance,
+                    rounded = format_distance(change.distance, options.precision),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        body.push_str(&format!(
+            r#"    <h2>Changed</h2>
+    <table>
+    <tr>
+    <th>Language</th><th>Old</th><th>New</th><th>Distance</th>
+    </tr>
+    {}
+    </table>
+"#,
+            rows
+        ));
+    }
+
+    page(metadata, options, body.trim())
+}
+
+fn page_name(language: &str) -> String {
+    match collation_key(language).chars().next() {
+        Some(c) if c.is_ascii_lowercase() => c.to_string(),
+        _ => OTHER_PAGE.to_string(),
+    }
+}
+
+fn page_label(page_name: &str) -> String {
+    if page_name == OTHER_PAGE {
+        "Other".to_string()
+    } else {
+        page_name.to_uppercase()
+    }
+}
+
+fn navigation(pages: &BTreeMap<String, Vec<(String, Color)>>) -> String {
+    let links = pages
+        .keys()
+        .map(|page| format!(r#"<a href="{}.html">{}</a>"#, page, page_label(page)))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!(r#"<nav><a href="index.html">Index</a> {}</nav>"#, links)
+}
+
+/// Rows of languages in `defaults` are striped. With `anchors` every row gets
+/// an id the color wheel links to.
+fn table(languages: &[(String, Color)], defaults: &[String], anchors: bool) -> String {
+    let rows = languages
+        .iter()
+        .map(|(name, color)| row(name, color, defaults, anchors))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        r#"<table>
+    <tr>
+    <th>Language</th><th>Color</th>
+    </tr>
+    {}
+    </table>"#,
+        rows
+    )
+}
+
+/// Like `table` but every band of the chain gets its own `<tbody>` that
+/// starts with a row showing the distance of the jump into the band. The
+/// unrounded distance is kept in `data-distance` for sorting.
+fn banded_table(chain: &[ChainEntry], defaults: &[String], precision: usize) -> String {
+    let mut bodies: Vec<Vec<String>> = Vec::new();
+    for entry in chain {
+        if bodies.len() <= entry.band {
+            let mut rows = Vec::new();
+            if let Some(distance) = entry.distance.filter(|_| entry.band > 0) {
+                rows.push(format!(
+                    r#"<tr class="band_separator"><td>jump</td><td class="distance" data-distance="{}">{}</td></tr>"#,
+                    distance,
+                    format_distance(distance, precision)
+                ));
+            }
+
+            bodies.push(rows);
+        }
+
+        bodies[entry.band].push(row(&entry.language, &entry.color, defaults, false));
+    }
+
+    let bodies = bodies
+        .iter()
+        .map(|rows| format!("<tbody>\n{}\n    </tbody>", rows.join("\n")))
+        .collect::<Vec<_>>()
+        .join("\n    ");
+
+    format!(
+        r#"<table>
+    <thead>
+    <tr>
+    <th>Language</th><th>Color</th>
+    </tr>
+    </thead>
+    {}
+    </table>"#,
+        bodies
+    )
+}
+
+fn row(name: &str, color: &Color, defaults: &[String], anchors: bool) -> String {
+    let id = if anchors {
+        format!(r#" id="{}""#, anchor(name))
+    } else {
+        String::new()
+    };
+
+    let class = if defaults.iter().any(|default| default == name) {
+        "outline_text default_color"
+    } else {
+        "outline_text"
+    };
+
+    format!(
+        r#"<tr{id} class="{class}" style="color: {text_color}">
+                    <td bgcolor="{color}">{name}</td>
+                    <td bgcolor="{color}"><code data-color="{color}">{color}</code></td>
+                    </tr>"#,
+        id = id,
+        class = class,
+        name = escape(name),
+        color = color.as_webcolor(),
+        text_color = color.suggested_text_color().as_webcolor(),
+    )
+}
+
+fn page(metadata: &Metadata, options: &PageOptions, body: &str) -> String {
+    let comments = metadata
+        .lines()
+        .iter()
+        .map(|line| format!("<!-- {} -->", line))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let script = match (options.script, options.page_size) {
+        (false, _) => String::new(),
+        (true, 0) => COPY_SCRIPT.to_string(),
+        (true, _) => format!("{}\n    {}", COPY_SCRIPT, PAGER_SCRIPT),
+    };
+    let description = match options.description {
+        Some(ref description) => format!("    <p>{}</p>\n", escape(description)),
+        None => String::new(),
+    };
+
+    format!(
+        r#"
+    <!DOCTYPE html>
+    {comments}
+    <html lang="en">
+    <head>
+    <meta charset="utf-8">
+    <title>{title}</title>
+    <style>
+    body {{
+      font-size: 30px
+    }}
+
+    tr {{
+      line-height: 50px;
+    }}
+
+    td {{
+      padding-left: 15px;
+    }}
+
+    table {{
+      width: 100%;
+    }}
+
+    .legend {{
+      display: flex;
+      height: 30px;
+      margin-bottom: 30px;
+    }}
+
+    .default_color td {{
+      background-image: repeating-linear-gradient(
+        45deg,
+        transparent 0 10px,
+        rgba(255, 255, 255, 0.4) 10px 20px
+      );
+    }}
+
+    .language_type h2, .color_family {{
+      text-transform: capitalize;
+    }}
+
+    .band_separator td {{
+      border-top: 4px solid black;
+      font-size: 20px;
+    }}
+
+    td.distance {{
+      text-align: right;
+      font-variant-numeric: tabular-nums;
+    }}
+
+    .pager {{
+      margin-bottom: 15px;
+    }}
+
+    .footnote {{
+      font-size: 14px;
+    }}
+
+    .outline_text {{
+      color: white;
+      text-shadow:
+        -1px -1px 0 #000,
+        1px -1px 0 #000,
+        -1px 1px 0 #000,
+        1px 1px 0 #000;
+    }}
+    </style>
+    {script}
+    </head>
+    <body>
+    <h1>{heading}</h1>
+{description}
+    {body}
+    </body>
+    </html>
+                 "#,
+        comments = comments,
+        title = escape(&options.title),
+        script = script,
+        heading = escape(&options.heading),
+        description = description,
+        body = body,
+    )
+}
+
+/// Escapes the characters that have a meaning in html text and attributes.
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close((x, y): (f64, f64), (expected_x, expected_y): (f64, f64)) {
+        assert!(
+            (x - expected_x).abs() < 1e-9 && (y - expected_y).abs() < 1e-9,
+            "({}, {}) != ({}, {})",
+            x,
+            y,
+            expected_x,
+            expected_y
+        );
+    }
+
+    #[test]
+    fn wheel_position_turns_counter_clockwise() {
+        assert_close(wheel_position(0.0, 1.0, 100.0), (100.0, 0.0));
+        assert_close(wheel_position(90.0, 1.0, 100.0), (0.0, -100.0));
+        assert_close(wheel_position(180.0, 1.0, 100.0), (-100.0, 0.0));
+        assert_close(wheel_position(270.0, 0.5, 100.0), (0.0, 50.0));
+    }
+
+    #[test]
+    fn gray_is_in_the_center() {
+        assert_close(wheel_position(0.0, 0.0, 100.0), (0.0, 0.0));
+        assert_close(wheel_position(123.0, 0.0, 100.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn colors_are_placed_by_hue_and_saturation() {
+        let (hue, saturation, _) = "#00ff00".parse::<Color>().unwrap().to_hsl();
+
+        let (x, y) = wheel_position(hue, saturation, 100.0);
+        assert_close((x, y), (-50.0, -100.0 * 120f64.to_radians().sin()));
+    }
+
+    #[test]
+    fn jitter_keeps_duplicates_apart() {
+        let position = (10.0, 20.0);
+        assert_close(jitter(position, 0), position);
+
+        let moved = (1..8)
+            .map(|index| jitter(position, index))
+            .collect::<Vec<_>>();
+        for (index, &(x, y)) in moved.iter().enumerate() {
+            let distance = ((x - position.0).powi(2) + (y - position.1).powi(2)).sqrt();
+            assert!((distance - WHEEL_JITTER * ((index + 1) as f64).sqrt()).abs() < 1e-9);
+
+            for &(other_x, other_y) in &moved[index + 1..] {
+                assert!((x - other_x).abs() > 0.1 || (y - other_y).abs() > 0.1);
+            }
+        }
+    }
+
+    #[test]
+    fn language_names_are_escaped() {
+        let name = "<b>R&D</b>".to_string();
+        let color = "#123456".parse::<Color>().unwrap();
+        let by_name = vec![(name.clone(), color.clone())];
+        let groups = vec![SharedColor {
+            color: color.clone(),
+            languages: vec![name.clone(), "C".to_string()],
+        }];
+        let colorless = vec![name.clone()];
+
+        let mut out = Vec::new();
+        render_section_name(&mut out, &by_name, &Order::Name, &[], 0).unwrap();
+        render_section_groups(&mut out, &groups).unwrap();
+        render_section_colorless(&mut out, &colorless).unwrap();
+        render_section_wheel(&mut out, &by_name).unwrap();
+        let html = String::from_utf8(out).unwrap();
+
+        assert!(!html.contains("<b>"), "{}", html);
+        assert_eq!(html.matches("&lt;b&gt;R&amp;D&lt;/b&gt;").count(), 4);
+    }
+
+    #[test]
+    fn page_texts_are_escaped() {
+        let options = PageOptions {
+            title: "Colors <draft> & more".to_string(),
+            heading: "R&D <colors>".to_string(),
+            description: Some("<script>alert(1)</script>".to_string()),
+            ..PageOptions::default()
+        };
+
+        let html = page(&Metadata::default(), &options, "");
+
+        assert!(html.contains("<title>Colors &lt;draft&gt; &amp; more</title>"));
+        assert!(html.contains("R&amp;D &lt;colors&gt;"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(!html.contains("<script>alert"));
+    }
+}
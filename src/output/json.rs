@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use schemars::JsonSchema;
+
+use crate::color::Color;
+use crate::order::ChainEntry;
+use crate::output::{
+    Metadata,
+    COLOR_PATTERN,
+    SCHEMA_VERSION,
+};
+use crate::parameters::Parameters;
+
+/// `--output-format json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LanguagesOutput {
+    #[schemars(extend("const" = SCHEMA_VERSION))]
+    pub schema_version: u32,
+    /// Lines describing how the output was generated.
+    pub metadata: Vec<String>,
+    /// Latest github/linguist release with `--version-header`.
+    pub linguist_version: Option<String>,
+    /// Options of the run, accepted by `--params-file`, `null` with
+    /// `--no-meta`.
+    pub parameters: Option<Parameters>,
+    pub languages: Vec<LanguageEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LanguageEntry {
+    pub language: String,
+    #[schemars(regex(pattern = COLOR_PATTERN))]
+    pub color: String,
+    /// Distance to the color of the previous entry, `null` for the first one.
+    pub distance: Option<f64>,
+    /// See `order::band`.
+    pub band: usize,
+    /// The language got the default color.
+    pub default: bool,
+    /// Color before `--separate` nudged it.
+    #[schemars(regex(pattern = COLOR_PATTERN))]
+    pub adjusted_from: Option<String>,
+}
+
+/// `--output-format class-map`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ClassMapOutput {
+    #[schemars(extend("const" = SCHEMA_VERSION))]
+    pub schema_version: u32,
+    /// Lines describing how the output was generated.
+    pub metadata: Vec<String>,
+    /// Latest github/linguist release with `--version-header`.
+    pub linguist_version: Option<String>,
+    /// Options of the run, accepted by `--params-file`, `null` with
+    /// `--no-meta`.
+    pub parameters: Option<Parameters>,
+    /// Css class name and color by language name.
+    pub languages: BTreeMap<String, ClassEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ClassEntry {
+    pub class: String,
+    #[schemars(regex(pattern = COLOR_PATTERN))]
+    pub color: String,
+    /// The language got the default color.
+    pub default: bool,
+}
+
+/// The languages as a json array in output order. Each entry has the
+/// distance to the color of the previous entry, `null` for the first one,
+/// and its band, see `order::band`.
+/// Languages in `defaults` are marked with `"default": true`, languages in
+/// `adjusted` have their original color in `adjusted_from`.
+pub fn render(
+    chain: &[ChainEntry],
+    defaults: &[String],
+    adjusted: &BTreeMap<String, Color>,
+    metadata: &Metadata,
+) -> String {
+    let languages = chain
+        .iter()
+        .map(|entry| LanguageEntry {
+            language: entry.language.clone(),
+            color: entry.color.as_webcolor(),
+            distance: entry.distance,
+            band: entry.band,
+            default: defaults.contains(&entry.language),
+            adjusted_from: adjusted.get(&entry.language).map(Color::as_webcolor),
+        })
+        .collect();
+
+    let output = LanguagesOutput {
+        schema_version: SCHEMA_VERSION,
+        metadata: metadata.lines_without_parameters(),
+        linguist_version: metadata.linguist_version.clone(),
+        parameters: metadata.parameters.clone(),
+        languages,
+    };
+
+    serde_json::to_string_pretty(&output).expect("can not serialize languages")
+}
+
+/// Prefix of the css class names in the class map.
+const CLASS_PREFIX: &str = "lang-";
+
+/// Object from language name to its css class name and color. `identifiers`
+/// maps every language name to its identifier, see `sanitize_identifiers`.
+/// Languages in `defaults` are marked with `"default": true`.
+pub fn render_class_map(
+    languages: &[(String, Color)],
+    identifiers: &BTreeMap<String, String>,
+    defaults: &[String],
+    metadata: &Metadata,
+) -> String {
+    let classes = languages
+        .iter()
+        .map(|(name, color)| {
+            let class = ClassEntry {
+                class: format!("{}{}", CLASS_PREFIX, identifiers[name]),
+                color: color.as_webcolor(),
+                default: defaults.contains(name),
+            };
+
+            (name.clone(), class)
+        })
+        .collect();
+
+    let output = ClassMapOutput {
+        schema_version: SCHEMA_VERSION,
+        metadata: metadata.lines_without_parameters(),
+        linguist_version: metadata.linguist_version.clone(),
+        parameters: metadata.parameters.clone(),
+        languages: classes,
+    };
+
+    serde_json::to_string_pretty(&output).expect("can not serialize languages")
+}
@@ -0,0 +1,41 @@
+use std::collections::BTreeMap;
+
+use crate::color::Color;
+use crate::output::Metadata;
+
+/// `identifiers` maps every language name to its variable name, see
+/// `sanitize_identifiers`.
+pub fn render(
+    languages: &[(String, Color)],
+    identifiers: &BTreeMap<String, String>,
+    metadata: &Metadata,
+) -> String {
+    let comments = metadata
+        .lines()
+        .iter()
+        .map(|line| format!("// {}\n", line))
+        .collect::<String>();
+
+    let variables = languages
+        .iter()
+        .map(|(name, color)| {
+            format!(
+                "@{name}-color: {color};",
+                name = identifiers[name],
+                color = color.as_webcolor(),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let entries = languages
+        .iter()
+        .map(|(name, _)| format!("  {name}: @{name}-color;", name = identifiers[name]))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "{}{}\n\n@language-colors: {{\n{}\n}};",
+        comments, variables, entries
+    )
+}
@@ -0,0 +1,264 @@
+use schemars::JsonSchema;
+
+use crate::color::Color;
+use crate::language::LanguageInfo;
+use crate::output::{
+    format_distance,
+    COLOR_PATTERN,
+    SCHEMA_VERSION,
+};
+use crate::palette::MIN_CONTRAST;
+
+pub fn render(
+    name: &str,
+    info: &LanguageInfo,
+    color: Option<&Color>,
+    nearest: &[(&str, f64)],
+    precision: usize,
+) -> String {
+    let color = color
+        .map(Color::as_webcolor)
+        .unwrap_or_else(|| "none".to_string());
+
+    let extensions = info
+        .extensions
+        .as_ref()
+        .map(|extensions| extensions.join(", "))
+        .unwrap_or_else(|| "none".to_string());
+
+    let mut lines = vec![
+        name.to_string(),
+        format!("  color:      {}", color),
+        format!("  type:       {}", info.language_type),
+        format!("  extensions: {}", extensions),
+    ];
+
+    if !nearest.is_empty() {
+        lines.push("  nearest:".to_string());
+
+        let name_width = nearest
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        let distances = nearest
+            .iter()
+            .map(|(_, distance)| format_distance(*distance, precision))
+            .collect::<Vec<_>>();
+        let distance_width = distances.iter().map(String::len).max().unwrap_or(0);
+        for ((name, _), distance) in nearest.iter().zip(&distances) {
+            lines.push(format!(
+                "    {:name_width$}  {:>distance_width$}",
+                name,
+                distance,
+                name_width = name_width,
+                distance_width = distance_width
+            ));
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Differences between the colors of two languages. They are called
+/// different when their contrast ratio reaches `MIN_CONTRAST`, the WCAG
+/// minimum for graphical objects.
+pub fn render_compare(
+    (a, a_color): (&str, &Color),
+    (b, b_color): (&str, &Color),
+    precision: usize,
+) -> String {
+    let contrast = a_color.contrast_ratio(b_color);
+    let verdict = if contrast >= MIN_CONTRAST {
+        "different"
+    } else {
+        "similar"
+    };
+
+    [
+        format!(
+            "{} {} / {} {}",
+            a,
+            a_color.as_webcolor(),
+            b,
+            b_color.as_webcolor()
+        ),
+        format!(
+            "  rgb distance:   {}",
+            format_distance(a_color.euclidean_distance(b_color), precision)
+        ),
+        format!(
+            "  delta e 76:     {}",
+            format_distance(a_color.delta_e_76(b_color), precision)
+        ),
+        format!(
+            "  delta e 2000:   {}",
+            format_distance(a_color.delta_e_2000(b_color), precision)
+        ),
+        format!(
+            "  contrast ratio: {}:1",
+            format_distance(contrast, precision)
+        ),
+        format!(
+            "  verdict:        {} (threshold {}:1)",
+            verdict, MIN_CONTRAST
+        ),
+    ]
+    .join("\n")
+}
+
+/// `--full` record of one language, see `render_full`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct FullRecord {
+    #[schemars(extend("const" = SCHEMA_VERSION))]
+    pub schema_version: u32,
+    pub name: String,
+    pub language_id: i64,
+    pub color: Option<ColorDetails>,
+    #[serde(rename = "type")]
+    pub language_type: String,
+    pub extensions: Option<Vec<String>>,
+    pub filenames: Option<Vec<String>>,
+    pub aliases: Option<Vec<String>>,
+    pub tm_scope: Option<String>,
+    pub ace_mode: String,
+    pub group: Option<String>,
+    pub css_named_color: Option<CssNamedColor>,
+    /// Black or white, whichever is easier to read on the color.
+    #[schemars(regex(pattern = COLOR_PATTERN))]
+    pub text_color: Option<String>,
+    pub nearest: Vec<NearestLanguage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ColorDetails {
+    #[schemars(regex(pattern = COLOR_PATTERN))]
+    pub hex: String,
+    pub rgb: Rgb,
+    pub hsl: Hsl,
+    pub lab: Lab,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Rgb {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Hsl {
+    /// Degrees from `0.0` to `360.0`.
+    pub hue: f64,
+    pub saturation: f64,
+    pub lightness: f64,
+}
+
+/// CIELAB.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Lab {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+/// Closest CSS named color and its CIEDE2000 difference.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct CssNamedColor {
+    pub name: String,
+    pub delta_e: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct NearestLanguage {
+    pub language: String,
+    pub distance: f64,
+}
+
+/// Everything known about one language as a json object. Fields without a
+/// value are `null` so every record has the same keys. The HSL and CIELAB
+/// components of the color and the CIEDE2000 difference to the closest CSS
+/// named color are rounded to `precision` decimals.
+///
+/// ```
+/// use language_colors::color::Color;
+/// use language_colors::output::lookup::render_full;
+///
+/// let info = serde_yaml::from_str("{type: programming, ace_mode: rust, language_id: 327}").unwrap();
+/// let full = render_full("Rust", &info, Some(&Color::from_webcolor("#DEA584").unwrap()), &[], 3);
+/// let full = serde_json::from_str::<serde_json::Value>(&full).unwrap();
+///
+/// assert_eq!(full["color"]["hsl"]["saturation"], 0.577);
+/// assert_eq!(full["color"]["lab"]["l"], 72.299);
+/// assert_eq!(full["color"]["lab"]["b"], 25.32);
+/// assert_eq!(full["css_named_color"]["name"], "burlywood");
+/// ```
+pub fn render_full(
+    name: &str,
+    info: &LanguageInfo,
+    color: Option<&Color>,
+    nearest: &[(&str, f64)],
+    precision: usize,
+) -> String {
+    let round = |component: f64| {
+        format_distance(component, precision)
+            .parse::<f64>()
+            .expect("can not parse rounded component")
+    };
+
+    let color_details = color.map(|color| {
+        let (hue, saturation, lightness) = color.to_hsl();
+        let (lab_l, lab_a, lab_b) = color.to_lab();
+        let (red, green, blue) = color.as_tuple();
+
+        ColorDetails {
+            hex: color.as_webcolor(),
+            rgb: Rgb { red, green, blue },
+            hsl: Hsl {
+                hue: round(hue),
+                saturation: round(saturation),
+                lightness: round(lightness),
+            },
+            lab: Lab {
+                l: round(lab_l),
+                a: round(lab_a),
+                b: round(lab_b),
+            },
+        }
+    });
+
+    let css_named_color = color.map(|color| {
+        let (name, delta_e) = color.nearest_named_css();
+        CssNamedColor {
+            name: name.to_string(),
+            delta_e: round(delta_e),
+        }
+    });
+
+    let nearest = nearest
+        .iter()
+        .map(|(name, distance)| NearestLanguage {
+            language: name.to_string(),
+            distance: *distance,
+        })
+        .collect();
+
+    let output = FullRecord {
+        schema_version: SCHEMA_VERSION,
+        name: name.to_string(),
+        language_id: info.language_id,
+        color: color_details,
+        language_type: info.language_type.as_str().to_string(),
+        extensions: info.extensions.clone(),
+        filenames: info.filenames.clone(),
+        aliases: info.aliases.clone(),
+        tm_scope: info.tm_scope.clone(),
+        ace_mode: info.ace_mode.clone(),
+        group: info.group.clone(),
+        css_named_color,
+        text_color: color.map(|color| color.suggested_text_color().as_webcolor()),
+        nearest,
+    };
+
+    serde_json::to_string_pretty(&output).expect("can not serialize language")
+}
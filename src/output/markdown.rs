@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+
+use crate::color::Color;
+use crate::language::LanguageType;
+use crate::output::Metadata;
+
+const SHIELDS_URL: &str = "https://img.shields.io/badge";
+
+/// Settings of the markdown output.
+#[derive(Debug, Default)]
+pub struct MarkdownOptions {
+    /// Paragraph with the language counts above the table.
+    pub summary: bool,
+    /// Shields badges for the number of languages and the linguist version
+    /// above the summary.
+    pub badges: bool,
+}
+
+/// Numbers shown in the summary, for all languages and not only the
+/// rendered ones.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub languages: usize,
+    /// Languages with a color, including ones that got the default color.
+    pub colored: usize,
+    pub by_type: BTreeMap<LanguageType, usize>,
+}
+
+/// Table of the languages and their colors. Languages in `defaults` are
+/// marked with `(default)`. The summary and the badges are taken from
+/// `summary` and the linguist version in `metadata`.
+pub fn render(
+    languages: &[(String, Color)],
+    defaults: &[String],
+    summary: &Summary,
+    metadata: &Metadata,
+    options: &MarkdownOptions,
+) -> String {
+    let mut out = metadata
+        .lines()
+        .iter()
+        .map(|line| format!("<!-- {} -->\n", line))
+        .collect::<String>();
+
+    if options.badges {
+        out.push_str(&badge("languages", &summary.languages.to_string(), "blue"));
+
+        if let Some(ref version) = metadata.linguist_version {
+            out.push(' ');
+            out.push_str(&badge("linguist", version, "informational"));
+        }
+
+        out.push_str("\n\n");
+    }
+
+    if options.summary {
+        let by_type = summary
+            .by_type
+            .iter()
+            .map(|(language_type, count)| format!("{} {}", count, language_type.as_str()))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        out.push_str(&format!(
+            "**{}** languages, **{}** of them with a color ({}).",
+            summary.languages, summary.colored, by_type
+        ));
+
+        if let Some(ref version) = metadata.linguist_version {
+            out.push_str(&format!(
+                " Generated from github/linguist {}.",
+                escape_markdown(version)
+            ));
+        }
+
+        out.push_str("\n\n");
+    }
+
+    out.push_str("| Language | Color |\n| --- | --- |\n");
+    for (name, color) in languages {
+        let default = if defaults.contains(name) {
+            " (default)"
+        } else {
+            ""
+        };
+
+        out.push_str(&format!(
+            "| {} | `{}`{} |\n",
+            escape_markdown(name),
+            color.as_webcolor(),
+            default
+        ));
+    }
+
+    out.trim_end().to_string()
+}
+
+/// Image link to a static shields badge.
+fn badge(label: &str, message: &str, color: &str) -> String {
+    format!(
+        "![{alt}]({url}/{label}-{message}-{color})",
+        alt = escape_markdown(&format!("{} {}", label, message)),
+        url = SHIELDS_URL,
+        label = shields_escape(label),
+        message = shields_escape(message),
+        color = color,
+    )
+}
+
+/// Backslash-escapes the ascii punctuation that markdown can interpret, so
+/// the text shows up literally in a paragraph, a table cell or link text.
+///
+/// ```
+/// use language_colors::output::markdown::escape_markdown;
+///
+/// assert_eq!(escape_markdown("v7.26.0"), "v7\\.26\\.0");
+/// assert_eq!(escape_markdown("C++"), "C\\+\\+");
+/// assert_eq!(escape_markdown("a|b"), "a\\|b");
+/// assert_eq!(escape_markdown("*[x](y)_"), "\\*\\[x\\]\\(y\\)\\_");
+/// assert_eq!(escape_markdown("Rust"), "Rust");
+/// ```
+pub fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii_punctuation() {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+/// Label or message of a shields static badge path: dashes and underscores
+/// are doubled because single ones separate the parts and stand for spaces,
+/// everything except unreserved url characters is percent-encoded.
+///
+/// ```
+/// use language_colors::output::markdown::shields_escape;
+///
+/// assert_eq!(shields_escape("languages"), "languages");
+/// assert_eq!(shields_escape("v7.26.0"), "v7.26.0");
+/// assert_eq!(shields_escape("v7-rc_1"), "v7--rc__1");
+/// assert_eq!(shields_escape("42 languages"), "42%20languages");
+/// assert_eq!(shields_escape("a/b?c#d"), "a%2Fb%3Fc%23d");
+/// assert_eq!(shields_escape("é"), "%C3%A9");
+/// ```
+pub fn shields_escape(text: &str) -> String {
+    let text = text.replace('-', "--").replace('_', "__");
+
+    let mut escaped = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            escaped.push(byte as char);
+        } else {
+            escaped.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    escaped
+}
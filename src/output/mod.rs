@@ -0,0 +1,397 @@
+pub mod card;
+pub mod csv;
+pub mod html;
+pub mod json;
+pub mod less;
+pub mod lookup;
+pub mod markdown;
+pub mod report;
+pub mod schema;
+
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+use std::str::FromStr;
+
+use crate::language::{
+    LanguageType,
+    Profile,
+};
+use crate::order::{
+    collation_key,
+    Slice,
+};
+use crate::parameters::Parameters;
+use crate::sample::Sample;
+
+/// Version of the json outputs described by `schema::json_schema`. Bumped
+/// whenever the schema changes, including new fields.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Pattern of the colors in the json outputs, see `Color::as_webcolor`.
+pub const COLOR_PATTERN: &str = "^#[0-9A-F]{6}([0-9A-F]{2})?$";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    ClassMap,
+    Csv,
+    Html,
+    Json,
+    Less,
+    Markdown,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "class-map" => Ok(OutputFormat::ClassMap),
+            "csv" => Ok(OutputFormat::Csv),
+            "html" => Ok(OutputFormat::Html),
+            "json" => Ok(OutputFormat::Json),
+            "less" => Ok(OutputFormat::Less),
+            "markdown" => Ok(OutputFormat::Markdown),
+            _ => Err(format!("unknown output format: {}", s)),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// Extension of the files the format is written to with `--out-dir`.
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::ClassMap => "class-map.json",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Html => "html",
+            OutputFormat::Json => "json",
+            OutputFormat::Less => "less",
+            OutputFormat::Markdown => "md",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Paginate {
+    Letter,
+}
+
+impl FromStr for Paginate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "letter" => Ok(Paginate::Letter),
+            _ => Err(format!("unknown pagination: {}", s)),
+        }
+    }
+}
+
+/// How `--out-dir` outputs are split into several files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitBy {
+    /// One file per language type, see `render::split_by_type`.
+    Type,
+}
+
+impl FromStr for SplitBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "type" => Ok(SplitBy::Type),
+            _ => Err(format!("unknown split: {}", s)),
+        }
+    }
+}
+
+/// Url or file the languages were read from.
+#[derive(Debug, Clone)]
+pub struct Source {
+    pub location: String,
+    /// Hex encoded SHA-256 of the raw languages.
+    pub sha256: String,
+}
+
+/// Information about how an output was generated. Every format writes it as
+/// comments at the top of the output.
+#[derive(Debug, Default)]
+pub struct Metadata {
+    pub slice: Slice,
+    pub linguist_version: Option<String>,
+    /// Urls and files the languages were read from.
+    pub sources: Vec<Source>,
+    pub sample: Option<Sample>,
+    /// Number of languages left out because their color can not be parsed.
+    pub invalid_colors: usize,
+    pub profile: Profile,
+    /// Types selected with `--type`, they replace the types of the profile.
+    pub types: Vec<LanguageType>,
+    /// Options of the run, written as a json line.
+    pub parameters: Option<Parameters>,
+}
+
+impl Metadata {
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = self.lines_without_parameters();
+
+        if let Some(ref parameters) = self.parameters {
+            lines.push(format!(
+                "parameters: {}",
+                serde_json::to_string(parameters).expect("can not serialize parameters")
+            ));
+        }
+
+        lines
+    }
+
+    /// `lines` for the json outputs, which have the parameters as an object
+    /// of their own.
+    pub fn lines_without_parameters(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        if let Some(ref version) = self.linguist_version {
+            lines.push(format!("Generated from github/linguist {}", version));
+        }
+
+        for source in &self.sources {
+            lines.push(format!(
+                "Fetched from {} (sha256 {})",
+                source.location, source.sha256
+            ));
+        }
+
+        if let Some(ref sample) = self.sample {
+            lines.push(format!("sample: {}", sample));
+        }
+
+        if self.profile != Profile::All {
+            lines.push(format!("profile: {}", self.profile));
+        }
+
+        if !self.types.is_empty() {
+            let types = self
+                .types
+                .iter()
+                .map(LanguageType::as_str)
+                .collect::<Vec<_>>();
+            lines.push(format!("types: {}", types.join(", ")));
+        }
+
+        if self.invalid_colors > 0 {
+            lines.push(format!("invalid colors: {}", self.invalid_colors));
+        }
+
+        if !self.slice.is_full() {
+            lines.push(format!("slice: {}", self.slice));
+        }
+
+        lines
+    }
+}
+
+/// Formats a distance with `precision` decimals for people to read, used by
+/// the html and the terminal outputs. The rounding is done on the shortest
+/// decimal representation of the distance, so `0.995` is rounded like it is
+/// written instead of like the slightly smaller binary value.
+///
+/// ```
+/// use language_colors::output::format_distance;
+///
+/// assert_eq!(format_distance(33.970575502926055, 2), "33.97");
+/// assert_eq!(format_distance(0.995, 2), "1.00");
+/// assert_eq!(format_distance(0.994, 2), "0.99");
+/// assert_eq!(format_distance(1.005, 2), "1.01");
+/// assert_eq!(format_distance(9.9996, 3), "10.000");
+/// assert_eq!(format_distance(2.5, 0), "3");
+/// assert_eq!(format_distance(17.0, 2), "17.00");
+/// assert_eq!(format_distance(0.0, 1), "0.0");
+/// assert_eq!(format_distance(-1.25, 1), "-1.3");
+/// assert_eq!(format_distance(-0.001, 2), "0.00");
+/// ```
+pub fn format_distance(distance: f64, precision: usize) -> String {
+    if !distance.is_finite() {
+        return distance.to_string();
+    }
+
+    let shortest = distance.abs().to_string();
+    let (integer, fraction) = match shortest.find('.') {
+        Some(dot) => (&shortest[..dot], &shortest[dot + 1..]),
+        None => (shortest.as_str(), ""),
+    };
+
+    let mut digits = integer.bytes().collect::<Vec<_>>();
+    digits.extend(fraction.bytes().take(precision));
+    digits.resize(integer.len() + precision, b'0');
+
+    if matches!(fraction.as_bytes().get(precision), Some(&next) if next >= b'5') {
+        let mut carry = true;
+        for digit in digits.iter_mut().rev() {
+            if *digit == b'9' {
+                *digit = b'0';
+            } else {
+                *digit += 1;
+                carry = false;
+                break;
+            }
+        }
+
+        if carry {
+            digits.insert(0, b'1');
+        }
+    }
+
+    let point = digits.len() - precision;
+    let digits = String::from_utf8(digits).expect("digits are ascii");
+    let sign = if distance < 0.0 && digits.bytes().any(|digit| digit != b'0') {
+        "-"
+    } else {
+        ""
+    };
+
+    if precision == 0 {
+        format!("{}{}", sign, digits)
+    } else {
+        format!("{}{}.{}", sign, &digits[..point], &digits[point..])
+    }
+}
+
+/// Characters that are spelled out in identifiers instead of being dropped,
+/// so `C`, `C++` and `C#` don't end up with the same identifier.
+const SPELLED_OUT: &[(char, &str)] = &[('+', "plus"), ('#', "sharp"), ('*', "star")];
+
+/// Converts a language name into a kebab-case identifier usable as a
+/// stylesheet variable or class name. Letters are lowercased and stripped of
+/// diacritics, `+`, `#` and `*` are spelled out as `plus`, `sharp` and
+/// `star`, and everything else separates words, e.g. `Common Lisp` becomes
+/// `common-lisp` and `Objective-C++` becomes `objective-c-plus-plus`. Names
+/// without anything left become `language`. Leading digits and css keywords
+/// are kept, every output puts a prefix or suffix around the identifier.
+pub fn sanitize_identifier(name: &str) -> String {
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+
+    for c in collation_key(name).chars() {
+        if c.is_ascii_alphanumeric() {
+            word.push(c);
+            continue;
+        }
+
+        if !word.is_empty() {
+            words.push(word.clone());
+            word.clear();
+        }
+
+        if let Some((_, spelled)) = SPELLED_OUT.iter().find(|(special, _)| *special == c) {
+            words.push(spelled.to_string());
+        }
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    if words.is_empty() {
+        return "language".to_string();
+    }
+
+    words.join("-")
+}
+
+/// Identifiers for all `names` that are unique among them. Names that
+/// sanitize to an identifier that is already taken get a numeric suffix
+/// starting with `-2`, in the order of the original names.
+pub fn sanitize_identifiers<'a>(
+    names: impl IntoIterator<Item = &'a String>,
+) -> BTreeMap<String, String> {
+    let names = names.into_iter().collect::<BTreeSet<_>>();
+    let mut taken = BTreeSet::new();
+    let mut identifiers = BTreeMap::new();
+
+    for name in names {
+        let base = sanitize_identifier(name);
+        let mut identifier = base.clone();
+        let mut suffix = 2;
+
+        while taken.contains(&identifier) {
+            identifier = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+
+        taken.insert(identifier.clone());
+        identifiers.insert(name.clone(), identifier);
+    }
+
+    identifiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn special_characters_are_spelled_out() {
+        assert_eq!(sanitize_identifier("C"), "c");
+        assert_eq!(sanitize_identifier("C++"), "c-plus-plus");
+        assert_eq!(sanitize_identifier("C#"), "c-sharp");
+        assert_eq!(sanitize_identifier("F*"), "f-star");
+        assert_eq!(sanitize_identifier("F#"), "f-sharp");
+        assert_eq!(
+            sanitize_identifier("Objective-C++"),
+            "objective-c-plus-plus"
+        );
+    }
+
+    #[test]
+    fn words_are_separated_by_dashes() {
+        assert_eq!(sanitize_identifier("Common Lisp"), "common-lisp");
+        assert_eq!(sanitize_identifier("Ren'Py"), "ren-py");
+        assert_eq!(sanitize_identifier("  Vim  Script "), "vim-script");
+        assert_eq!(sanitize_identifier("Fantômas"), "fantomas");
+    }
+
+    #[test]
+    fn leading_digits_and_keywords_are_kept() {
+        assert_eq!(sanitize_identifier("1C Enterprise"), "1c-enterprise");
+        assert_eq!(sanitize_identifier("4D"), "4d");
+        assert_eq!(sanitize_identifier("Default"), "default");
+        assert_eq!(sanitize_identifier("Import"), "import");
+    }
+
+    #[test]
+    fn empty_identifiers_get_a_name() {
+        assert_eq!(sanitize_identifier(""), "language");
+        assert_eq!(sanitize_identifier("!?"), "language");
+    }
+
+    #[test]
+    fn collisions_get_a_suffix_in_name_order() {
+        let names = ["C++", "c++", "C plus plus", "C#", "C"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+
+        let identifiers = sanitize_identifiers(&names);
+
+        assert_eq!(identifiers["C plus plus"], "c-plus-plus");
+        assert_eq!(identifiers["C++"], "c-plus-plus-2");
+        assert_eq!(identifiers["c++"], "c-plus-plus-3");
+        assert_eq!(identifiers["C#"], "c-sharp");
+        assert_eq!(identifiers["C"], "c");
+    }
+
+    #[test]
+    fn suffixes_skip_taken_identifiers() {
+        let names = ["A 2", "A", "a"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+
+        let identifiers = sanitize_identifiers(&names);
+
+        assert_eq!(identifiers["A"], "a");
+        assert_eq!(identifiers["A 2"], "a-2");
+        assert_eq!(identifiers["a"], "a-3");
+    }
+}
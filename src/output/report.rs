@@ -0,0 +1,280 @@
+use std::io::{
+    self,
+    Write,
+};
+use std::str::FromStr;
+
+use serde_json::json;
+
+use crate::color::Color;
+use crate::diff::LanguageDiff;
+use crate::order::DistanceMetric;
+use crate::output::format_distance;
+use crate::report::{
+    ExtensionConflict,
+    GrayscaleEntry,
+    SharedColor,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(ReportFormat::Text),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(format!("unknown report format: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffFormat {
+    Text,
+    Html,
+}
+
+impl FromStr for DiffFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(DiffFormat::Text),
+            "html" => Ok(DiffFormat::Html),
+            _ => Err(format!("unknown diff format: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatrixFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for MatrixFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(MatrixFormat::Csv),
+            "json" => Ok(MatrixFormat::Json),
+            _ => Err(format!("unknown matrix format: {}", s)),
+        }
+    }
+}
+
+pub fn render_grayscale(entries: &[GrayscaleEntry], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Text => entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{}: {} saturation {:.3} lightness {:.3}, suggested {}",
+                    entry.language,
+                    entry.color.as_webcolor(),
+                    entry.saturation,
+                    entry.lightness,
+                    entry.suggestion.as_webcolor(),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        ReportFormat::Json => {
+            let entries = entries
+                .iter()
+                .map(|entry| {
+                    json!({
+                        "language": entry.language,
+                        "color": entry.color.as_webcolor(),
+                        "saturation": entry.saturation,
+                        "lightness": entry.lightness,
+                        "suggestion": entry.suggestion.as_webcolor(),
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            serde_json::to_string_pretty(&entries).expect("can not serialize report")
+        }
+    }
+}
+
+pub fn render_shared_colors(groups: &[SharedColor], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Text => groups
+            .iter()
+            .map(|group| {
+                format!(
+                    "{} ({}): {}",
+                    group.color.as_webcolor(),
+                    group.languages.len(),
+                    group.languages.join(", "),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        ReportFormat::Json => {
+            let groups = groups
+                .iter()
+                .map(|group| {
+                    json!({
+                        "color": group.color.as_webcolor(),
+                        "count": group.languages.len(),
+                        "languages": group.languages,
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            serde_json::to_string_pretty(&groups).expect("can not serialize report")
+        }
+    }
+}
+
+/// Colorless claimants are shown as `none` in the text format and as `null`
+/// in the json format.
+pub fn render_extension_conflicts(conflicts: &[ExtensionConflict], format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Text => conflicts
+            .iter()
+            .map(|conflict| {
+                let languages = conflict
+                    .languages
+                    .iter()
+                    .map(|(name, color)| {
+                        let color = color
+                            .as_ref()
+                            .map(Color::as_webcolor)
+                            .unwrap_or_else(|| "none".to_string());
+
+                        format!("{} {}", name, color)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                format!(
+                    "{} ({}): {}",
+                    conflict.extension,
+                    conflict.languages.len(),
+                    languages
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        ReportFormat::Json => {
+            let conflicts = conflicts
+                .iter()
+                .map(|conflict| {
+                    let languages = conflict
+                        .languages
+                        .iter()
+                        .map(|(name, color)| {
+                            json!({
+                                "language": name,
+                                "color": color.as_ref().map(Color::as_webcolor),
+                            })
+                        })
+                        .collect::<Vec<_>>();
+
+                    json!({
+                        "extension": conflict.extension,
+                        "count": conflict.languages.len(),
+                        "languages": languages,
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            serde_json::to_string_pretty(&conflicts).expect("can not serialize report")
+        }
+    }
+}
+
+/// One line per difference: `+` for added, `-` for removed and `~` for
+/// changed languages with the distance of the change.
+pub fn render_diff_text(diff: &LanguageDiff) -> String {
+    let added = diff
+        .added
+        .iter()
+        .map(|(name, color)| format!("+ {} {}", name, color.as_webcolor()));
+    let removed = diff
+        .removed
+        .iter()
+        .map(|(name, color)| format!("- {} {}", name, color.as_webcolor()));
+    let changed = diff.changed.iter().map(|change| {
+        format!(
+            "~ {} {} -> {} (delta e {})",
+            change.language,
+            change.old.as_webcolor(),
+            change.new.as_webcolor(),
+            format_distance(change.distance, 2)
+        )
+    });
+
+    added
+        .chain(removed)
+        .chain(changed)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Writes the distances between all `languages` as a square matrix. The csv
+/// has the language names as header row and first column and is written row
+/// by row. The json is an object with the `labels` and the `data` rows.
+pub fn write_matrix(
+    out: &mut dyn Write,
+    languages: &[(String, Color)],
+    metric: DistanceMetric,
+    format: MatrixFormat,
+) -> io::Result<()> {
+    let row = |color: &Color| {
+        languages
+            .iter()
+            .map(|(_, other)| metric.distance(color, other))
+            .collect::<Vec<_>>()
+    };
+
+    match format {
+        MatrixFormat::Csv => {
+            let header = languages
+                .iter()
+                .map(|(name, _)| csv_field(name))
+                .collect::<Vec<_>>()
+                .join(",");
+            writeln!(out, ",{}", header)?;
+
+            for (name, color) in languages {
+                let cells = row(color)
+                    .iter()
+                    .map(f64::to_string)
+                    .collect::<Vec<_>>()
+                    .join(",");
+                writeln!(out, "{},{}", csv_field(name), cells)?;
+            }
+
+            Ok(())
+        }
+        MatrixFormat::Json => {
+            let matrix = json!({
+                "labels": languages.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+                "data": languages.iter().map(|(_, color)| row(color)).collect::<Vec<_>>(),
+            });
+
+            serde_json::to_writer(&mut *out, &matrix)?;
+            writeln!(out)
+        }
+    }
+}
+
+/// Quotes a csv field if it contains a separator, quote or line break.
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
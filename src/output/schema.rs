@@ -0,0 +1,90 @@
+use std::str::FromStr;
+
+use schemars::JsonSchema;
+use serde_json::Value;
+
+use crate::output::json::{
+    ClassMapOutput,
+    LanguagesOutput,
+};
+use crate::output::lookup::FullRecord;
+use crate::output::SCHEMA_VERSION;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaFormat {
+    JsonSchema,
+}
+
+impl FromStr for SchemaFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json-schema" => Ok(SchemaFormat::JsonSchema),
+            _ => Err(format!("unknown schema format: {}", s)),
+        }
+    }
+}
+
+/// Any of the json outputs, only used to derive the schema.
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)]
+enum Output {
+    Languages(LanguagesOutput),
+    ClassMap(ClassMapOutput),
+    Full(FullRecord),
+}
+
+/// JSON Schema (draft 2020-12) of the `json` and `class-map` outputs and of
+/// the `--full` record, derived from the types the renderers serialize.
+pub fn json_schema() -> Value {
+    let mut schema = schemars::schema_for!(Output);
+    schema.insert("title".to_string(), "language_colors".into());
+    schema.insert(
+        "description".to_string(),
+        format!(
+            "Outputs of language_colors, schema version {}",
+            SCHEMA_VERSION
+        )
+        .into(),
+    );
+
+    schema.to_value()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_is_valid_draft_2020_12() {
+        let schema = json_schema();
+
+        assert_eq!(
+            schema["$schema"],
+            "https://json-schema.org/draft/2020-12/schema"
+        );
+        jsonschema::meta::validate(&schema).expect("invalid json schema");
+    }
+
+    #[test]
+    fn schema_version_is_pinned() {
+        let schema = json_schema();
+        let validator = jsonschema::validator_for(&schema).unwrap();
+
+        let record = serde_json::json!({
+            "schema_version": SCHEMA_VERSION - 1,
+            "metadata": [],
+            "languages": [],
+        });
+        assert!(!validator.is_valid(&record));
+
+        let record = serde_json::json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": [],
+            "languages": [],
+        });
+        assert!(validator.is_valid(&record));
+    }
+}
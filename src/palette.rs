@@ -0,0 +1,56 @@
+use std::collections::BTreeMap;
+
+use crate::color::Color;
+
+/// Smallest WCAG contrast ratio a palette color needs against both black
+/// and white, the WCAG minimum for graphical objects.
+pub const MIN_CONTRAST: f64 = 3.0;
+
+/// Greedily picks up to `n` language colors that are as distinguishable as
+/// possible. Only colors with a contrast ratio of at least `MIN_CONTRAST`
+/// against black and against white are considered. The first pick is the
+/// color with the highest chroma, every further pick is the color with the
+/// largest CIEDE2000 difference to its closest already picked color. Ties go
+/// to the first language by name.
+pub fn generate_accessible_palette(
+    language_colors: &BTreeMap<String, Color>,
+    n: usize,
+) -> Vec<(String, Color)> {
+    let mut candidates = language_colors
+        .iter()
+        .filter(|(_, color)| {
+            color.contrast_ratio(&Color::BLACK) >= MIN_CONTRAST
+                && color.contrast_ratio(&Color::WHITE) >= MIN_CONTRAST
+        })
+        .collect::<Vec<_>>();
+
+    let mut palette: Vec<(String, Color)> = Vec::new();
+
+    while palette.len() < n && !candidates.is_empty() {
+        let score = |color: &Color| {
+            if palette.is_empty() {
+                color.chroma()
+            } else {
+                palette
+                    .iter()
+                    .map(|(_, picked)| color.delta_e_2000(picked))
+                    .fold(f64::INFINITY, f64::min)
+            }
+        };
+
+        let mut best = 0;
+        let mut best_score = score(candidates[0].1);
+        for (index, (_, color)) in candidates.iter().enumerate().skip(1) {
+            let score = score(color);
+            if score > best_score {
+                best = index;
+                best_score = score;
+            }
+        }
+
+        let (name, color) = candidates.remove(best);
+        palette.push((name.clone(), color.clone()));
+    }
+
+    palette
+}
@@ -0,0 +1,79 @@
+use schemars::JsonSchema;
+
+/// Every option that changes which languages are rendered and how they are
+/// ordered, written to the output metadata and read by `--params-file` to
+/// replay a run. The field names are part of the json outputs and stay
+/// stable, missing fields get the defaults of the command line.
+///
+/// ```
+/// use language_colors::parameters::Parameters;
+///
+/// let parameters = Parameters {
+///     order: "nearest".to_string(),
+///     start_language: Some("Rust".to_string()),
+///     seed: Some(7),
+///     sample: Some(50),
+///     separate: true,
+///     ..Parameters::default()
+/// };
+///
+/// let json = serde_json::to_string(&parameters).unwrap();
+/// assert_eq!(serde_json::from_str::<Parameters>(&json).unwrap(), parameters);
+///
+/// let defaults = serde_json::from_str::<Parameters>("{}").unwrap();
+/// assert_eq!(defaults, Parameters::default());
+/// assert_eq!(defaults.min_distance, 15.0);
+///
+/// assert!(serde_json::from_str::<Parameters>(r#"{"colour": 1}"#).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Parameters {
+    pub order: String,
+    pub order_file: Option<String>,
+    pub order_file_exclusive: bool,
+    pub metric: String,
+    pub start_language: Option<String>,
+    pub clusters: usize,
+    /// Seed of the cluster centroids and of the sample.
+    pub seed: Option<u64>,
+    pub average_space: String,
+    pub band_threshold: f64,
+    pub sort_groups: String,
+    /// Size of the sample.
+    pub sample: Option<usize>,
+    pub separate: bool,
+    pub min_distance: f64,
+    pub profile: String,
+    pub types: Vec<String>,
+    pub default_color: Option<String>,
+    pub offset: usize,
+    pub limit: Option<usize>,
+    pub reverse: bool,
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Self {
+            order: "name".to_string(),
+            order_file: None,
+            order_file_exclusive: false,
+            metric: "euclidean".to_string(),
+            start_language: None,
+            clusters: 8,
+            seed: None,
+            average_space: "linear".to_string(),
+            band_threshold: 0.0,
+            sort_groups: "size".to_string(),
+            sample: None,
+            separate: false,
+            min_distance: 15.0,
+            profile: "all".to_string(),
+            types: Vec::new(),
+            default_color: None,
+            offset: 0,
+            limit: None,
+            reverse: false,
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::cell::{
+    Cell,
+    RefCell,
+};
+use std::io::{
+    stderr,
+    IsTerminal,
+};
+
+use indicatif::{
+    ProgressBar,
+    ProgressStyle,
+};
+
+/// Receives progress updates from long running phases like fetching the
+/// languages or computing the nearest color ordering.
+pub trait Progress {
+    /// Starts a new phase with `total` steps, `None` if the number of steps
+    /// is unknown.
+    fn start(&self, phase: &str, total: Option<u64>);
+
+    fn advance(&self, steps: u64);
+
+    fn finish(&self);
+
+    /// Counts `pairs` of colors compared by the ordering, for `--timings`.
+    fn compared(&self, _pairs: u64) {}
+}
+
+/// Reporter that ignores all updates.
+pub struct NoProgress;
+
+impl Progress for NoProgress {
+    fn start(&self, _phase: &str, _total: Option<u64>) {}
+
+    fn advance(&self, _steps: u64) {}
+
+    fn finish(&self) {}
+}
+
+/// Progress bars on stderr: a bar when the number of steps is known and a
+/// spinner otherwise.
+#[derive(Default)]
+pub struct BarProgress {
+    bar: RefCell<Option<ProgressBar>>,
+}
+
+impl Progress for BarProgress {
+    fn start(&self, phase: &str, total: Option<u64>) {
+        let bar = match total {
+            Some(total) => {
+                let bar = ProgressBar::new(total);
+                bar.set_style(
+                    ProgressStyle::with_template("{msg} [{bar:40}] {pos}/{len}")
+                        .expect("progress bar template is valid")
+                        .progress_chars("=> "),
+                );
+                bar
+            }
+            None => ProgressBar::new_spinner(),
+        };
+
+        bar.set_message(phase.to_string());
+        *self.bar.borrow_mut() = Some(bar);
+    }
+
+    fn advance(&self, steps: u64) {
+        if let Some(ref bar) = *self.bar.borrow() {
+            bar.inc(steps);
+        }
+    }
+
+    fn finish(&self) {
+        if let Some(bar) = self.bar.borrow_mut().take() {
+            bar.finish_and_clear();
+        }
+    }
+}
+
+/// `tracing` events for every 10% of progress, for when stderr is not a
+/// terminal and redrawing a bar would flood the log.
+#[derive(Default)]
+pub struct LogProgress {
+    phase: RefCell<String>,
+    total: Cell<Option<u64>>,
+    current: Cell<u64>,
+    logged_percent: Cell<u64>,
+}
+
+impl Progress for LogProgress {
+    fn start(&self, phase: &str, total: Option<u64>) {
+        *self.phase.borrow_mut() = phase.to_string();
+        self.total.set(total);
+        self.current.set(0);
+        self.logged_percent.set(0);
+    }
+
+    fn advance(&self, steps: u64) {
+        self.current.set(self.current.get() + steps);
+
+        let total = match self.total.get() {
+            Some(total) if total > 0 => total,
+            _ => return,
+        };
+
+        let percent = (self.current.get() * 100 / total).min(100) / 10 * 10;
+        if percent > self.logged_percent.get() {
+            self.logged_percent.set(percent);
+            tracing::info!(phase = %self.phase.borrow(), percent, "progress");
+        }
+    }
+
+    fn finish(&self) {
+        if self.total.get().is_none() {
+            tracing::info!(phase = %self.phase.borrow(), steps = self.current.get(), "done");
+        }
+    }
+}
+
+/// Forwards all updates to `inner` and counts the compared color pairs.
+pub struct CountingProgress<'a> {
+    inner: &'a dyn Progress,
+    pairs: Cell<u64>,
+}
+
+impl<'a> CountingProgress<'a> {
+    pub fn new(inner: &'a dyn Progress) -> Self {
+        Self {
+            inner,
+            pairs: Cell::new(0),
+        }
+    }
+
+    pub fn pairs(&self) -> u64 {
+        self.pairs.get()
+    }
+}
+
+impl Progress for CountingProgress<'_> {
+    fn start(&self, phase: &str, total: Option<u64>) {
+        self.inner.start(phase, total);
+    }
+
+    fn advance(&self, steps: u64) {
+        self.inner.advance(steps);
+    }
+
+    fn finish(&self) {
+        self.inner.finish();
+    }
+
+    fn compared(&self, pairs: u64) {
+        self.pairs.set(self.pairs.get() + pairs);
+        self.inner.compared(pairs);
+    }
+}
+
+/// Reporter for the current environment: nothing when `quiet` is set,
+/// progress bars when stderr is a terminal and log lines otherwise.
+pub fn reporter(quiet: bool) -> Box<dyn Progress> {
+    if quiet {
+        Box::new(NoProgress)
+    } else if stderr().is_terminal() {
+        Box::new(BarProgress::default())
+    } else {
+        Box::new(LogProgress::default())
+    }
+}
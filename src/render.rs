@@ -0,0 +1,741 @@
+use std::cell::OnceCell;
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+    HashMap,
+};
+
+use crate::cluster;
+use crate::color::{
+    AverageSpace,
+    Color,
+    ColorParseError,
+};
+use crate::language::{
+    IdIndex,
+    LanguageInfo,
+    LanguageType,
+};
+use crate::merge;
+use crate::order::{
+    self,
+    ChainEntry,
+    DistanceMetric,
+    Order,
+    Slice,
+};
+use crate::output::csv::CsvOptions;
+use crate::output::html::{
+    self,
+    PageOptions,
+    Section,
+    TypeGroup,
+};
+use crate::output::markdown::{
+    MarkdownOptions,
+    Summary,
+};
+use crate::output::{
+    self,
+    Metadata,
+    OutputFormat,
+};
+use crate::progress::{
+    NoProgress,
+    Progress,
+};
+use crate::report::{
+    self,
+    GroupOrder,
+};
+use crate::stats;
+
+/// Smallest Jaro-Winkler similarity of a match of
+/// `LanguageColors::search_by_name`.
+pub const SEARCH_THRESHOLD: f64 = 0.7;
+
+/// Parsed languages together with their colors.
+#[derive(Debug, Default)]
+pub struct LanguageColors {
+    pub languages: BTreeMap<String, LanguageInfo>,
+    /// Colors of the languages that have a valid color or got the default
+    /// color.
+    pub colors: BTreeMap<String, Color>,
+    /// Languages without a color of their own that got the default color.
+    pub defaults: Vec<String>,
+    /// Languages without a color.
+    pub colorless: Vec<String>,
+    /// Names of the languages by their `merge::normalize_name`.
+    pub normalized_names: HashMap<String, String>,
+    /// Original colors of the languages whose color was changed by
+    /// `separate::separate`.
+    pub adjusted: BTreeMap<String, Color>,
+    /// Names of the languages by their `language_id`, see `by_id`.
+    pub ids: IdIndex,
+}
+
+impl LanguageColors {
+    /// Parses the colors of `languages`. Languages without a color get
+    /// `default_color` if there is one, languages with a color that can not
+    /// be parsed are left out and returned with the parse error.
+    pub fn new(
+        languages: BTreeMap<String, LanguageInfo>,
+        default_color: Option<Color>,
+    ) -> (Self, Vec<(String, ColorParseError)>) {
+        let colorless = languages
+            .iter()
+            .filter(|(_, info)| info.color.is_none())
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+
+        let defaults = match default_color {
+            Some(_) => colorless.clone(),
+            None => Vec::new(),
+        };
+
+        let mut normalized_names = HashMap::new();
+        for name in languages.keys() {
+            normalized_names
+                .entry(merge::normalize_name(name))
+                .or_insert_with(|| name.clone());
+        }
+
+        let mut invalid_colors = Vec::new();
+        let colors = languages
+            .iter()
+            .filter_map(|(name, info)| {
+                let color = match info.color {
+                    Some(ref color) => match color.parse() {
+                        Ok(color) => color,
+                        Err(err) => {
+                            invalid_colors.push((name.clone(), err));
+                            return None;
+                        }
+                    },
+                    None => default_color.clone()?,
+                };
+
+                Some((name.clone(), color))
+            })
+            .collect();
+
+        let language_colors = Self {
+            ids: IdIndex::new(&languages),
+            languages,
+            colors,
+            defaults,
+            colorless,
+            normalized_names,
+            adjusted: BTreeMap::new(),
+        };
+
+        (language_colors, invalid_colors)
+    }
+
+    /// Color of the language `name` ignoring case and whitespace differences,
+    /// e.g. `javascript` finds `JavaScript`.
+    ///
+    /// ```
+    /// use language_colors::render::LanguageColors;
+    ///
+    /// let languages = LanguageColors::from_yaml(
+    ///     r##"
+    /// JavaScript:
+    ///   type: programming
+    ///   color: "#f1e05a"
+    ///   ace_mode: javascript
+    ///   language_id: 183
+    /// "##,
+    /// )
+    /// .unwrap();
+    ///
+    /// let color = languages.color_for_language_case_insensitive("javascript");
+    /// assert_eq!(color.unwrap().as_webcolor(), "#F1E05A");
+    /// assert!(languages.color_for_language_case_insensitive("java").is_none());
+    /// ```
+    pub fn color_for_language_case_insensitive(&self, name: &str) -> Option<Color> {
+        self.colors
+            .get(name)
+            .or_else(|| {
+                self.normalized_names
+                    .get(&merge::normalize_name(name))
+                    .and_then(|name| self.colors.get(name))
+            })
+            .cloned()
+    }
+
+    /// Like `color_for_language_case_insensitive` but returns `fallback` for
+    /// unknown or colorless languages.
+    pub fn color_for_language_or_default(&self, name: &str, fallback: Color) -> Color {
+        self.color_for_language_case_insensitive(name)
+            .unwrap_or(fallback)
+    }
+
+    /// Languages whose name is similar to `query`, ignoring case, with their
+    /// Jaro-Winkler similarity above `SEARCH_THRESHOLD`. The most similar
+    /// language comes first, equally similar ones are sorted by name.
+    ///
+    /// ```
+    /// use language_colors::render::LanguageColors;
+    ///
+    /// let languages = LanguageColors::from_yaml(
+    ///     r##"
+    /// JavaScript:
+    ///   type: programming
+    ///   color: "#f1e05a"
+    ///   ace_mode: javascript
+    ///   language_id: 183
+    /// Java:
+    ///   type: programming
+    ///   color: "#b07219"
+    ///   ace_mode: java
+    ///   language_id: 181
+    /// Rust:
+    ///   type: programming
+    ///   color: "#dea584"
+    ///   ace_mode: rust
+    ///   language_id: 327
+    /// "##,
+    /// )
+    /// .unwrap();
+    ///
+    /// let matches = languages.search_by_name("jav");
+    /// let names = matches.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+    /// assert_eq!(names, ["Java", "JavaScript"]);
+    /// assert!(matches[0].1 > matches[1].1);
+    ///
+    /// assert_eq!(languages.search_by_name("rust")[0], ("Rust", 1.0));
+    /// assert!(languages.search_by_name("haskell").is_empty());
+    /// ```
+    pub fn search_by_name(&self, query: &str) -> Vec<(&str, f64)> {
+        let query = query.to_lowercase();
+
+        let mut matches = self
+            .languages
+            .keys()
+            .map(|name| {
+                (
+                    name.as_str(),
+                    strsim::jaro_winkler(&query, &name.to_lowercase()),
+                )
+            })
+            .filter(|(_, similarity)| *similarity > SEARCH_THRESHOLD)
+            .collect::<Vec<_>>();
+
+        matches.sort_by(|(a, a_similarity), (b, b_similarity)| {
+            b_similarity
+                .partial_cmp(a_similarity)
+                .expect("similarities are not nan")
+                .then_with(|| a.cmp(b))
+        });
+
+        matches
+    }
+
+    /// Name of the language with the linguist `language_id` `id`. A
+    /// language whose id is already taken by one earlier in name order is
+    /// not found by it, see `IdIndex::duplicates`.
+    ///
+    /// ```
+    /// use language_colors::render::LanguageColors;
+    ///
+    /// let languages = LanguageColors::from_yaml(
+    ///     r##"
+    /// Rust:
+    ///   type: programming
+    ///   color: "#dea584"
+    ///   ace_mode: rust
+    ///   language_id: 327
+    /// Go:
+    ///   type: programming
+    ///   color: "#00ADD8"
+    ///   ace_mode: golang
+    ///   language_id: 132
+    /// "##,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(languages.by_id(327), Some("Rust"));
+    /// assert_eq!(languages.by_id(1), None);
+    /// assert!(languages.ids.duplicates.is_empty());
+    /// ```
+    pub fn by_id(&self, id: i64) -> Option<&str> {
+        self.ids.by_id(id)
+    }
+
+    /// The colored languages in `Order::Nearest` with the distance of every
+    /// step, starting at `start_language` or the first language by name,
+    /// see `order::nearest_chain`.
+    ///
+    /// ```
+    /// use language_colors::order::DistanceMetric;
+    /// use language_colors::render::LanguageColors;
+    ///
+    /// let languages = LanguageColors::from_yaml(
+    ///     r##"
+    /// Black:
+    ///   type: programming
+    ///   color: "#000000"
+    ///   ace_mode: text
+    ///   language_id: 1
+    /// Gray:
+    ///   type: programming
+    ///   color: "#0a0a0a"
+    ///   ace_mode: text
+    ///   language_id: 2
+    /// White:
+    ///   type: programming
+    ///   color: "#ffffff"
+    ///   ace_mode: text
+    ///   language_id: 3
+    /// "##,
+    /// )
+    /// .unwrap();
+    ///
+    /// let chain = languages.nearest_chain(Some("White"), DistanceMetric::Euclidean);
+    /// let names = chain.iter().map(|entry| entry.language.as_str()).collect::<Vec<_>>();
+    /// assert_eq!(names, ["White", "Gray", "Black"]);
+    /// assert_eq!(chain[0].distance, None);
+    /// assert_eq!(chain[2].distance, Some(300f64.sqrt()));
+    /// ```
+    pub fn nearest_chain(
+        &self,
+        start_language: Option<&str>,
+        metric: DistanceMetric,
+    ) -> Vec<ChainEntry> {
+        order::nearest_chain(&self.colors, metric, start_language)
+    }
+
+    /// The languages for which `keep` returns true with their colors.
+    pub fn retain(&self, keep: impl Fn(&str, &LanguageInfo) -> bool) -> LanguageColors {
+        let languages = self
+            .languages
+            .iter()
+            .filter(|(name, info)| keep(name, info))
+            .map(|(name, info)| (name.clone(), info.clone()))
+            .collect::<BTreeMap<_, _>>();
+        let kept = |name: &String| languages.contains_key(name);
+
+        LanguageColors {
+            colors: self
+                .colors
+                .iter()
+                .filter(|(name, _)| kept(name))
+                .map(|(name, color)| (name.clone(), color.clone()))
+                .collect(),
+            defaults: self
+                .defaults
+                .iter()
+                .filter(|name| kept(name))
+                .cloned()
+                .collect(),
+            colorless: self
+                .colorless
+                .iter()
+                .filter(|name| kept(name))
+                .cloned()
+                .collect(),
+            normalized_names: self
+                .normalized_names
+                .iter()
+                .filter(|(_, name)| kept(name))
+                .map(|(normalized, name)| (normalized.clone(), name.clone()))
+                .collect(),
+            adjusted: self
+                .adjusted
+                .iter()
+                .filter(|(name, _)| kept(name))
+                .map(|(name, color)| (name.clone(), color.clone()))
+                .collect(),
+            ids: IdIndex::new(&languages),
+            languages,
+        }
+    }
+
+    /// Parses a languages file like linguist's `languages.yml`. Languages
+    /// with an invalid color are left out.
+    pub fn from_yaml(yaml: &str) -> Result<Self, serde_yaml::Error> {
+        let languages = serde_yaml::from_str(yaml)?;
+
+        Ok(Self::new(languages, None).0)
+    }
+}
+
+/// Settings of all output formats, the defaults are the defaults of the
+/// command line.
+#[derive(Debug)]
+pub struct RenderOptions {
+    pub order: Order,
+    pub metric: DistanceMetric,
+    /// Language the nearest color ordering starts with.
+    pub start_language: Option<String>,
+    pub slice: Slice,
+    /// Sections of the html output.
+    pub sections: Vec<Section>,
+    /// Number of clusters in the clusters section.
+    pub clusters: usize,
+    /// Seed of the cluster centroids, evenly spaced centroids if `None`.
+    pub seed: Option<u64>,
+    /// Space of the cluster centroids and the average colors per type.
+    pub average_space: AverageSpace,
+    pub sort_groups: GroupOrder,
+    /// See `order::band`.
+    pub band_threshold: f64,
+    pub metadata: Metadata,
+    pub page: PageOptions,
+    pub markdown: MarkdownOptions,
+    pub csv: CsvOptions,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            order: Order::Name,
+            metric: DistanceMetric::Euclidean,
+            start_language: None,
+            slice: Slice::default(),
+            sections: vec![Section::Legend, Section::Name, Section::Nearest],
+            clusters: 8,
+            seed: None,
+            average_space: AverageSpace::default(),
+            sort_groups: GroupOrder::Size,
+            band_threshold: 0.0,
+            metadata: Metadata::default(),
+            page: PageOptions::default(),
+            markdown: MarkdownOptions::default(),
+            csv: CsvOptions::default(),
+        }
+    }
+}
+
+/// Renders `languages` in `format`. The command line renders through the
+/// same code, so the output is the same as with the matching flags except
+/// for the final newline the command line writes after it.
+///
+/// ```
+/// use language_colors::output::OutputFormat;
+/// use language_colors::render::{
+///     render,
+///     LanguageColors,
+///     RenderOptions,
+/// };
+///
+/// let languages = LanguageColors::from_yaml(
+///     r##"
+/// Rust:
+///   type: programming
+///   color: "#dea584"
+///   ace_mode: rust
+///   language_id: 327
+/// "##,
+/// )
+/// .unwrap();
+///
+/// let json = render(&languages, OutputFormat::Json, &RenderOptions::default());
+/// assert!(String::from_utf8(json).unwrap().contains("\"#DEA584\""));
+///
+/// let less = render(&languages, OutputFormat::Less, &RenderOptions::default());
+/// assert!(String::from_utf8(less)
+///     .unwrap()
+///     .contains("@rust-color: #DEA584;"));
+/// ```
+pub fn render(
+    languages: &LanguageColors,
+    format: OutputFormat,
+    options: &RenderOptions,
+) -> Vec<u8> {
+    Renderer::new(languages, options, &NoProgress).render(format)
+}
+
+/// File name stem of the file with all languages of `split_by_type`.
+pub const SPLIT_ALL: &str = "all";
+
+/// Splits the languages for `--split-by type`: all languages under
+/// `SPLIT_ALL`, followed by the languages of every type under the lowercase
+/// type name. Types without a colored language are left out.
+///
+/// ```
+/// use std::collections::BTreeSet;
+///
+/// use language_colors::render::{
+///     split_by_type,
+///     LanguageColors,
+/// };
+///
+/// let languages = LanguageColors::from_yaml(
+///     r##"
+/// Rust:
+///   type: programming
+///   color: "#dea584"
+///   ace_mode: rust
+///   language_id: 327
+/// Go:
+///   type: programming
+///   color: "#00ADD8"
+///   ace_mode: golang
+///   language_id: 132
+/// HTML:
+///   type: markup
+///   color: "#e34c26"
+///   ace_mode: html
+///   language_id: 146
+/// JSON:
+///   type: data
+///   ace_mode: json
+///   language_id: 174
+/// "##,
+/// )
+/// .unwrap();
+///
+/// let split = split_by_type(&languages);
+/// let stems = split.iter().map(|(stem, _)| stem.as_str()).collect::<Vec<_>>();
+/// assert_eq!(stems, ["all", "programming", "markup"]);
+///
+/// let union = split[1..]
+///     .iter()
+///     .flat_map(|(_, part)| part.colors.keys())
+///     .collect::<BTreeSet<_>>();
+/// assert_eq!(union, split[0].1.colors.keys().collect::<BTreeSet<_>>());
+/// ```
+pub fn split_by_type(languages: &LanguageColors) -> Vec<(String, LanguageColors)> {
+    let types = languages
+        .colors
+        .keys()
+        .map(|name| &languages.languages[name].language_type)
+        .collect::<BTreeSet<_>>();
+
+    let mut split = vec![(SPLIT_ALL.to_string(), languages.retain(|_, _| true))];
+    for language_type in types {
+        split.push((
+            language_type.as_str().to_lowercase(),
+            languages.retain(|_, info| info.language_type == *language_type),
+        ));
+    }
+
+    split
+}
+
+/// Renders several formats from the same languages, the ordering is only
+/// computed once for all formats.
+pub struct Renderer<'a> {
+    languages: &'a LanguageColors,
+    options: &'a RenderOptions,
+    progress: &'a dyn Progress,
+    ordered: OnceCell<Vec<(String, Color)>>,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(
+        languages: &'a LanguageColors,
+        options: &'a RenderOptions,
+        progress: &'a dyn Progress,
+    ) -> Self {
+        Self {
+            languages,
+            options,
+            progress,
+            ordered: OnceCell::new(),
+        }
+    }
+
+    pub fn render(&self, format: OutputFormat) -> Vec<u8> {
+        let _span = tracing::info_span!("render", ?format).entered();
+
+        let output = match format {
+            OutputFormat::Html => self.render_html(),
+            OutputFormat::ClassMap => output::json::render_class_map(
+                &self.options.slice.apply(self.by_name()),
+                &output::sanitize_identifiers(self.languages.colors.keys()),
+                &self.languages.defaults,
+                &self.options.metadata,
+            ),
+            OutputFormat::Json => output::json::render(
+                &order::band(
+                    order::chain(
+                        &self.options.slice.apply(self.ordered().to_vec()),
+                        self.options.metric,
+                    ),
+                    self.options.band_threshold,
+                ),
+                &self.languages.defaults,
+                &self.languages.adjusted,
+                &self.options.metadata,
+            ),
+            OutputFormat::Csv => output::csv::render(
+                &order::chain(
+                    &self.options.slice.apply(self.ordered().to_vec()),
+                    self.options.metric,
+                ),
+                &self.options.metadata,
+                &self.options.csv,
+            ),
+            OutputFormat::Less => output::less::render(
+                &self.options.slice.apply(self.ordered().to_vec()),
+                &output::sanitize_identifiers(self.languages.colors.keys()),
+                &self.options.metadata,
+            ),
+            OutputFormat::Markdown => output::markdown::render(
+                &self.options.slice.apply(self.ordered().to_vec()),
+                &self.languages.defaults,
+                &Summary {
+                    languages: self.languages.languages.len(),
+                    colored: self.languages.colors.len(),
+                    by_type: stats::count_by_type(&self.languages.languages),
+                },
+                &self.options.metadata,
+                &self.options.markdown,
+            ),
+        };
+
+        output.into_bytes()
+    }
+
+    /// Languages in `RenderOptions::order`. Computed on first use, calling
+    /// it before `render` only moves the work.
+    pub fn ordered(&self) -> &[(String, Color)] {
+        self.ordered.get_or_init(|| {
+            order::sort_languages_with_progress(
+                &self.languages.colors,
+                self.options.order.clone(),
+                self.options.metric,
+                self.options.start_language.as_deref(),
+                self.progress,
+            )
+        })
+    }
+
+    fn by_name(&self) -> Vec<(String, Color)> {
+        order::sort_languages(
+            &self.languages.colors,
+            Order::Name,
+            self.options.metric,
+            None,
+        )
+    }
+
+    fn render_html(&self) -> String {
+        let LanguageColors {
+            ref languages,
+            ref colors,
+            ref defaults,
+            ref colorless,
+            ref adjusted,
+            ..
+        } = *self.languages;
+        let options = self.options;
+        let sections = &options.sections;
+
+        let nearest_colors = if sections.contains(&Section::Nearest) {
+            order::sort_languages_with_progress(
+                colors,
+                Order::Nearest,
+                options.metric,
+                options.start_language.as_deref(),
+                self.progress,
+            )
+        } else {
+            Vec::new()
+        };
+
+        let clusters = if sections.contains(&Section::Clusters) {
+            let clusters = match options.seed {
+                Some(seed) => cluster::cluster_by_color_seeded(
+                    colors,
+                    options.clusters,
+                    seed,
+                    options.average_space,
+                ),
+                None => cluster::cluster_by_color(colors, options.clusters, options.average_space),
+            };
+
+            clusters
+                .into_iter()
+                .map(|cluster| {
+                    cluster
+                        .into_iter()
+                        .map(|name| {
+                            let color = colors[&name].clone();
+                            (name, color)
+                        })
+                        .collect()
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let types = stats::type_distribution(languages, colors, options.average_space);
+
+        let by_type = if sections.contains(&Section::Types) {
+            type_groups(languages, colors, options.metric)
+        } else {
+            Vec::new()
+        };
+
+        let by_name = options.slice.apply(self.by_name());
+        let ordered = match options.order {
+            Order::Name => by_name.clone(),
+            _ if !sections.contains(&Section::Name) => Vec::new(),
+            _ => options.slice.apply(self.ordered().to_vec()),
+        };
+        let nearest_colors = order::band(
+            order::chain(&options.slice.apply(nearest_colors), options.metric),
+            options.band_threshold,
+        );
+
+        let adjusted = adjusted
+            .iter()
+            .map(|(name, color)| (name.clone(), color.clone()))
+            .collect::<Vec<_>>();
+
+        let groups = if sections.contains(&Section::Groups) {
+            report::shared_colors(&by_name, options.sort_groups)
+        } else {
+            Vec::new()
+        };
+
+        html::render(
+            sections,
+            &html::Languages {
+                by_name: &by_name,
+                ordered: &ordered,
+                order: options.order.clone(),
+                defaults,
+                nearest: &nearest_colors,
+                clusters: &clusters,
+                groups: &groups,
+                colorless,
+                types: &types,
+                by_type: &by_type,
+                adjusted: &adjusted,
+            },
+            &options.metadata,
+            &options.page,
+        )
+    }
+}
+
+/// Colored languages grouped by their type, each group ordered by name and
+/// by nearest color.
+fn type_groups(
+    languages: &BTreeMap<String, LanguageInfo>,
+    languages_colors: &BTreeMap<String, Color>,
+    metric: DistanceMetric,
+) -> Vec<TypeGroup> {
+    let mut by_type: BTreeMap<LanguageType, BTreeMap<String, Color>> = BTreeMap::new();
+    for (name, color) in languages_colors {
+        by_type
+            .entry(languages[name].language_type.clone())
+            .or_default()
+            .insert(name.clone(), color.clone());
+    }
+
+    by_type
+        .into_iter()
+        .map(|(language_type, languages_colors)| TypeGroup {
+            language_type,
+            by_name: order::sort_languages(&languages_colors, Order::Name, metric, None),
+            nearest: order::sort_languages(&languages_colors, Order::Nearest, metric, None),
+        })
+        .collect()
+}
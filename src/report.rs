@@ -0,0 +1,327 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use crate::color::{
+    Color,
+    PerceptualColor,
+};
+use crate::language::ExtensionIndex;
+use crate::order::collation_key;
+
+/// Language with a color that is close to gray.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrayscaleEntry {
+    pub language: String,
+    pub color: Color,
+    pub saturation: f64,
+    pub lightness: f64,
+    /// Same hue and lightness with the saturation raised to the floor. Only
+    /// a suggestion, nothing else uses it.
+    pub suggestion: Color,
+}
+
+/// Languages whose colors have an HSL saturation of at most
+/// `max_saturation`, in name order.
+pub fn grayscale(
+    languages_colors: &BTreeMap<String, Color>,
+    max_saturation: f64,
+    saturation_floor: f64,
+) -> Vec<GrayscaleEntry> {
+    languages_colors
+        .iter()
+        .filter_map(|(language, color)| {
+            let (_, saturation, lightness) = color.to_hsl();
+            if saturation > max_saturation {
+                return None;
+            }
+
+            Some(GrayscaleEntry {
+                language: language.clone(),
+                color: color.clone(),
+                saturation,
+                lightness,
+                suggestion: color.with_saturation(saturation.max(saturation_floor)),
+            })
+        })
+        .collect()
+}
+
+/// Order of the groups of languages that share a color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupOrder {
+    /// Largest group first, groups of the same size by hex.
+    Size,
+    Hex,
+    /// By `Color::perceptual_sort_key`.
+    Perceptual,
+}
+
+impl GroupOrder {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GroupOrder::Size => "size",
+            GroupOrder::Hex => "hex",
+            GroupOrder::Perceptual => "perceptual",
+        }
+    }
+}
+
+impl FromStr for GroupOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "size" => Ok(GroupOrder::Size),
+            "hex" => Ok(GroupOrder::Hex),
+            "perceptual" => Ok(GroupOrder::Perceptual),
+            _ => Err(format!("unknown group order: {}", s)),
+        }
+    }
+}
+
+/// Color that is used by more than one language.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedColor {
+    pub color: Color,
+    /// Names in collation order, see `collation_key`.
+    pub languages: Vec<String>,
+}
+
+/// Colors that are used by more than one of `languages` in `order`.
+pub fn shared_colors(languages: &[(String, Color)], order: GroupOrder) -> Vec<SharedColor> {
+    let mut groups: BTreeMap<&Color, Vec<String>> = BTreeMap::new();
+    for (name, color) in languages {
+        groups.entry(color).or_default().push(name.clone());
+    }
+
+    let mut shared = groups
+        .into_iter()
+        .filter(|(_, languages)| languages.len() > 1)
+        .map(|(color, mut languages)| {
+            languages.sort_by_cached_key(|name| (collation_key(name), name.clone()));
+
+            SharedColor {
+                color: color.clone(),
+                languages,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    match order {
+        GroupOrder::Size => shared.sort_by_cached_key(|group| {
+            (
+                std::cmp::Reverse(group.languages.len()),
+                group.color.as_webcolor(),
+            )
+        }),
+        GroupOrder::Hex => shared.sort_by_cached_key(|group| group.color.as_webcolor()),
+        GroupOrder::Perceptual => {
+            shared.sort_by_cached_key(|group| PerceptualColor(group.color.clone()))
+        }
+    }
+
+    shared
+}
+
+/// Extension that is claimed by several languages.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtensionConflict {
+    /// With the leading dot, e.g. `.h`.
+    pub extension: String,
+    /// Claimants in collation order together with their colors.
+    pub languages: Vec<(String, Option<Color>)>,
+}
+
+/// Extensions claimed by at least `min_claimants` languages but never by
+/// less than two, the most claimed extensions first and extensions with the
+/// same number of claimants in extension order.
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use language_colors::language::{
+///     ExtensionIndex,
+///     LanguageInfo,
+/// };
+/// use language_colors::report::extension_conflicts;
+///
+/// let languages: BTreeMap<String, LanguageInfo> = serde_yaml::from_str(
+///     r##"
+/// C:
+///   type: programming
+///   color: "#555555"
+///   extensions: [".c", ".h"]
+///   ace_mode: c_cpp
+///   language_id: 41
+/// C++:
+///   type: programming
+///   color: "#f34b7d"
+///   extensions: [".cpp", ".h"]
+///   ace_mode: c_cpp
+///   language_id: 43
+/// Objective-C:
+///   type: programming
+///   color: "#438eff"
+///   extensions: [".m", ".h"]
+///   ace_mode: objectivec
+///   language_id: 257
+/// MATLAB:
+///   type: programming
+///   color: "#e16737"
+///   extensions: [".matlab", ".m"]
+///   ace_mode: matlab
+///   language_id: 225
+/// "##,
+/// )
+/// .unwrap();
+/// let colors = BTreeMap::new();
+///
+/// let conflicts = extension_conflicts(&ExtensionIndex::new(&languages), &colors, 2);
+/// let summary = conflicts
+///     .iter()
+///     .map(|conflict| (conflict.extension.as_str(), conflict.languages.len()))
+///     .collect::<Vec<_>>();
+/// assert_eq!(summary, [(".h", 3), (".m", 2)]);
+///
+/// let claimants = conflicts[0]
+///     .languages
+///     .iter()
+///     .map(|(name, _)| name.as_str())
+///     .collect::<Vec<_>>();
+/// assert_eq!(claimants, ["C", "C++", "Objective-C"]);
+///
+/// assert_eq!(extension_conflicts(&ExtensionIndex::new(&languages), &colors, 3).len(), 1);
+/// ```
+pub fn extension_conflicts(
+    index: &ExtensionIndex,
+    languages_colors: &BTreeMap<String, Color>,
+    min_claimants: usize,
+) -> Vec<ExtensionConflict> {
+    let mut conflicts = index
+        .iter()
+        .filter(|(_, claimants)| claimants.len() >= min_claimants.max(2))
+        .map(|(extension, claimants)| {
+            let mut languages = claimants
+                .iter()
+                .map(|name| (name.clone(), languages_colors.get(name).cloned()))
+                .collect::<Vec<_>>();
+            languages.sort_by_cached_key(|(name, _)| (collation_key(name), name.clone()));
+
+            ExtensionConflict {
+                extension: format!(".{}", extension),
+                languages,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    conflicts.sort_by_key(|conflict| std::cmp::Reverse(conflict.languages.len()));
+
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colors(languages: &[(&str, &str)]) -> BTreeMap<String, Color> {
+        languages
+            .iter()
+            .map(|(name, color)| (name.to_string(), color.parse().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn grayscale_keeps_colors_up_to_the_threshold() {
+        let languages = colors(&[
+            ("Gray", "#808080"),
+            ("Near Gray", "#7a8080"),
+            ("Slate", "#70807f"),
+            ("Red", "#ff0000"),
+        ]);
+
+        let names = |max_saturation| {
+            grayscale(&languages, max_saturation, 0.3)
+                .into_iter()
+                .map(|entry| entry.language)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(0.08), ["Gray", "Near Gray", "Slate"]);
+        assert_eq!(names(0.05), ["Gray", "Near Gray"]);
+        assert_eq!(names(0.0), ["Gray"]);
+        assert_eq!(names(1.0).len(), 4);
+    }
+
+    #[test]
+    fn grayscale_reports_saturation_and_lightness() {
+        let entries = grayscale(&colors(&[("Near Gray", "#7a8080")]), 0.08, 0.3);
+
+        assert_eq!(entries.len(), 1);
+        assert!((entries[0].saturation - 6.0 / 250.0).abs() < 1e-9);
+        assert!((entries[0].lightness - 125.0 / 255.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn grayscale_suggests_the_saturation_floor() {
+        let entries = grayscale(&colors(&[("Near Gray", "#7a8080")]), 0.08, 0.3);
+        let entry = &entries[0];
+
+        let (hue, saturation, lightness) = entry.color.to_hsl();
+        let (suggested_hue, suggested_saturation, suggested_lightness) = entry.suggestion.to_hsl();
+
+        assert!((suggested_saturation - 0.3).abs() < 0.01);
+        assert!((suggested_hue - hue).abs() < 1.0);
+        assert!((suggested_lightness - lightness).abs() < 0.01);
+        assert!(saturation < suggested_saturation);
+    }
+
+    #[test]
+    fn shared_colors_are_grouped_in_order() {
+        let languages = [
+            ("Zig", "#0000ff"),
+            ("Go", "#00ff00"),
+            ("Rust", "#ff0000"),
+            ("Nim", "#000080"),
+            ("elm", "#00ff00"),
+            ("Text", "#ffffff"),
+            ("Ada", "#0000ff"),
+            ("C", "#ff0000"),
+            ("Bash", "#00ff00"),
+            ("Odin", "#000080"),
+        ]
+        .iter()
+        .map(|(name, color)| (name.to_string(), color.parse().unwrap()))
+        .collect::<Vec<_>>();
+
+        let groups = |order| {
+            shared_colors(&languages, order)
+                .into_iter()
+                .map(|group| (group.color.as_webcolor(), group.languages))
+                .collect::<Vec<_>>()
+        };
+        let group = |color: &str, names: &[&str]| {
+            (
+                color.to_string(),
+                names
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        let navy = group("#000080", &["Nim", "Odin"]);
+        let blue = group("#0000FF", &["Ada", "Zig"]);
+        let green = group("#00FF00", &["Bash", "elm", "Go"]);
+        let red = group("#FF0000", &["C", "Rust"]);
+
+        assert_eq!(
+            groups(GroupOrder::Size),
+            [green.clone(), navy.clone(), blue.clone(), red.clone()]
+        );
+        assert_eq!(
+            groups(GroupOrder::Hex),
+            [navy.clone(), blue.clone(), green.clone(), red.clone()]
+        );
+        assert_eq!(groups(GroupOrder::Perceptual), [navy, red, green, blue]);
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::color::Color;
+
+/// Seed used for `--sample` when no seed is given.
+pub const DEFAULT_SEED: u64 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    pub size: usize,
+    pub seed: u64,
+}
+
+impl fmt::Display for Sample {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} languages, seed {}", self.size, self.seed)
+    }
+}
+
+/// SplitMix64 from Steele, Lea and Flood, "Fast splittable pseudorandom
+/// number generators" (2014). Small and fully specified, so a seed gives the
+/// same numbers on every platform and with every version of the dependencies.
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Number in `0..bound` using the high bits of a multiplication.
+    pub fn next_below(&mut self, bound: usize) -> usize {
+        ((u128::from(self.next_u64()) * bound as u128) >> 64) as usize
+    }
+}
+
+/// Deterministic random sample of `sample.size` languages. Returns all
+/// languages when there are not more than that.
+pub fn sample(
+    languages_colors: &BTreeMap<String, Color>,
+    sample: Sample,
+) -> BTreeMap<String, Color> {
+    let mut names = languages_colors.keys().collect::<Vec<_>>();
+    let size = sample.size.min(names.len());
+    let mut rng = SplitMix64::new(sample.seed);
+
+    // Partial Fisher-Yates shuffle, the first `size` names are the sample.
+    for i in 0..size {
+        let j = i + rng.next_below(names.len() - i);
+        names.swap(i, j);
+    }
+
+    names[..size]
+        .iter()
+        .map(|name| ((*name).clone(), languages_colors[*name].clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_mix_matches_the_reference() {
+        let mut rng = SplitMix64::new(0);
+
+        assert_eq!(rng.next_u64(), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(rng.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+        assert_eq!(rng.next_u64(), 0x06C4_5D18_8009_454F);
+    }
+
+    #[test]
+    fn next_below_stays_in_bounds() {
+        let mut rng = SplitMix64::new(7);
+
+        for bound in 1..100 {
+            assert!(rng.next_below(bound) < bound);
+        }
+    }
+
+    fn languages(count: usize) -> BTreeMap<String, Color> {
+        (0..count)
+            .map(|i| (format!("Language {:02}", i), Color::from_rgb(i as u8, 0, 0)))
+            .collect()
+    }
+
+    fn names(languages: &BTreeMap<String, Color>) -> Vec<&str> {
+        languages.keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn sample_is_pinned() {
+        let languages = languages(20);
+
+        let sampled = sample(&languages, Sample { size: 4, seed: 42 });
+        assert_eq!(
+            names(&sampled),
+            ["Language 04", "Language 07", "Language 08", "Language 14"]
+        );
+
+        let sampled = sample(
+            &languages,
+            Sample {
+                size: 4,
+                seed: DEFAULT_SEED,
+            },
+        );
+        assert_eq!(
+            names(&sampled),
+            ["Language 02", "Language 09", "Language 17", "Language 19"]
+        );
+    }
+
+    #[test]
+    fn small_inputs_are_kept() {
+        let languages = languages(3);
+
+        let sampled = sample(&languages, Sample { size: 5, seed: 42 });
+        assert_eq!(sampled, languages);
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+
+use crate::color::Color;
+use crate::order::DistanceMetric;
+
+/// Hue rotation in degrees of every nudge step.
+const HUE_STEP: f64 = 6.0;
+/// Lightness shift of every nudge step.
+const LIGHTNESS_STEP: f64 = 0.03;
+/// Number of steps before a color is left as it is.
+const MAX_STEPS: usize = 12;
+/// Directions of hue and lightness tried in every step, in this order.
+const DIRECTIONS: [(f64, f64); 8] = [
+    (1.0, 0.0),
+    (-1.0, 0.0),
+    (0.0, 1.0),
+    (0.0, -1.0),
+    (1.0, 1.0),
+    (-1.0, -1.0),
+    (1.0, -1.0),
+    (-1.0, 1.0),
+];
+
+/// Result of `separate`.
+#[derive(Debug, Default)]
+pub struct Separated {
+    pub colors: BTreeMap<String, Color>,
+    /// Original colors of the languages that got a nudged color.
+    pub adjusted: BTreeMap<String, Color>,
+    /// Languages that are still too close to another language after all
+    /// nudges, they keep their original color.
+    pub unresolved: Vec<String>,
+}
+
+/// Nudges colors apart so no two languages are closer than `min_distance`.
+/// Languages are accepted in name order, a language too close to an already
+/// accepted one gets its hue rotated and its lightness shifted in growing
+/// steps until it clears `min_distance`. The search doesn't use randomness,
+/// so the same colors always give the same result.
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use language_colors::color::Color;
+/// use language_colors::order::DistanceMetric;
+/// use language_colors::separate::separate;
+///
+/// let colors = ["C", "C++", "Objective-C", "Objective-C++"]
+///     .iter()
+///     .map(|name| (name.to_string(), Color::from_webcolor("#555555").unwrap()))
+///     .collect::<BTreeMap<_, _>>();
+///
+/// let separated = separate(&colors, 15.0, DistanceMetric::Euclidean);
+///
+/// assert_eq!(separated.colors["C"].as_webcolor(), "#555555");
+/// assert_eq!(separated.adjusted.len(), 3);
+/// assert!(separated.unresolved.is_empty());
+/// for (a, color_a) in &separated.colors {
+///     for (b, color_b) in &separated.colors {
+///         assert!(a == b || color_a.euclidean_distance(color_b) >= 15.0);
+///     }
+/// }
+///
+/// let again = separate(&colors, 15.0, DistanceMetric::Euclidean);
+/// assert_eq!(again.colors, separated.colors);
+/// ```
+pub fn separate(
+    languages_colors: &BTreeMap<String, Color>,
+    min_distance: f64,
+    metric: DistanceMetric,
+) -> Separated {
+    let mut separated = Separated::default();
+
+    for (name, color) in languages_colors {
+        let is_clear = |candidate: &Color, accepted: &BTreeMap<String, Color>| {
+            accepted
+                .values()
+                .all(|other| metric.distance(candidate, other) >= min_distance)
+        };
+
+        let color = if is_clear(color, &separated.colors) {
+            color.clone()
+        } else {
+            match nudges(color).find(|candidate| is_clear(candidate, &separated.colors)) {
+                Some(nudged) => {
+                    separated.adjusted.insert(name.clone(), color.clone());
+                    nudged
+                }
+                None => {
+                    separated.unresolved.push(name.clone());
+                    color.clone()
+                }
+            }
+        };
+
+        separated.colors.insert(name.clone(), color);
+    }
+
+    separated
+}
+
+/// Candidates for `color` in the order they are tried, each step further
+/// away than the one before.
+fn nudges(color: &Color) -> impl Iterator<Item = Color> + '_ {
+    let (hue, saturation, lightness) = color.to_hsl();
+
+    (1..=MAX_STEPS).flat_map(move |step| {
+        DIRECTIONS
+            .iter()
+            .map(move |(hue_direction, lightness_direction)| {
+                let step = step as f64;
+
+                Color {
+                    alpha: color.alpha,
+                    ..Color::from_hsl(
+                        hue + hue_direction * step * HUE_STEP,
+                        saturation,
+                        (lightness + lightness_direction * step * LIGHTNESS_STEP).clamp(0.0, 1.0),
+                    )
+                }
+            })
+    })
+}
@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+
+use crate::color::{
+    extreme_by,
+    AverageSpace,
+    Color,
+};
+use crate::language::{
+    LanguageInfo,
+    LanguageType,
+};
+use crate::order::DistanceMetric;
+
+pub fn count_by_type(languages: &BTreeMap<String, LanguageInfo>) -> BTreeMap<LanguageType, usize> {
+    let mut counts = BTreeMap::new();
+    for info in languages.values() {
+        *counts.entry(info.language_type.clone()).or_default() += 1;
+    }
+
+    counts
+}
+
+/// Number of languages per type together with the average color of the
+/// languages of that type that have a color, averaged in `space`.
+pub fn type_distribution(
+    languages: &BTreeMap<String, LanguageInfo>,
+    languages_colors: &BTreeMap<String, Color>,
+    space: AverageSpace,
+) -> Vec<(LanguageType, usize, Option<Color>)> {
+    count_by_type(languages)
+        .into_iter()
+        .map(|(language_type, count)| {
+            let colors = languages
+                .iter()
+                .filter(|(_, info)| info.language_type == language_type)
+                .filter_map(|(name, _)| languages_colors.get(name));
+
+            let average = Color::average(colors, space);
+            (language_type, count, average)
+        })
+        .collect()
+}
+
+/// Average distance between all pairs of colors, `None` for less than two
+/// colors.
+pub fn average_distance(
+    languages_colors: &BTreeMap<String, Color>,
+    metric: DistanceMetric,
+) -> Option<f64> {
+    let colors = languages_colors.values().collect::<Vec<_>>();
+
+    let mut total = 0.0;
+    let mut pairs = 0;
+    for (index, color) in colors.iter().enumerate() {
+        for other in &colors[index + 1..] {
+            total += metric.distance(color, other);
+            pairs += 1;
+        }
+    }
+
+    if pairs == 0 {
+        None
+    } else {
+        Some(total / pairs as f64)
+    }
+}
+
+/// Languages with the most extreme colors for spotting odd colors, labelled
+/// for printing. Empty if there are no colors.
+pub fn extremes(languages_colors: &BTreeMap<String, Color>) -> Vec<(&'static str, &str, &Color)> {
+    let extremes = vec![
+        ("most saturated", Color::most_saturated(languages_colors)),
+        ("least saturated", Color::least_saturated(languages_colors)),
+        (
+            "brightest",
+            extreme_by(languages_colors, Color::lightness, true),
+        ),
+        (
+            "darkest",
+            extreme_by(languages_colors, Color::lightness, false),
+        ),
+        (
+            "most chromatic",
+            extreme_by(languages_colors, Color::chroma, true),
+        ),
+        (
+            "least chromatic",
+            extreme_by(languages_colors, Color::chroma, false),
+        ),
+    ];
+
+    extremes
+        .into_iter()
+        .filter_map(|(label, extreme)| extreme.map(|(name, color)| (label, name, color)))
+        .collect()
+}
@@ -0,0 +1,132 @@
+const MAX_DISTANCE: usize = 3;
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Damerau-Levenshtein distance (optimal string alignment) between `a` and
+/// `b`, or `None` if it is larger than `max`. Pairs whose lengths already
+/// differ by more than `max` are rejected without computing the distance.
+pub fn bounded_levenshtein(a: &str, b: &str, max: usize) -> Option<usize> {
+    let (a_length, b_length) = (a.chars().count(), b.chars().count());
+    if a_length.max(b_length) - a_length.min(b_length) > max {
+        return None;
+    }
+
+    let distance = strsim::osa_distance(a, b);
+    if distance > max {
+        None
+    } else {
+        Some(distance)
+    }
+}
+
+/// Up to three candidates within a case-insensitive edit distance of three
+/// from `name`. Candidates starting with `name` come first, then the closest
+/// ones.
+pub fn suggestions<'a, I>(name: &str, candidates: I) -> Vec<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let name = name.to_lowercase();
+
+    let mut matches = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let lowercase = candidate.to_lowercase();
+            let is_prefix = lowercase.starts_with(&name);
+
+            bounded_levenshtein(&name, &lowercase, MAX_DISTANCE)
+                .map(|distance| (!is_prefix, distance, candidate))
+        })
+        .collect::<Vec<_>>();
+
+    matches.sort();
+
+    matches
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, _, candidate)| candidate)
+        .collect()
+}
+
+/// Error message for a language name that could not be found, listing
+/// similar names if there are any.
+pub fn not_found_message<'a, I>(name: &str, candidates: I) -> String
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let suggestions = suggestions(name, candidates);
+
+    if suggestions.is_empty() {
+        format!("Language '{}' not found", name)
+    } else {
+        format!(
+            "Language '{}' not found. Did you mean: {}?",
+            name,
+            suggestions.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LANGUAGES: &[&str] = &["C", "C#", "C++", "Go", "Rust", "Ruby", "RenderScript"];
+
+    #[test]
+    fn distance_counts_edits() {
+        assert_eq!(bounded_levenshtein("rust", "rust", 3), Some(0));
+        assert_eq!(bounded_levenshtein("rust", "rest", 3), Some(1));
+        assert_eq!(bounded_levenshtein("rust", "rus", 3), Some(1));
+        assert_eq!(bounded_levenshtein("", "go", 3), Some(2));
+    }
+
+    #[test]
+    fn distance_counts_transpositions_once() {
+        assert_eq!(bounded_levenshtein("ruts", "rust", 3), Some(1));
+        assert_eq!(bounded_levenshtein("ca", "ac", 3), Some(1));
+        // Optimal string alignment edits every substring at most once, so
+        // this is 3 and not 2 like the unrestricted Damerau-Levenshtein.
+        assert_eq!(bounded_levenshtein("ca", "abc", 3), Some(3));
+    }
+
+    #[test]
+    fn distance_stops_at_bound() {
+        assert_eq!(bounded_levenshtein("rust", "ruby", 2), Some(2));
+        assert_eq!(bounded_levenshtein("rust", "ruby", 1), None);
+        assert_eq!(bounded_levenshtein("go", "renderscript", 3), None);
+        assert_eq!(bounded_levenshtein("abcd", "dcba", 2), None);
+    }
+
+    #[test]
+    fn suggestions_prefer_prefixes() {
+        assert_eq!(
+            suggestions("ru", LANGUAGES.iter().copied()),
+            ["Ruby", "Rust", "C"]
+        );
+        assert_eq!(
+            suggestions("RUTS", LANGUAGES.iter().copied()),
+            ["Rust", "Ruby"]
+        );
+        assert!(suggestions("haskell", LANGUAGES.iter().copied()).is_empty());
+    }
+
+    #[test]
+    fn suggestions_are_limited() {
+        assert_eq!(
+            suggestions("c", LANGUAGES.iter().copied()),
+            ["C", "C#", "C++"]
+        );
+    }
+
+    #[test]
+    fn not_found_message_lists_suggestions() {
+        assert_eq!(
+            not_found_message("Ruts", LANGUAGES.iter().copied()),
+            "Language 'Ruts' not found. Did you mean: Rust, Ruby?"
+        );
+        assert_eq!(
+            not_found_message("Haskell", LANGUAGES.iter().copied()),
+            "Language 'Haskell' not found"
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use std::time::{
+    Duration,
+    Instant,
+};
+
+use serde_json::{
+    json,
+    Map,
+    Value,
+};
+
+/// Wall-clock durations of the phases of a run and a few counters, written
+/// by `--timings`.
+///
+/// ```
+/// use language_colors::timings::Timings;
+///
+/// let mut timings = Timings::new();
+/// timings.start("fetch");
+/// timings.bytes_downloaded = 1024;
+/// timings.start("render");
+/// timings.finish();
+///
+/// let report = timings.to_json();
+/// assert!(report["phases"]["fetch"].as_f64().unwrap() >= 0.0);
+/// assert!(report["phases"]["render"].is_f64());
+/// assert_eq!(report["bytes_downloaded"], 1024);
+/// assert_eq!(report["cache_hit"], false);
+/// assert!(report["cache_age"].is_null());
+/// assert!(report["total"].as_f64().unwrap() >= report["phases"]["render"].as_f64().unwrap());
+/// ```
+#[derive(Debug)]
+pub struct Timings {
+    started: Instant,
+    current: Option<(&'static str, Instant)>,
+    phases: Vec<(&'static str, Duration)>,
+    pub bytes_downloaded: u64,
+    /// Bytes read from `--input` and `--merge` files.
+    pub bytes_read: u64,
+    pub languages_parsed: usize,
+    /// Colored languages left after `--sample`.
+    pub languages_filtered: usize,
+    /// Languages left after `--offset`, `--limit` and `--reverse`.
+    pub languages_rendered: usize,
+    /// Color pairs compared by the nearest color ordering, see
+    /// `progress::Progress::compared`.
+    pub pairs_compared: u64,
+    /// Age of the cached languages if `--offline` read them from the cache.
+    pub cache_age: Option<Duration>,
+}
+
+impl Timings {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            current: None,
+            phases: Vec::new(),
+            bytes_downloaded: 0,
+            bytes_read: 0,
+            languages_parsed: 0,
+            languages_filtered: 0,
+            languages_rendered: 0,
+            pairs_compared: 0,
+            cache_age: None,
+        }
+    }
+
+    /// Finishes the running phase and starts `phase`.
+    pub fn start(&mut self, phase: &'static str) {
+        self.finish();
+        self.current = Some((phase, Instant::now()));
+    }
+
+    /// Finishes the running phase, if there is one.
+    pub fn finish(&mut self) {
+        if let Some((phase, started)) = self.current.take() {
+            self.phases.push((phase, started.elapsed()));
+        }
+    }
+
+    /// Durations in seconds. Phases that ran more than once are summed up.
+    pub fn to_json(&self) -> Value {
+        let mut phases = Map::new();
+        for (phase, duration) in &self.phases {
+            let seconds = phases.get(*phase).and_then(Value::as_f64).unwrap_or(0.0);
+
+            phases.insert(phase.to_string(), json!(seconds + duration.as_secs_f64()));
+        }
+
+        json!({
+            "total": self.started.elapsed().as_secs_f64(),
+            "phases": phases,
+            "bytes_downloaded": self.bytes_downloaded,
+            "bytes_read": self.bytes_read,
+            "cache_hit": self.cache_age.is_some(),
+            "cache_age": self.cache_age.map(|age| age.as_secs_f64()),
+            "languages": {
+                "parsed": self.languages_parsed,
+                "filtered": self.languages_filtered,
+                "rendered": self.languages_rendered,
+            },
+            "pairs_compared": self.pairs_compared,
+        })
+    }
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,168 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::language::LanguageInfo;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    TooLarge(u64),
+    Html,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::TooLarge(max_bytes) => write!(
+                f,
+                "languages are larger than {} bytes, raise the limit with --max-bytes",
+                max_bytes
+            ),
+            ValidationError::Html => write!(
+                f,
+                "languages are an html page instead of yaml, check the source url"
+            ),
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+/// Suspicious data in a single language that doesn't stop the rendering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationWarning {
+    /// Programming language without a color and without a group to take
+    /// one from.
+    MissingColor(String),
+    /// Color that is not written as `#RRGGBB` like in linguist.
+    InvalidColorFormat(String, String),
+    /// Extension listed more than once, compared like in
+    /// `LanguageInfo::has_extension`.
+    DuplicateExtension(String, String),
+    /// Extensions given as an empty list instead of being left out.
+    EmptyExtensions(String),
+}
+
+impl fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationWarning::MissingColor(name) => {
+                write!(f, "language {} has no color", name)
+            }
+            ValidationWarning::InvalidColorFormat(name, color) => write!(
+                f,
+                "language {} has the color {} which is not in the format #RRGGBB",
+                name, color
+            ),
+            ValidationWarning::DuplicateExtension(name, extension) => write!(
+                f,
+                "language {} lists the extension {} more than once",
+                name, extension
+            ),
+            ValidationWarning::EmptyExtensions(name) => {
+                write!(f, "language {} has an empty list of extensions", name)
+            }
+        }
+    }
+}
+
+/// Warnings for the data of the language `name`, empty if nothing looks
+/// suspicious.
+///
+/// ```
+/// use language_colors::language::LanguageInfo;
+/// use language_colors::validate::{
+///     validate_language_info,
+///     ValidationWarning,
+/// };
+///
+/// let info = |yaml: &str| serde_yaml::from_str::<LanguageInfo>(yaml).unwrap();
+///
+/// let rust = info("{type: programming, color: '#dea584', extensions: [.rs], ace_mode: rust, language_id: 327}");
+/// assert!(validate_language_info("Rust", &rust).is_empty());
+///
+/// let broken = info("{type: programming, color: '#abc', extensions: [.b, .B], ace_mode: text, language_id: 1}");
+/// assert_eq!(
+///     validate_language_info("Broken", &broken),
+///     [
+///         ValidationWarning::InvalidColorFormat("Broken".to_string(), "#abc".to_string()),
+///         ValidationWarning::DuplicateExtension("Broken".to_string(), ".B".to_string()),
+///     ]
+/// );
+///
+/// let empty = info("{type: programming, extensions: [], ace_mode: text, language_id: 2}");
+/// assert_eq!(
+///     validate_language_info("Empty", &empty),
+///     [
+///         ValidationWarning::MissingColor("Empty".to_string()),
+///         ValidationWarning::EmptyExtensions("Empty".to_string()),
+///     ]
+/// );
+/// ```
+pub fn validate_language_info(name: &str, info: &LanguageInfo) -> Vec<ValidationWarning> {
+    let mut warnings = Vec::new();
+
+    match info.color {
+        None if info.is_programming() && info.group.is_none() => {
+            warnings.push(ValidationWarning::MissingColor(name.to_string()))
+        }
+        Some(ref color) if !is_linguist_color(color) => warnings.push(
+            ValidationWarning::InvalidColorFormat(name.to_string(), color.clone()),
+        ),
+        _ => {}
+    }
+
+    match info.extensions {
+        Some(ref extensions) if extensions.is_empty() => {
+            warnings.push(ValidationWarning::EmptyExtensions(name.to_string()))
+        }
+        Some(ref extensions) => {
+            let mut seen = Vec::new();
+            for extension in extensions {
+                let normalized = extension.trim_start_matches('.').to_lowercase();
+                if seen.contains(&normalized) {
+                    warnings.push(ValidationWarning::DuplicateExtension(
+                        name.to_string(),
+                        extension.clone(),
+                    ));
+                } else {
+                    seen.push(normalized);
+                }
+            }
+        }
+        None => {}
+    }
+
+    warnings
+}
+
+fn is_linguist_color(color: &str) -> bool {
+    color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+pub fn check_size(size: u64, max_bytes: u64) -> Result<(), ValidationError> {
+    if size > max_bytes {
+        Err(ValidationError::TooLarge(max_bytes))
+    } else {
+        Ok(())
+    }
+}
+
+/// Rejects content types that can't be yaml, a missing content type is
+/// accepted.
+pub fn check_content_type(content_type: Option<&str>) -> Result<(), ValidationError> {
+    match content_type {
+        Some(content_type) if content_type.trim().starts_with("text/html") => {
+            Err(ValidationError::Html)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Cheap sanity check that runs before the yaml parser. An html error page
+/// starts with `<` which never starts a yaml document.
+pub fn check_yaml(body: &[u8]) -> Result<(), ValidationError> {
+    match body.iter().find(|byte| !byte.is_ascii_whitespace()) {
+        Some(b'<') => Err(ValidationError::Html),
+        _ => Ok(()),
+    }
+}
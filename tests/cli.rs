@@ -0,0 +1,1009 @@
+use std::env;
+use std::fs;
+use std::io::{
+    Read,
+    Write,
+};
+use std::net::TcpListener;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::process::{
+    Command,
+    Output,
+};
+use std::thread;
+use std::time::{
+    SystemTime,
+    UNIX_EPOCH,
+};
+
+use language_colors::checksum;
+use language_colors::order::{
+    DistanceMetric,
+    Order,
+};
+use language_colors::render::{
+    render,
+    LanguageColors,
+    RenderOptions,
+};
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/languages.yml");
+
+fn run(args: &[&str]) -> Output {
+    run_input(FIXTURE, args)
+}
+
+fn run_input(input: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_language_colors"))
+        .args(["--quiet", "--input", input])
+        .args(args)
+        .output()
+        .expect("can not run language_colors")
+}
+
+/// Empty directory for the output of one test.
+fn out_dir(test: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("language_colors_{}_{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    dir
+}
+
+fn file_names(dir: &Path) -> Vec<String> {
+    let mut names = fs::read_dir(dir)
+        .expect("can not read output directory")
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    names.sort();
+
+    names
+}
+
+#[test]
+fn paginate_writes_a_page_per_letter() {
+    let dir = out_dir("paginate");
+    let output = run(&["--paginate", "letter", "--out-dir", dir.to_str().unwrap()]);
+    assert!(output.status.success(), "{:?}", output);
+
+    assert_eq!(
+        file_names(&dir),
+        [
+            "a.html",
+            "c.html",
+            "g.html",
+            "h.html",
+            "index.html",
+            "j.html",
+            "m.html",
+            "other.html",
+            "r.html",
+        ]
+    );
+
+    let index = fs::read_to_string(dir.join("index.html")).unwrap();
+    assert!(index.contains(r#"<a href="r.html">R</a> (2)"#));
+
+    let r = fs::read_to_string(dir.join("r.html")).unwrap();
+    assert!(r.contains("Ruby") && r.contains("Rust") && !r.contains("Ada"));
+    assert!(r.contains(r#"href="a.html""#));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn paginate_needs_an_out_dir() {
+    let output = run(&["--paginate", "letter"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--out-dir"));
+}
+
+#[test]
+fn fetch_prints_the_sha256_of_the_input() {
+    let output = run(&["fetch", "--print-sha256"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let expected = checksum::sha256_hex(&fs::read(FIXTURE).unwrap());
+    assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), expected);
+}
+
+#[test]
+fn expect_sha256_accepts_the_matching_hash() {
+    let expected = checksum::sha256_hex(&fs::read(FIXTURE).unwrap());
+    let output = run(&["--expect-sha256", &expected, "--output-format", "json"]);
+
+    assert!(output.status.success(), "{:?}", output);
+}
+
+#[test]
+fn expect_sha256_rejects_a_wrong_hash() {
+    let wrong = "0".repeat(64);
+    let output = run(&["--expect-sha256", &wrong, "--output-format", "json"]);
+
+    assert_eq!(output.status.code(), Some(3));
+    assert!(output.stdout.is_empty());
+
+    let expected = checksum::sha256_hex(&fs::read(FIXTURE).unwrap());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(&format!("expected sha256 {} but got {}", wrong, expected)));
+}
+
+#[test]
+fn json_has_a_linguist_version_field() {
+    let output = run(&["--output-format", "json"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["linguist_version"], serde_json::Value::Null);
+}
+
+#[test]
+fn no_meta_conflicts_with_version_header() {
+    let output = run(&["--no-meta", "--version-header"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+}
+
+#[test]
+fn html_lists_the_languages_in_the_order() {
+    let dir = out_dir("html_order");
+    fs::create_dir_all(&dir).unwrap();
+    let order_file = dir.join("order.txt");
+    fs::write(&order_file, "Rust\nGo\nAda\n").unwrap();
+
+    let output = run(&[
+        "--sections",
+        "name",
+        "--order",
+        "file",
+        "--order-file",
+        order_file.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let html = String::from_utf8(output.stdout).unwrap();
+    let position = |name: &str| html.find(&format!("id=\"language-{}\"", name)).unwrap();
+    assert!(html.contains("<h2>By Order File</h2>"));
+    assert!(position("Rust") < position("Go"));
+    assert!(position("Go") < position("Ada"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn every_format_is_reproducible() {
+    let formats = ["class-map", "csv", "html", "json", "less", "markdown"];
+    let sections = "legend,name,nearest,groups,clusters,colorless,wheel,types,families";
+
+    for format in &formats {
+        for seed in &[None, Some("7")] {
+            let mut args = vec![
+                "--output-format",
+                format,
+                "--sections",
+                sections,
+                "--clusters",
+                "3",
+            ];
+            if let Some(seed) = seed {
+                args.extend(&["--seed", seed]);
+            }
+
+            let first = run(&args);
+            assert!(first.status.success(), "{:?}", first);
+            let second = run(&args);
+
+            assert_eq!(
+                first.stdout, second.stdout,
+                "{} with seed {:?}",
+                format, seed
+            );
+        }
+    }
+}
+
+#[test]
+fn sample_is_pinned_and_recorded() {
+    let output = run(&["--sample", "4", "--seed", "42", "--output-format", "json"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let languages = json["languages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["language"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(languages, ["C", "Go", "HTML", "Markdown"]);
+
+    let metadata = json["metadata"].as_array().unwrap();
+    assert!(metadata.contains(&serde_json::json!("sample: 4 languages, seed 42")));
+}
+
+#[test]
+fn unknown_order_file_names_fail_with_strict() {
+    let dir = out_dir("order_strict");
+    fs::create_dir_all(&dir).unwrap();
+    let order_file = dir.join("order.txt");
+    fs::write(&order_file, "Rust\nCobol\nGo\n").unwrap();
+    let order_file = order_file.to_str().unwrap();
+
+    let args = [
+        "--order",
+        "file",
+        "--order-file",
+        order_file,
+        "--output-format",
+        "csv",
+    ];
+    let output = run(&args);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Language 'Cobol' not found"));
+
+    let output = run(&[&args[..], &["--strict"]].concat());
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn full_lookup_matches_the_golden_file() {
+    let output = run(&["--language", "Rust", "--full"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let full: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let golden = fs::read(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/fixtures/rust_full.json"
+    ))
+    .unwrap();
+    let golden: serde_json::Value = serde_json::from_slice(&golden).unwrap();
+
+    assert_eq!(full, golden);
+}
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+#[test]
+fn merged_name_collisions_warn_and_fail_with_strict() {
+    let collisions = fixture("collisions.yml");
+
+    let output = run(&["--merge", &collisions, "--output-format", "csv"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    for warning in &[
+        r#"languages "Rust" and "rust" only differ in case or whitespace, keeping "rust""#,
+        r#"languages "Go" and "GO" only differ in case or whitespace, keeping "GO""#,
+        r#"languages "go" and "GO" only differ in case or whitespace, keeping "GO""#,
+    ] {
+        assert!(stderr.contains(warning), "{}", stderr);
+    }
+
+    let csv = String::from_utf8(output.stdout).unwrap();
+    assert!(csv.contains("\nrust,#B7410E\n"), "{}", csv);
+    assert!(csv.contains("\nGO,#00FF00\n"), "{}", csv);
+    assert!(!csv.contains("Rust,") && !csv.contains("\nGo,") && !csv.contains("\ngo,"));
+
+    let output = run(&["--merge", &collisions, "--strict"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+
+    let output = run(&["--merge", &collisions, "--dry-run"]);
+    let summary = String::from_utf8(output.stderr).unwrap();
+    assert!(summary.contains("name collisions        3"), "{}", summary);
+}
+
+#[test]
+fn broken_colors_are_skipped_or_fail_with_strict() {
+    let input = fixture("broken_color.yml");
+    let message =
+        "invalid color for language Rust: expected #RGB, #RRGGBB or #RRGGBBAA but got #dea58";
+
+    let output = run_input(&input, &["--output-format", "json"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8_lossy(&output.stderr).contains(message));
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let languages = json["languages"].as_array().unwrap();
+    assert_eq!(languages.len(), 1);
+    assert_eq!(languages[0]["language"], "Go");
+    assert!(json["metadata"]
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!("invalid colors: 1")));
+
+    let output = run_input(&input, &["--output-format", "json", "--strict"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains(message));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn batch_writes_a_file_per_format() {
+    let formats = ["html", "json", "less", "class-map", "markdown", "csv"];
+    let dir = out_dir("batch");
+
+    let mut args = vec!["--out-dir", dir.to_str().unwrap(), "--out-name", "colors"];
+    for format in &formats {
+        args.extend(&["--output-format", format]);
+    }
+    let output = run(&args);
+    assert!(output.status.success(), "{:?}", output);
+
+    assert_eq!(
+        file_names(&dir),
+        [
+            "colors.class-map.json",
+            "colors.csv",
+            "colors.html",
+            "colors.json",
+            "colors.less",
+            "colors.md",
+        ]
+    );
+
+    // Every file is the same as the output of a single format run.
+    for (format, extension) in
+        formats
+            .iter()
+            .zip(&["html", "json", "less", "class-map.json", "md", "csv"])
+    {
+        let file = fs::read(dir.join(format!("colors.{}", extension))).unwrap();
+        assert!(!file.is_empty(), "{}", format);
+
+        let single = run(&["--output-format", format]);
+        assert_eq!(file, single.stdout, "{}", format);
+    }
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn batch_needs_an_out_dir() {
+    let output = run(&["--output-format", "json", "--output-format", "csv"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("needs --out-dir"));
+}
+
+#[test]
+fn outputs_match_the_json_schema() {
+    let output = run(&["schema", "--format", "json-schema"]);
+    assert!(output.status.success(), "{:?}", output);
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let validator = jsonschema::validator_for(&schema).expect("invalid json schema");
+
+    let runs: &[&[&str]] = &[
+        &["--output-format", "json"],
+        &[
+            "--output-format",
+            "json",
+            "--no-meta",
+            "--separate",
+            "--order",
+            "nearest",
+        ],
+        &["--output-format", "class-map"],
+        &["--language", "Rust", "--full"],
+        &["--language", "Text", "--full"],
+    ];
+    for args in runs {
+        let output = run(args);
+        assert!(output.status.success(), "{:?}", output);
+
+        let instance: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let errors = validator
+            .iter_errors(&instance)
+            .map(|error| error.to_string())
+            .collect::<Vec<_>>();
+        assert!(errors.is_empty(), "{:?}: {:?}", args, errors);
+    }
+
+    let mut broken: serde_json::Value =
+        serde_json::from_slice(&run(&["--output-format", "json"]).stdout).unwrap();
+    broken["languages"][0]["color"] = "red".into();
+    assert!(!validator.is_valid(&broken));
+}
+
+#[test]
+fn band_threshold_numbers_the_bands_in_json() {
+    let bands = |threshold: &str| {
+        let output = run(&[
+            "--output-format",
+            "json",
+            "--order",
+            "nearest",
+            "--band-threshold",
+            threshold,
+        ]);
+        assert!(output.status.success(), "{:?}", output);
+
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        json["languages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|language| language["band"].as_u64().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    assert!(bands("0").iter().all(|band| *band == 0));
+
+    let banded = bands("100");
+    assert_eq!(banded[0], 0);
+    assert!(banded
+        .windows(2)
+        .all(|pair| pair[1] == pair[0] || pair[1] == pair[0] + 1));
+    assert!(*banded.last().unwrap() >= 1, "{:?}", banded);
+}
+
+#[test]
+fn library_renders_like_the_cli() {
+    let yaml = fs::read_to_string(FIXTURE).unwrap();
+    let languages = LanguageColors::from_yaml(&yaml).unwrap();
+
+    let runs: &[(&[&str], RenderOptions)] = &[
+        (&[], RenderOptions::default()),
+        (
+            &["--order", "nearest", "--start-language", "Rust"],
+            RenderOptions {
+                order: Order::Nearest,
+                start_language: Some("Rust".to_string()),
+                ..RenderOptions::default()
+            },
+        ),
+    ];
+
+    for (args, options) in runs {
+        for format in &["class-map", "csv", "html", "json", "less", "markdown"] {
+            let mut cli_args = vec!["--no-meta", "--output-format", format];
+            cli_args.extend_from_slice(args);
+
+            let output = run(&cli_args);
+            assert!(output.status.success(), "{:?}", output);
+
+            let mut rendered = render(&languages, format.parse().unwrap(), options);
+            rendered.push(b'\n');
+            assert!(rendered == output.stdout, "{:?}", cli_args);
+        }
+    }
+}
+
+#[test]
+fn merged_sources_override_and_are_recorded() {
+    let fork = fixture("fork.yml");
+
+    let output = run(&["--merge", &fork, "--output-format", "json"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains(
+            r#"language "Go" has id 9000 in a later source but 132 in an earlier one, using 9000"#
+        ),
+        "{}",
+        stderr
+    );
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let color = |name: &str| {
+        json["languages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|language| language["language"] == name)
+            .map(|language| language["color"].clone())
+    };
+    assert_eq!(color("Ruby"), Some("#CC342D".into()));
+    assert_eq!(color("Internal DSL"), Some("#123456".into()));
+    assert_eq!(color("Go"), Some("#00ADD8".into()));
+
+    let metadata = json["metadata"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|line| line.as_str().unwrap())
+        .collect::<Vec<_>>();
+    for (path, sha256) in &[
+        (FIXTURE, checksum::sha256_hex(&fs::read(FIXTURE).unwrap())),
+        (
+            fork.as_str(),
+            checksum::sha256_hex(&fs::read(&fork).unwrap()),
+        ),
+    ] {
+        let line = format!("Fetched from {} (sha256 {})", path, sha256);
+        assert!(metadata.contains(&line.as_str()), "{:?}", metadata);
+    }
+
+    let output = run(&["--merge", &fork, "--strict"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+}
+
+fn run_cached(cache_dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_language_colors"))
+        .arg("--quiet")
+        .arg("--cache-dir")
+        .arg(cache_dir)
+        .args(args)
+        .output()
+        .expect("can not run language_colors")
+}
+
+/// Cache with the fixture as languages fetched `age_days` ago.
+fn synthetic_cache(test: &str, age_days: u64) -> PathBuf {
+    let dir = out_dir(test);
+    fs::create_dir_all(&dir).unwrap();
+
+    let body = fs::read(FIXTURE).unwrap();
+    let fetched_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        - age_days * 24 * 60 * 60;
+    fs::write(dir.join("languages.yml"), &body).unwrap();
+    fs::write(
+        dir.join("languages.json"),
+        serde_json::json!({
+            "url": "https://example.com/languages.yml",
+            "fetched_at": fetched_at,
+            "etag": "\"abc\"",
+            "sha256": checksum::sha256_hex(&body),
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    dir
+}
+
+#[test]
+fn cache_info_describes_the_cache() {
+    let dir = synthetic_cache("cache_info", 40);
+
+    let output = run_cached(&dir, &["cache", "info"]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let sha256 = checksum::sha256_hex(&fs::read(FIXTURE).unwrap());
+    let size = fs::metadata(FIXTURE).unwrap().len();
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!(
+            "path: {}\nsize: {} bytes\nage: 40 days\nurl: \
+             https://example.com/languages.yml\netag: \"abc\"\nsha256: {}\n",
+            dir.join("languages.yml").display(),
+            size,
+            sha256
+        )
+    );
+
+    let output = run_cached(&dir, &["cache", "clear"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(file_names(&dir).is_empty());
+
+    let output = run_cached(&dir, &["cache", "info"]);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        format!("no languages cached in {}\n", dir.display())
+    );
+
+    let output = run_cached(&dir, &["--offline"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn offline_warns_about_a_stale_cache() {
+    let stale = synthetic_cache("cache_stale", 40);
+    let warning = "WARNING: the cached languages are 40 days old";
+
+    let output = run_cached(
+        &stale,
+        &["--offline", "--no-meta", "--output-format", "csv"],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8(output.stderr).unwrap().contains(warning));
+    assert_eq!(
+        output.stdout,
+        run(&["--no-meta", "--output-format", "csv"]).stdout
+    );
+
+    for args in &[
+        &["--offline", "--no-stale-warning"][..],
+        &["--offline", "--stale-after-days", "60"][..],
+    ] {
+        let output = run_cached(&stale, args);
+        assert!(output.status.success(), "{:?}", output);
+        assert!(!String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("WARNING"));
+    }
+
+    let fresh = synthetic_cache("cache_fresh", 2);
+    let output = run_cached(&fresh, &["--offline"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(!String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("WARNING"));
+}
+
+#[test]
+fn fetched_languages_are_cached() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/languages.yml", listener.local_addr().unwrap());
+    let body = fs::read(FIXTURE).unwrap();
+
+    let server = {
+        let body = body.clone();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 4096];
+            let _ = stream.read(&mut request).unwrap();
+
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nETag: \
+                 \"fixture\"\r\nConnection: close\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(&body).unwrap();
+        })
+    };
+
+    let dir = out_dir("cache_fetch");
+    let output = run_cached(&dir, &["--source-url", &url, "fetch", "--print-sha256"]);
+    assert!(output.status.success(), "{:?}", output);
+    server.join().unwrap();
+
+    assert_eq!(fs::read(dir.join("languages.yml")).unwrap(), body);
+
+    let output = run_cached(&dir, &["cache", "info"]);
+    let info = String::from_utf8(output.stdout).unwrap();
+    for line in &[
+        format!("url: {}", url),
+        "etag: \"fixture\"".to_string(),
+        format!("sha256: {}", checksum::sha256_hex(&body)),
+        "age: 0 minutes".to_string(),
+    ] {
+        assert!(info.lines().any(|info| info == line), "{}", info);
+    }
+}
+
+#[test]
+fn split_by_type_writes_a_file_per_type() {
+    let dir = out_dir("split_by");
+    let output = run(&[
+        "--split-by",
+        "type",
+        "--out-dir",
+        dir.to_str().unwrap(),
+        "--output-format",
+        "csv",
+        "--output-format",
+        "less",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    assert_eq!(
+        file_names(&dir),
+        [
+            "all.csv",
+            "all.less",
+            "data.csv",
+            "data.less",
+            "markup.csv",
+            "markup.less",
+            "programming.csv",
+            "programming.less",
+            "prose.csv",
+            "prose.less",
+        ]
+    );
+}
+
+#[test]
+fn split_by_needs_an_out_dir() {
+    let output = run(&["--split-by", "type"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--out-dir"));
+}
+
+#[test]
+fn offline_conflicts_with_other_sources() {
+    let dir = synthetic_cache("cache_conflicts", 0);
+
+    for args in &[
+        &["--offline", "--input", FIXTURE][..],
+        &[
+            "--offline",
+            "--source-url",
+            "http://127.0.0.1:9/languages.yml",
+        ][..],
+    ] {
+        let output = run_cached(&dir, args);
+        assert_eq!(output.status.code(), Some(1), "{:?}", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+    }
+}
+
+#[test]
+fn params_file_replays_a_run() {
+    let json = |args: &[&str]| {
+        let output = run(args);
+        assert!(output.status.success(), "{:?}", output);
+
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let original = json(&[
+        "--output-format",
+        "json",
+        "--order",
+        "nearest",
+        "--start-language",
+        "Rust",
+        "--band-threshold",
+        "100",
+        "--offset",
+        "1",
+        "--limit",
+        "5",
+        "--reverse",
+    ]);
+    let metadata = original["metadata"].as_array().unwrap();
+    assert!(
+        metadata
+            .iter()
+            .all(|line| !line.as_str().unwrap().starts_with("parameters:")),
+        "{:?}",
+        metadata
+    );
+
+    let dir = out_dir("params_file");
+    fs::create_dir_all(&dir).unwrap();
+    let params_file = dir.join("params.json");
+    fs::write(&params_file, original["parameters"].to_string()).unwrap();
+
+    let replayed = json(&[
+        "--output-format",
+        "json",
+        "--params-file",
+        params_file.to_str().unwrap(),
+    ]);
+    assert_eq!(replayed, original);
+
+    let less = String::from_utf8(
+        run(&[
+            "--output-format",
+            "less",
+            "--params-file",
+            params_file.to_str().unwrap(),
+        ])
+        .stdout,
+    )
+    .unwrap();
+    assert_eq!(less.matches("parameters: ").count(), 1, "{}", less);
+}
+
+#[test]
+fn csv_has_metadata_comments_and_distances() {
+    let output = run(&[
+        "--output-format",
+        "csv",
+        "--order",
+        "nearest",
+        "--columns",
+        "language,color,distance",
+        "--limit",
+        "3",
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let csv = String::from_utf8(output.stdout).unwrap();
+    let (comments, rows): (Vec<_>, Vec<_>) = csv.lines().partition(|line| line.starts_with("# "));
+
+    let sha256 = checksum::sha256_hex(&fs::read(FIXTURE).unwrap());
+    assert_eq!(
+        comments[0],
+        format!("# Fetched from {} (sha256 {})", FIXTURE, sha256)
+    );
+    assert!(
+        comments.contains(&"# slice: offset 0, limit 3"),
+        "{:?}",
+        comments
+    );
+    assert!(comments
+        .iter()
+        .any(|line| line.starts_with("# parameters: {")));
+
+    assert_eq!(rows[0], "language,color,distance");
+    assert_eq!(rows.len(), 4);
+    assert!(rows[1].ends_with(','), "{}", rows[1]);
+    for row in &rows[2..] {
+        let distance = row.rsplit(',').next().unwrap();
+        assert!(distance.parse::<f64>().unwrap() > 0.0, "{}", row);
+    }
+
+    let output = run(&["--output-format", "csv", "--no-meta"]);
+    assert!(!String::from_utf8(output.stdout).unwrap().contains("# "));
+}
+
+#[test]
+fn id_finds_the_language() {
+    let output = run(&["--id", "327"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .starts_with("Rust\n"));
+
+    let output = run(&["--id", "327", "--full"]);
+    assert!(output.status.success(), "{:?}", output);
+    let full: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(full["name"], "Rust");
+    assert_eq!(full["language_id"], 327);
+
+    let output = run(&["--language", "Go", "--full"]);
+    let full: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(full["language_id"], 132);
+}
+
+#[test]
+fn unknown_ids_fail() {
+    let output = run(&["--id", "9999"]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("no language with id 9999"));
+}
+
+#[test]
+fn dry_run_reports_duplicate_ids() {
+    let output = run_input(&fixture("duplicate_ids.yml"), &["--dry-run"]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains(r#"language "Rusty" has id 327 which is already used by "Rust""#),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("duplicate language ids 1"), "{}", stderr);
+
+    let output = run_input(&fixture("duplicate_ids.yml"), &["--id", "327"]);
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .starts_with("Rust\n"));
+}
+
+#[test]
+fn dry_run_warnings_do_not_fail() {
+    let output = run_input(&fixture("warnings.yml"), &["--dry-run"]);
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("2 languages, 0 issues, 1 warnings"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn nearest_chain_matches_the_golden_file() {
+    let golden: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(fixture("nearest_chain.json")).unwrap()).unwrap();
+
+    let output = run(&["--no-meta", "--order", "nearest", "--output-format", "json"]);
+    assert!(output.status.success(), "{:?}", output);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json, golden);
+
+    let languages = LanguageColors::from_yaml(&fs::read_to_string(FIXTURE).unwrap()).unwrap();
+    let chain = languages
+        .nearest_chain(None, DistanceMetric::Euclidean)
+        .into_iter()
+        .map(|entry| {
+            serde_json::json!({
+                "language": entry.language,
+                "color": entry.color.as_webcolor(),
+                "distance": entry.distance,
+            })
+        })
+        .collect::<Vec<_>>();
+    let expected = golden["languages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| {
+            serde_json::json!({
+                "language": entry["language"],
+                "color": entry["color"],
+                "distance": entry["distance"],
+            })
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(chain, expected);
+}
+
+#[test]
+fn timings_report_the_run() {
+    let dir = out_dir("timings");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("timings.json");
+
+    let output = run(&[
+        "--output-format",
+        "html",
+        "--sections",
+        "nearest",
+        "--timings",
+        path.to_str().unwrap(),
+    ]);
+    assert!(output.status.success(), "{:?}", output);
+
+    let timings: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    let mut keys = timings
+        .as_object()
+        .unwrap()
+        .keys()
+        .cloned()
+        .collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(
+        keys,
+        [
+            "bytes_downloaded",
+            "bytes_read",
+            "cache_age",
+            "cache_hit",
+            "languages",
+            "pairs_compared",
+            "phases",
+            "total",
+        ]
+    );
+
+    for phase in &["fetch", "parse", "order", "render"] {
+        let seconds = timings["phases"][phase].as_f64().unwrap();
+        assert!(seconds >= 0.0 && seconds <= timings["total"].as_f64().unwrap());
+    }
+    assert_eq!(timings["bytes_downloaded"], 0);
+    assert_eq!(timings["bytes_read"], fs::metadata(FIXTURE).unwrap().len());
+    assert_eq!(timings["cache_hit"], false);
+    assert!(timings["cache_age"].is_null());
+    assert_eq!(timings["languages"]["parsed"], 11);
+    assert_eq!(timings["languages"]["filtered"], 10);
+    assert_eq!(timings["languages"]["rendered"], 10);
+    // The nearest chain compares 9 + 8 + ... + 1 pairs of the 10 colors.
+    assert!(timings["pairs_compared"].as_u64().unwrap() >= 45);
+
+    let cache = synthetic_cache("timings_cache", 40);
+    let output = run_cached(
+        &cache,
+        &[
+            "--offline",
+            "--no-stale-warning",
+            "--timings",
+            path.to_str().unwrap(),
+        ],
+    );
+    assert!(output.status.success(), "{:?}", output);
+
+    let timings: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(timings["cache_hit"], true);
+    let age = timings["cache_age"].as_f64().unwrap();
+    assert!((age - 40.0 * 24.0 * 60.0 * 60.0).abs() < 60.0, "{}", age);
+}